@@ -0,0 +1,31 @@
+use std::process::Command;
+
+/// Embed the git commit the binary is built from as `TVS_NODE_GIT_COMMIT`, for `tvs_node info`
+/// Builds outside a checkout (e.g. from a source tarball) report "unknown" unless the variable
+/// is already set in the build environment
+fn main() {
+    println!("cargo:rerun-if-env-changed=TVS_NODE_GIT_COMMIT");
+    let commit = std::env::var("TVS_NODE_GIT_COMMIT")
+        .ok()
+        .or_else(|| git(&["rev-parse", "--short=12", "HEAD"]))
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=TVS_NODE_GIT_COMMIT={}", commit);
+
+    // Rebuild when HEAD moves, or the branch it points at does, so the commit stays current
+    if let Some(git_dir) = git(&["rev-parse", "--git-dir"]) {
+        println!("cargo:rerun-if-changed={}/HEAD", git_dir);
+        if let Some(branch) = git(&["symbolic-ref", "-q", "HEAD"]) {
+            println!("cargo:rerun-if-changed={}/{}", git_dir, branch);
+        }
+    }
+}
+
+/// Output of a git command, or `None` if git is missing or the command fails
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8(output.stdout).ok()?;
+    Some(stdout.trim().to_string()).filter(|s| !s.is_empty())
+}
@@ -0,0 +1,136 @@
+use std::{net::SocketAddr, time::Instant};
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    middleware::{self, Next},
+    response::Response,
+    Router,
+};
+
+/// Probe and metrics endpoints, kept out of the access log unless asked for; scrapers and
+/// health checks would otherwise drown out the vote traffic
+const PROBE_PATHS: &[&str] = &[
+    "/healthz",
+    "/readyz",
+    "/health",
+    "/metrics",
+    "/debug/pool",
+    "/debug/backend",
+    "/debug/stats",
+];
+
+/// Log one event per response on `router` with method, path, status, latency and client IP
+/// The probe endpoints are logged only with `include_probes`
+pub fn apply(router: Router, include_probes: bool) -> Router {
+    router.layer(middleware::from_fn_with_state(include_probes, log))
+}
+
+async fn log(State(include_probes): State<bool>, request: Request, next: Next) -> Response {
+    // The path alone: query strings can carry vote tokens that don't belong in logs
+    let path = request.uri().path().to_string();
+    if !include_probes && PROBE_PATHS.contains(&path.as_str()) {
+        return next.run(request).await;
+    }
+
+    let method = request.method().clone();
+    let client_ip = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(peer)| peer.ip().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    let started = Instant::now();
+
+    let response = next.run(request).await;
+
+    let status = response.status().as_u16();
+    let latency_ms = started.elapsed().as_secs_f64() * 1000.0;
+    tracing::info!(
+        method = %method,
+        path = %path,
+        status,
+        latency_ms,
+        client_ip = %client_ip,
+        "{} {} {}",
+        method,
+        path,
+        status
+    );
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logging::tests::Capture;
+    use axum::{http::StatusCode, routing::get};
+
+    /// Serve `router` and GET each of `paths`, all on one thread so the capture sees the events
+    fn request_events(router: Router, paths: &[&str]) -> Vec<serde_json::Value> {
+        let events = Capture::json_events(|| {
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap()
+                .block_on(async {
+                    let runner =
+                        crate::vote_server::VoteServerRunner::serve(router, "127.0.0.1", 0)
+                            .await
+                            .unwrap();
+                    for path in paths {
+                        reqwest::get(format!("http://{}{}", runner.local_addr(), path))
+                            .await
+                            .unwrap();
+                    }
+                    runner.shutdown().await.unwrap();
+                })
+        });
+        events
+            .into_iter()
+            .filter(|event| event["target"] == module_path!().trim_end_matches("::tests"))
+            .collect()
+    }
+
+    fn routes() -> Router {
+        Router::new()
+            .route("/votes/active", get(|| async { "[]" }))
+            .route(
+                "/cast_vote/missing",
+                get(|| async { StatusCode::NOT_FOUND }),
+            )
+            .route("/healthz", get(|| async { "ok" }))
+    }
+
+    #[test]
+    fn test_request_is_logged_with_fields() {
+        let events = request_events(apply(routes(), false), &["/votes/active?token=secret"]);
+
+        assert_eq!(events.len(), 1);
+        let fields = &events[0]["fields"];
+        assert_eq!(events[0]["level"], "INFO");
+        assert_eq!(fields["method"], "GET");
+        assert_eq!(fields["path"], "/votes/active");
+        assert_eq!(fields["status"], 200);
+        assert_eq!(fields["client_ip"], "127.0.0.1");
+        assert!(fields["latency_ms"].as_f64().unwrap() >= 0.0);
+        assert_eq!(fields["message"], "GET /votes/active 200");
+    }
+
+    #[test]
+    fn test_error_status_is_logged() {
+        let events = request_events(apply(routes(), false), &["/cast_vote/missing"]);
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["fields"]["status"], 404);
+    }
+
+    #[test]
+    fn test_probes_are_left_out_unless_included() {
+        let excluded = request_events(apply(routes(), false), &["/healthz", "/votes/active"]);
+        assert_eq!(excluded.len(), 1);
+        assert_eq!(excluded[0]["fields"]["path"], "/votes/active");
+
+        let included = request_events(apply(routes(), true), &["/healthz"]);
+        assert_eq!(included.len(), 1);
+        assert_eq!(included[0]["fields"]["path"], "/healthz");
+    }
+}
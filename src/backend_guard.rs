@@ -0,0 +1,384 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{header, HeaderValue, Method, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    Router,
+};
+
+use crate::{config::PersistenceConfig, vote_server::VoteBackend};
+
+/// Longest wait between retries of one read; a blip that outlasts this isn't one
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// How long a failed request waits for the backend check that decides whether the failure
+/// was the backend's; a backend that can't answer in this time counts as failing
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Where the breaker stands, as reported by `CircuitBreaker::state`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakerState {
+    /// Requests go through; counting consecutive failures
+    Closed,
+    /// Too many consecutive failures; requests get 503 until the cooldown ends
+    Open,
+    /// Cooldown over; one trial request decides whether to close or reopen
+    HalfOpen,
+}
+
+/// Consecutive-failure circuit breaker in front of the vote backend
+/// Time is passed in so the transitions can be tested without sleeping
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    threshold: u32,
+    cooldown: Duration,
+    failures: u32,
+    open_until: Option<Instant>,
+    /// When the half-open trial was let through; a trial that never reports back (e.g. the
+    /// client hung up) stops blocking others after another cooldown
+    trial_started: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    /// Open after `threshold` consecutive failures for `cooldown`; a threshold of 0 never opens
+    pub fn new(threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            threshold,
+            cooldown,
+            failures: 0,
+            open_until: None,
+            trial_started: None,
+        }
+    }
+
+    /// Whether the breaker can open at all; a threshold of 0 turns it off
+    pub fn is_enabled(&self) -> bool {
+        self.threshold > 0
+    }
+
+    pub fn state(&self, now: Instant) -> BreakerState {
+        match self.open_until {
+            None => BreakerState::Closed,
+            Some(until) if now < until => BreakerState::Open,
+            Some(_) => BreakerState::HalfOpen,
+        }
+    }
+
+    /// Let a request through at `now`, or return how long until the breaker will try again
+    /// Once the cooldown is over, only one trial request is let through at a time
+    pub fn admit(&mut self, now: Instant) -> Result<(), Duration> {
+        match self.state(now) {
+            BreakerState::Closed => Ok(()),
+            BreakerState::Open => Err(self.open_until.unwrap_or(now).duration_since(now)),
+            BreakerState::HalfOpen => match self.trial_started {
+                Some(started) if now < started + self.cooldown => {
+                    Err(started + self.cooldown - now)
+                }
+                _ => {
+                    self.trial_started = Some(now);
+                    Ok(())
+                }
+            },
+        }
+    }
+
+    /// The backend answered; closes a half-open breaker
+    pub fn record_success(&mut self) {
+        self.failures = 0;
+        self.open_until = None;
+        self.trial_started = None;
+    }
+
+    /// The backend failed at `now`; opens the breaker at the threshold, or again after a failed trial
+    pub fn record_failure(&mut self, now: Instant) {
+        self.trial_started = None;
+        self.failures = self.failures.saturating_add(1);
+        let tripped = self.threshold > 0 && self.failures >= self.threshold;
+        if tripped || self.open_until.is_some() {
+            self.open_until = Some(now + self.cooldown);
+        }
+    }
+}
+
+/// Retry and breaker settings for the vote routes, from `persistence`
+#[derive(Clone)]
+struct BackendGuard {
+    retries: u32,
+    retry_delay_ms: u64,
+    breaker: Arc<Mutex<CircuitBreaker>>,
+    /// Checked after a 5xx, so only a failure of the backend itself counts against it
+    backend: VoteBackend,
+}
+
+impl BackendGuard {
+    fn breaker(&self) -> std::sync::MutexGuard<'_, CircuitBreaker> {
+        // A poisoned lock only means another request panicked mid-update; the state is still usable
+        self.breaker.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Whether a 5xx came from `backend` failing rather than from the request
+    async fn backend_failed(&self) -> bool {
+        !matches!(
+            tokio::time::timeout(CONFIRM_TIMEOUT, self.backend.check()).await,
+            Ok(Ok(()))
+        )
+    }
+}
+
+/// Retry failed reads on `router` and stop calling `backend` once it keeps failing
+/// Body-less `GET`/`HEAD` requests that get a 5xx are retried up to `backend_read_retries`
+/// times with doubling delays. A 5xx only counts towards the breaker when `backend` fails
+/// its own check too, so a client can't trip it with requests that fail for other reasons;
+/// after `breaker_failure_threshold` such failures in a row every request gets 503 for
+/// `breaker_cooldown_secs`, then one trial request decides whether to resume
+// Only wrapped around a postgres backend; the in-memory one has nothing to protect
+#[cfg_attr(not(feature = "postgres"), allow(dead_code))]
+pub fn apply(router: Router, config: &PersistenceConfig, backend: VoteBackend) -> Router {
+    let guard = BackendGuard {
+        retries: config.backend_read_retries,
+        retry_delay_ms: config.backend_retry_delay_ms,
+        breaker: Arc::new(Mutex::new(CircuitBreaker::new(
+            config.breaker_failure_threshold,
+            Duration::from_secs(config.breaker_cooldown_secs),
+        ))),
+        backend,
+    };
+    router.layer(middleware::from_fn_with_state(guard, guarded))
+}
+
+async fn guarded(State(guard): State<BackendGuard>, request: Request, next: Next) -> Response {
+    if let Err(wait) = guard.breaker().admit(Instant::now()) {
+        let retry_after = wait.as_secs_f64().ceil().max(1.0) as u64;
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            [(header::RETRY_AFTER, HeaderValue::from(retry_after))],
+            "vote backend unavailable; not retrying until it recovers",
+        )
+            .into_response();
+    }
+
+    let response = if is_retryable_read(&request) {
+        let (parts, _) = request.into_parts();
+        let mut attempt = 0;
+        loop {
+            let response = next
+                .clone()
+                .run(Request::from_parts(parts.clone(), Body::empty()))
+                .await;
+            if !response.status().is_server_error() || attempt >= guard.retries {
+                break response;
+            }
+            attempt += 1;
+            tokio::time::sleep(retry_delay(guard.retry_delay_ms, attempt)).await;
+        }
+    } else {
+        next.run(request).await
+    };
+
+    if !guard.breaker().is_enabled() {
+        return response;
+    }
+    if response.status().is_server_error() && guard.backend_failed().await {
+        guard.breaker().record_failure(Instant::now());
+    } else {
+        guard.breaker().record_success();
+    }
+    response
+}
+
+/// Reads can be repeated without side effects; one with a body can't be replayed, as it is streamed
+fn is_retryable_read(request: &Request) -> bool {
+    let empty_body = request
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .is_none_or(|length| length == "0");
+    matches!(*request.method(), Method::GET | Method::HEAD)
+        && empty_body
+        && !request.headers().contains_key(header::TRANSFER_ENCODING)
+}
+
+/// Backoff before retry `attempt` (1-based): base, 2x base, 4x base, ... capped at 1s
+fn retry_delay(base_delay_ms: u64, attempt: u32) -> Duration {
+    let factor = 1u64.checked_shl(attempt - 1).unwrap_or(u64::MAX);
+    Duration::from_millis(base_delay_ms.saturating_mul(factor)).min(MAX_RETRY_DELAY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::get;
+
+    #[test]
+    fn test_breaker_opens_after_consecutive_failures() {
+        let now = Instant::now();
+        let mut breaker = CircuitBreaker::new(3, Duration::from_secs(30));
+
+        breaker.record_failure(now);
+        breaker.record_failure(now);
+        // A success in between resets the count
+        breaker.record_success();
+        breaker.record_failure(now);
+        breaker.record_failure(now);
+        assert_eq!(breaker.state(now), BreakerState::Closed);
+        assert!(breaker.admit(now).is_ok());
+
+        breaker.record_failure(now);
+        assert_eq!(breaker.state(now), BreakerState::Open);
+        assert_eq!(breaker.admit(now), Err(Duration::from_secs(30)));
+        let later = now + Duration::from_secs(10);
+        assert_eq!(breaker.admit(later), Err(Duration::from_secs(20)));
+    }
+
+    #[test]
+    fn test_breaker_half_open_trial() {
+        let now = Instant::now();
+        let mut breaker = CircuitBreaker::new(1, Duration::from_secs(30));
+        breaker.record_failure(now);
+
+        // After the cooldown a single trial goes through
+        let after = now + Duration::from_secs(30);
+        assert_eq!(breaker.state(after), BreakerState::HalfOpen);
+        assert!(breaker.admit(after).is_ok());
+        assert!(breaker.admit(after).is_err());
+        // A trial that never reports back stops blocking once another cooldown has passed
+        let abandoned = after + Duration::from_secs(30);
+        assert!(breaker.admit(abandoned).is_ok());
+        let after = abandoned;
+
+        // A failed trial reopens for another cooldown
+        breaker.record_failure(after);
+        assert_eq!(breaker.state(after), BreakerState::Open);
+        let retry = after + Duration::from_secs(30);
+        assert!(breaker.admit(retry).is_ok());
+
+        // A successful one closes it
+        breaker.record_success();
+        assert_eq!(breaker.state(retry), BreakerState::Closed);
+        assert!(breaker.admit(retry).is_ok());
+        assert!(breaker.admit(retry).is_ok());
+    }
+
+    #[test]
+    fn test_breaker_with_zero_threshold_never_opens() {
+        let now = Instant::now();
+        let mut breaker = CircuitBreaker::new(0, Duration::from_secs(30));
+        for _ in 0..100 {
+            breaker.record_failure(now);
+        }
+        assert_eq!(breaker.state(now), BreakerState::Closed);
+    }
+
+    #[test]
+    fn test_retry_delay_doubles_and_caps() {
+        assert_eq!(retry_delay(50, 1), Duration::from_millis(50));
+        assert_eq!(retry_delay(50, 2), Duration::from_millis(100));
+        assert_eq!(retry_delay(50, 3), Duration::from_millis(200));
+        assert_eq!(retry_delay(50, 40), MAX_RETRY_DELAY);
+    }
+
+    #[cfg(feature = "postgres")]
+    #[tokio::test]
+    async fn test_reads_are_retried_and_breaker_serves_503() {
+        use diesel::{r2d2::ConnectionManager, PgConnection};
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        // Nothing listens on port 1, so the backend check confirms every failure
+        let manager =
+            ConnectionManager::<PgConnection>::new("postgres://tvs@127.0.0.1:1/tfs_tvs_db");
+        let pool = tfs_postgres::DbPool::builder()
+            .connection_timeout(Duration::from_millis(500))
+            .build_unchecked(manager);
+        let calls = Arc::new(AtomicU32::new(0));
+        let counter = calls.clone();
+        let config = PersistenceConfig {
+            backend_read_retries: 2,
+            backend_retry_delay_ms: 1,
+            breaker_failure_threshold: 2,
+            ..PersistenceConfig::default()
+        };
+        let router = apply(
+            Router::new()
+                // Fails twice, then recovers: one request with two retries
+                .route(
+                    "/votes/active",
+                    get(move || async move {
+                        match counter.fetch_add(1, Ordering::SeqCst) {
+                            0 | 1 => StatusCode::INTERNAL_SERVER_ERROR,
+                            _ => StatusCode::OK,
+                        }
+                    }),
+                )
+                .route("/down", get(|| async { StatusCode::INTERNAL_SERVER_ERROR })),
+            &config,
+            VoteBackend::Postgres(pool),
+        );
+        let runner = crate::vote_server::VoteServerRunner::serve(router, "127.0.0.1", 0)
+            .await
+            .unwrap();
+        let base = format!("http://{}", runner.local_addr());
+
+        let read = reqwest::get(format!("{}/votes/active", base))
+            .await
+            .unwrap();
+        assert_eq!(read.status(), reqwest::StatusCode::OK);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        for _ in 0..2 {
+            let down = reqwest::get(format!("{}/down", base)).await.unwrap();
+            assert_eq!(down.status(), reqwest::StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        // Two failed requests in a row trip the breaker, so the healthy route is refused too
+        let refused = reqwest::get(format!("{}/votes/active", base))
+            .await
+            .unwrap();
+        assert_eq!(refused.status(), reqwest::StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(refused.headers()["retry-after"], "30");
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        runner.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_failures_with_a_healthy_backend_do_not_trip_the_breaker() {
+        let config = PersistenceConfig {
+            backend_read_retries: 0,
+            breaker_failure_threshold: 1,
+            ..PersistenceConfig::default()
+        };
+        let router = apply(
+            Router::new().route("/down", get(|| async { StatusCode::INTERNAL_SERVER_ERROR })),
+            &config,
+            VoteBackend::Ephemeral,
+        );
+        let runner = crate::vote_server::VoteServerRunner::serve(router, "127.0.0.1", 0)
+            .await
+            .unwrap();
+        let url = format!("http://{}/down", runner.local_addr());
+
+        // The handler fails, but the backend answers its check, so the failures are the
+        // requests' own
+        for _ in 0..3 {
+            let response = reqwest::get(&url).await.unwrap();
+            assert_eq!(response.status(), reqwest::StatusCode::INTERNAL_SERVER_ERROR);
+        }
+
+        runner.shutdown().await.unwrap();
+    }
+
+    #[test]
+    fn test_breaker_is_off_by_default() {
+        let config = PersistenceConfig::default();
+        assert_eq!(config.breaker_failure_threshold, 0);
+        let breaker = CircuitBreaker::new(
+            config.breaker_failure_threshold,
+            Duration::from_secs(config.breaker_cooldown_secs),
+        );
+        assert!(!breaker.is_enabled());
+    }
+}
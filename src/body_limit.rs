@@ -0,0 +1,106 @@
+use axum::{
+    extract::{DefaultBodyLimit, Request, State},
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    Router,
+};
+
+/// Reject request bodies over `max_bytes` on `router` with 413
+/// A `Content-Length` over the limit is refused before the body is read; a chunked body is
+/// cut off by the extractors once it passes the limit
+pub fn apply(router: Router, max_bytes: usize) -> Router {
+    router
+        .layer(DefaultBodyLimit::max(max_bytes))
+        .layer(middleware::from_fn_with_state(max_bytes, limit))
+}
+
+async fn limit(State(max_bytes): State<usize>, request: Request, next: Next) -> Response {
+    let declared = request
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|length| length.to_str().ok())
+        .and_then(|length| length.parse::<u64>().ok());
+    match declared {
+        Some(length) if length > max_bytes as u64 => (
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!("request body exceeds {} bytes", max_bytes),
+        )
+            .into_response(),
+        _ => next.run(request).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::{get, post};
+
+    /// Serve an echo route limited to `max_bytes` plus an unlimited probe, returning the base URL
+    async fn serve(max_bytes: usize) -> (crate::vote_server::VoteServerRunner, String) {
+        let limited = apply(
+            Router::new().route("/vote", post(|body: String| async move { body })),
+            max_bytes,
+        );
+        let router = limited.route("/healthz", post(|body: String| async move { body }));
+        let runner = crate::vote_server::VoteServerRunner::serve(router, "127.0.0.1", 0)
+            .await
+            .unwrap();
+        let base = format!("http://{}", runner.local_addr());
+        (runner, base)
+    }
+
+    #[tokio::test]
+    async fn test_oversize_body_is_rejected() {
+        let (runner, base) = serve(16).await;
+        let client = reqwest::Client::new();
+
+        let small = client
+            .post(format!("{}/vote", base))
+            .body("a".repeat(16))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(small.status(), reqwest::StatusCode::OK);
+
+        let large = client
+            .post(format!("{}/vote", base))
+            .body("a".repeat(17))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(large.status(), reqwest::StatusCode::PAYLOAD_TOO_LARGE);
+
+        runner.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_probes_are_not_limited() {
+        let (runner, base) = serve(16).await;
+
+        let response = reqwest::Client::new()
+            .post(format!("{}/healthz", base))
+            .body("a".repeat(1024))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+
+        runner.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_bodyless_requests_pass() {
+        let router = apply(Router::new().route("/votes", get(|| async { "[]" })), 16);
+        let runner = crate::vote_server::VoteServerRunner::serve(router, "127.0.0.1", 0)
+            .await
+            .unwrap();
+
+        let response = reqwest::get(format!("http://{}/votes", runner.local_addr()))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+
+        runner.shutdown().await.unwrap();
+    }
+}
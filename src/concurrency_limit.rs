@@ -0,0 +1,175 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    Router,
+};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::config::TvsServerConfig;
+
+/// Slots for requests in flight at once; a request holds its permit until the response is ready
+#[derive(Clone)]
+pub struct ConcurrencyLimiter {
+    permits: Arc<Semaphore>,
+}
+
+/// How a shed request is answered, from `tvs.max_connections_status` and
+/// `tvs.max_connections_retry_after_secs`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shed {
+    pub status: StatusCode,
+    /// Slots free up as soon as in-flight requests finish, so this is usually short
+    pub retry_after_secs: u64,
+}
+
+impl Default for Shed {
+    fn default() -> Self {
+        Self {
+            status: StatusCode::SERVICE_UNAVAILABLE,
+            retry_after_secs: 1,
+        }
+    }
+}
+
+impl Shed {
+    pub fn from_config(tvs: &TvsServerConfig) -> Self {
+        // Validation keeps the status to 4xx and 5xx
+        Self {
+            status: StatusCode::from_u16(tvs.max_connections_status)
+                .unwrap_or(StatusCode::SERVICE_UNAVAILABLE),
+            retry_after_secs: tvs.max_connections_retry_after_secs,
+        }
+    }
+}
+
+impl ConcurrencyLimiter {
+    pub fn new(max: usize) -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(max)),
+        }
+    }
+
+    /// Take a slot, or None when all of them are in use
+    pub fn try_acquire(&self) -> Option<OwnedSemaphorePermit> {
+        self.permits.clone().try_acquire_owned().ok()
+    }
+}
+
+/// Serve at most `max` requests at once on `router`, answering the rest per `shed` instead
+/// of queueing them
+pub fn apply(router: Router, max: usize, shed: Shed) -> Router {
+    router.layer(middleware::from_fn_with_state(
+        (ConcurrencyLimiter::new(max), shed),
+        limit,
+    ))
+}
+
+async fn limit(
+    State((limiter, shed)): State<(ConcurrencyLimiter, Shed)>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(_permit) = limiter.try_acquire() else {
+        return (
+            shed.status,
+            [(header::RETRY_AFTER, shed.retry_after_secs.to_string())],
+            "too many concurrent requests",
+        )
+            .into_response();
+    };
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::get;
+    use tokio::sync::Notify;
+
+    #[test]
+    fn test_limiter_accepts_up_to_max() {
+        let limiter = ConcurrencyLimiter::new(2);
+        let first = limiter.try_acquire().unwrap();
+        let _second = limiter.try_acquire().unwrap();
+        assert!(limiter.try_acquire().is_none());
+
+        // A finished request gives its slot back
+        drop(first);
+        assert!(limiter.try_acquire().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_overflow_request_is_shed() {
+        let release = Arc::new(Notify::new());
+        let held = release.clone();
+        let router = apply(
+            Router::new().route(
+                "/slow",
+                get(move || {
+                    let held = held.clone();
+                    async move {
+                        held.notified().await;
+                        "done"
+                    }
+                }),
+            ),
+            2,
+            Shed::default(),
+        );
+        let runner = crate::vote_server::VoteServerRunner::serve(router, "127.0.0.1", 0)
+            .await
+            .unwrap();
+        let url = format!("http://{}/slow", runner.local_addr());
+
+        let in_flight: Vec<_> = (0..2)
+            .map(|_| tokio::spawn(reqwest::get(url.clone())))
+            .collect();
+        // Give both requests time to reach the handler and take their slots
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+        let shed = reqwest::get(&url).await.unwrap();
+        assert_eq!(shed.status(), reqwest::StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(shed.headers()["retry-after"], "1");
+
+        release.notify_waiters();
+        for request in in_flight {
+            assert_eq!(
+                request.await.unwrap().unwrap().status(),
+                reqwest::StatusCode::OK
+            );
+        }
+
+        let after = tokio::spawn(reqwest::get(url.clone()));
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        release.notify_waiters();
+        assert_eq!(
+            after.await.unwrap().unwrap().status(),
+            reqwest::StatusCode::OK
+        );
+
+        runner.shutdown().await.unwrap();
+    }
+
+    #[test]
+    fn test_shed_from_config() {
+        let tvs = TvsServerConfig::default();
+        assert_eq!(Shed::from_config(&tvs), Shed::default());
+
+        let tvs = TvsServerConfig {
+            max_connections_status: 429,
+            max_connections_retry_after_secs: 5,
+            ..TvsServerConfig::default()
+        };
+        assert_eq!(
+            Shed::from_config(&tvs),
+            Shed {
+                status: StatusCode::TOO_MANY_REQUESTS,
+                retry_after_secs: 5,
+            }
+        );
+    }
+}
@@ -1,160 +1,3372 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use tfs_http::app_config::AppConfig;
 
 /// Unified configuration for TVS Node
 /// Combines TFS HTTP configuration with TVS-specific settings
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TvsNodeConfig {
     /// TFS HTTP server configuration (ports, node info, logging, etc.)
     #[serde(flatten)]
+    #[schemars(with = "crate::schema::AppConfigSchema")]
     pub tfs: AppConfig,
 
     /// TVS vote server configuration (optional)
     pub tvs: Option<TvsServerConfig>,
+
+    /// Process runtime tuning (optional)
+    pub runtime: Option<RuntimeConfig>,
+
+    /// Persistence backend tuning (optional, used by the postgres feature)
+    pub persistence: Option<PersistenceConfig>,
+
+    /// `/readyz` debouncing (optional)
+    pub readiness: Option<ReadinessConfig>,
+
+    /// Log output format (default: text). `LOG_FORMAT` overrides it.
+    #[serde(default)]
+    pub log_format: LogFormat,
+
+    /// Serve the admin frontend on its dedicated port (default: true when the
+    /// `admin-frontend` feature is compiled in). Has no effect without the feature.
+    pub admin_frontend_enabled: Option<bool>,
+
+    /// Skip the startup summary printed once the node is up (default: false). Also set by
+    /// `--quiet`
+    #[serde(default)]
+    pub quiet: bool,
+
+    /// Log how long each startup phase took, not just the total (default: false). Also set
+    /// by `--verbose-startup`
+    #[serde(default)]
+    pub verbose_startup: bool,
+
+    /// Refuse to start unless `node_name` is set, in the config or via `NODE_NAME`
+    /// (default: false). The Postgres schema is named after the node id, so a node that
+    /// falls back to the default name can't find its schema again after a restart
+    #[serde(default)]
+    pub require_node_name: bool,
+
+    /// Stop startup when the vote services can't be registered (default: true). When false
+    /// the failure is logged and the node keeps running TFS with the vote server disabled
+    #[serde(default = "default_vote_service_required")]
+    pub vote_service_required: bool,
 }
 
 /// Configuration for the TVS vote server
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TvsServerConfig {
-    /// Port for the vote server (default: 8090)
+    /// Port for the vote server (default: 8090). 0 binds a free port picked by the OS
     #[serde(default = "default_vote_port")]
     pub vote_port: u16,
 
-    /// Host for the vote server (default: "127.0.0.1")
-    #[serde(default = "default_vote_host")]
-    pub vote_host: String,
+    /// Host for the vote server (default: "127.0.0.1"). `unix:/path/to/socket` serves on a
+    /// Unix domain socket instead of TCP, and `vote_port` is then unused
+    #[serde(default = "default_vote_host")]
+    pub vote_host: String,
+
+    /// Address the vote server binds, when it differs from the `vote_host` clients are told
+    /// to use (e.g. `0.0.0.0` behind NAT). Unset binds `vote_host`
+    #[serde(default)]
+    pub bind_address: Option<String>,
+
+    /// Address family to bind when a listener host resolves to both (default: any, which
+    /// takes the first address the resolver returns)
+    #[serde(default)]
+    pub ip_version: IpVersion,
+
+    /// Accept only IPv6 clients on an IPv6 listener such as `::` (default: false, so IPv4
+    /// clients connect too, as IPv4-mapped addresses). Set on the socket either way, so
+    /// the result doesn't depend on the OS default
+    #[serde(default)]
+    pub ipv6_only: bool,
+
+    /// Enable the vote server (default: true if tvs section exists)
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+
+    /// Base URL for generated vote URLs (default: "http://localhost:8081/vote").
+    /// `TVS_ROOT_URL` takes precedence when set.
+    #[serde(default)]
+    pub root_url: Option<String>,
+
+    /// Seconds to wait for in-flight requests to drain on shutdown before forcing exit (default: 30)
+    #[serde(default = "default_shutdown_timeout_secs")]
+    pub shutdown_timeout_secs: u64,
+
+    /// Alias of `runtime.max_uptime_secs`: shut down gracefully after this many seconds so
+    /// the node is restarted. When both are set the shorter one applies
+    #[serde(default)]
+    pub max_lifetime_secs: Option<u64>,
+
+    /// Seconds to wait for the TFS node to answer before binding the vote port (default: 30)
+    #[serde(default = "default_tfs_ready_timeout_secs")]
+    pub tfs_ready_timeout_secs: u64,
+
+    /// Listeners to serve the vote routes on. When empty, a single listener on
+    /// `vote_host`:`vote_port` is used
+    #[serde(default)]
+    pub listeners: Vec<ListenerConfig>,
+
+    /// Serve the vote routes over HTTPS with this certificate and key. Unset serves plain HTTP
+    #[serde(default)]
+    pub tls: Option<TlsConfig>,
+
+    /// Browser origins allowed to call the vote server cross-origin, e.g.
+    /// `https://vote.example.com`; `*` allows any. Empty sends no CORS headers (default)
+    #[serde(default)]
+    pub cors_allowed_origins: Vec<String>,
+
+    /// Per-client-IP limit on the vote routes; the health and metrics endpoints are exempt.
+    /// Unset means no limit
+    #[serde(default)]
+    pub rate_limit: Option<RateLimitConfig>,
+
+    /// Largest request body the vote routes accept, in bytes; bigger ones get 413. The health
+    /// and metrics endpoints are exempt (default: 65536). `null` leaves only axum's 2 MiB cap
+    #[serde(default = "default_max_body_bytes")]
+    pub max_body_bytes: Option<usize>,
+
+    /// Most vote requests served at once; more are refused with `max_connections_status`
+    /// instead of waiting. The health and metrics endpoints are exempt. Unset means no limit
+    #[serde(default)]
+    pub max_connections: Option<usize>,
+
+    /// Status for requests refused by `max_connections`, 4xx or 5xx (default: 503)
+    #[serde(default = "default_max_connections_status")]
+    pub max_connections_status: u16,
+
+    /// `Retry-After` seconds sent with requests refused by `max_connections` (default: 1)
+    #[serde(default = "default_max_connections_retry_after_secs")]
+    pub max_connections_retry_after_secs: u64,
+
+    /// Seconds a vote connection may sit idle before TCP keepalive probes start, so NATs and
+    /// load balancers don't silently drop it. Unset leaves keepalive off (default)
+    #[serde(default)]
+    pub tcp_keepalive_secs: Option<u64>,
+
+    /// Set `TCP_NODELAY` on vote connections, sending small responses without waiting to
+    /// batch them (default: false)
+    #[serde(default)]
+    pub tcp_nodelay: bool,
+
+    /// Most connections one client IP may hold open to the vote listeners at once; a new
+    /// connection over the limit is closed as soon as it is accepted. Unset means no limit
+    #[serde(default)]
+    pub max_connections_per_ip: Option<usize>,
+
+    /// Proxy or load balancer addresses exempt from `max_connections_per_ip`, as their
+    /// connections carry many clients (default: none)
+    #[serde(default)]
+    pub trusted_proxies: Vec<String>,
+
+    /// When a listener's port is taken, serve on the next free port instead of failing
+    /// (default: false). Also set by `--auto-port`
+    #[serde(default)]
+    pub auto_port: bool,
+
+    /// Log one event per vote request with method, path, status, latency and client IP
+    /// (default: true)
+    #[serde(default = "default_enabled")]
+    pub access_log: bool,
+
+    /// Include `/healthz`, `/readyz`, `/health`, `/metrics`, `/debug/pool`, `/debug/backend`
+    /// and `/debug/stats` in the access log (default: false)
+    #[serde(default)]
+    pub access_log_probes: bool,
+
+    /// Send the serving node's id as `X-TVS-Node-Id` on every response (default: true)
+    #[serde(default = "default_enabled")]
+    pub expose_node_header: bool,
+
+    /// File the cumulative vote counters are checkpointed to and restored from at startup,
+    /// so they survive restarts (`metrics` feature). Unset keeps only the since-start ones
+    #[serde(default)]
+    pub metrics_checkpoint_path: Option<String>,
+
+    /// Seconds between metrics checkpoints (default: 60); one more is written on shutdown
+    #[serde(default = "default_metrics_checkpoint_interval_secs")]
+    pub metrics_checkpoint_interval_secs: u64,
+}
+
+/// Token-bucket limit applied to each client IP
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct RateLimitConfig {
+    /// Sustained requests per second a client may make
+    pub requests_per_second: u32,
+
+    /// Requests a client may make at once before the rate applies
+    pub burst: u32,
+
+    /// Status for a client out of tokens, 4xx or 5xx (default: 429; some clients expect 503
+    /// or 420)
+    #[serde(default = "default_rate_limit_status")]
+    pub status: u16,
+
+    /// `Retry-After` seconds sent to a client out of tokens. Unset sends the time until its
+    /// next token, rounded up (default)
+    #[serde(default)]
+    pub retry_after_secs: Option<u64>,
+}
+
+#[cfg(test)]
+impl RateLimitConfig {
+    /// A limit of `requests_per_second` with room for `burst`, refused the default way
+    pub fn new(requests_per_second: u32, burst: u32) -> Self {
+        Self {
+            requests_per_second,
+            burst,
+            status: default_rate_limit_status(),
+            retry_after_secs: None,
+        }
+    }
+}
+
+/// PEM files used to terminate TLS on the vote server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct TlsConfig {
+    /// Certificate chain, leaf first
+    pub cert_path: String,
+
+    /// Private key (PKCS#8, PKCS#1 or SEC1)
+    pub key_path: String,
+}
+
+/// One vote server listener, for serving the vote routes on several host/port pairs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ListenerConfig {
+    /// Host to bind (default: "127.0.0.1")
+    #[serde(default = "default_vote_host")]
+    pub host: String,
+
+    /// Port to bind (default: 8090)
+    #[serde(default = "default_vote_port")]
+    pub port: u16,
+
+    /// Start this listener (default: true)
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+impl TvsServerConfig {
+    /// Listeners to start: the enabled entries of `listeners`, or the bind host and
+    /// `vote_port` when no listeners are configured
+    pub fn active_listeners(&self) -> Vec<ListenerConfig> {
+        if self.listeners.is_empty() {
+            return vec![ListenerConfig {
+                host: self.bind_host().to_string(),
+                port: self.vote_port,
+                enabled: true,
+            }];
+        }
+
+        self.listeners
+            .iter()
+            .filter(|listener| listener.enabled)
+            .cloned()
+            .collect()
+    }
+
+    /// Host the single vote listener binds: `bind_address`, else `vote_host`
+    pub fn bind_host(&self) -> &str {
+        self.bind_address.as_deref().unwrap_or(&self.vote_host)
+    }
+
+    /// Config key of the active listener at `index`, for validation messages
+    fn listener_key(&self, index: usize, field: &str) -> String {
+        if self.listeners.is_empty() {
+            if field == "host" && self.bind_address.is_some() {
+                return "tvs.bind_address".to_string();
+            }
+            format!("tvs.vote_{}", field)
+        } else {
+            let position = self
+                .listeners
+                .iter()
+                .enumerate()
+                .filter(|(_, listener)| listener.enabled)
+                .nth(index)
+                .map_or(index, |(position, _)| position);
+            format!("tvs.listeners[{}].{}", position, field)
+        }
+    }
+}
+
+/// Process-level runtime settings applied at startup
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct RuntimeConfig {
+    /// Process niceness (-20 highest priority, 19 lowest). Unset leaves the inherited priority.
+    #[serde(default)]
+    pub nice: Option<i32>,
+
+    /// Shut down gracefully after this many seconds of uptime, leaving the restart
+    /// to the orchestrator. Unset means unlimited.
+    #[serde(default)]
+    pub max_uptime_secs: Option<u64>,
+
+    /// Stack size in bytes for Tokio worker threads. Unset uses the Tokio default (2 MiB).
+    #[serde(default)]
+    pub thread_stack_bytes: Option<usize>,
+
+    /// Announce readiness once the TFS and vote servers are up: a file path gets a JSON
+    /// record (removed on shutdown), `systemd` sends `READY=1` to `$NOTIFY_SOCKET`. Unset
+    /// announces nothing
+    #[serde(default)]
+    pub ready_notify: Option<String>,
+}
+
+/// How many backend checks in a row `/readyz` needs before it changes its answer, so a
+/// single failed check doesn't flap the node in and out of the load balancer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ReadinessConfig {
+    /// Consecutive failed checks before a ready node reports not ready (default: 1)
+    #[serde(default = "default_readiness_threshold")]
+    pub failure_threshold: u32,
+
+    /// Consecutive successful checks before a not-ready node reports ready again (default: 1)
+    #[serde(default = "default_readiness_threshold")]
+    pub success_threshold: u32,
+}
+
+impl Default for ReadinessConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: default_readiness_threshold(),
+            success_threshold: default_readiness_threshold(),
+        }
+    }
+}
+
+fn default_readiness_threshold() -> u32 {
+    1
+}
+
+/// Errors produced while loading or validating configuration
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The config file doesn't exist
+    NotFound { path: String },
+    /// The config file exists but this process may not read it
+    PermissionDenied { path: String },
+    /// The config file couldn't be read for another reason
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+    /// `--config -` was given but stdin was closed or empty
+    EmptyStdin,
+    /// `--profile` was given but its file doesn't exist next to the base config
+    ProfileNotFound { profile: String, path: String },
+    /// `--profile` was given for a config that didn't come from a file
+    ProfileWithoutFile { profile: String },
+    /// The config couldn't be deserialized; `origin` is the file path, "stdin" or "environment"
+    Parse {
+        origin: String,
+        format: ConfigFormat,
+        message: String,
+    },
+    /// The config parsed but has invalid settings
+    Validation(Vec<String>),
+    /// An unrecognized config format name was requested
+    UnknownFormat(String),
+    /// A line in an `--env-file` couldn't be parsed
+    EnvFile {
+        path: String,
+        line: usize,
+        message: String,
+    },
+    /// `persistence.database_password_file` is missing, unreadable or empty
+    // Only read by the postgres backend
+    #[cfg_attr(not(feature = "postgres"), allow(dead_code))]
+    PasswordFile { path: String, message: String },
+    /// An override environment variable is set but can't be parsed
+    InvalidEnv {
+        var: String,
+        value: String,
+        expected: &'static str,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::NotFound { path } => write!(
+                f,
+                "config file '{}' not found; create one with `tvs_node generate-config`",
+                path
+            ),
+            ConfigError::PermissionDenied { path } => write!(
+                f,
+                "config file '{}' is not readable (permission denied); check its owner and mode",
+                path
+            ),
+            ConfigError::Io { path, source } => {
+                write!(f, "Failed to read config {}: {}", path, source)
+            }
+            ConfigError::EmptyStdin => write!(
+                f,
+                "no config received on stdin (--config -); pipe the config into the process"
+            ),
+            ConfigError::ProfileNotFound { profile, path } => write!(
+                f,
+                "profile '{}' not found: expected its overrides in {}",
+                profile, path
+            ),
+            ConfigError::ProfileWithoutFile { profile } => write!(
+                f,
+                "profile '{}' needs a base config file to find config.{}.* next to",
+                profile, profile
+            ),
+            ConfigError::Parse {
+                origin,
+                format,
+                message,
+            } => write!(
+                f,
+                "Failed to parse config {} as {}: {}",
+                origin, format, message
+            ),
+            ConfigError::Validation(errors) => {
+                write!(f, "Invalid configuration: {}", errors.join("; "))
+            }
+            ConfigError::UnknownFormat(format) => write!(
+                f,
+                "unknown config format '{}' (expected json, toml or yaml)",
+                format
+            ),
+            ConfigError::EnvFile {
+                path,
+                line,
+                message,
+            } => write!(f, "Invalid env file {} (line {}): {}", path, line, message),
+            ConfigError::PasswordFile { path, message } => write!(
+                f,
+                "database password file '{}' {} (persistence.database_password_file)",
+                path, message
+            ),
+            ConfigError::InvalidEnv {
+                var,
+                value,
+                expected,
+            } => write!(
+                f,
+                "Invalid value for environment variable {}: {:?} (expected {})",
+                var, value, expected
+            ),
+        }
+    }
+}
+
+impl ConfigError {
+    /// Classify a failure to read the config file at `path`
+    pub fn from_read(path: &str, source: std::io::Error) -> Self {
+        let path = path.to_string();
+        match source.kind() {
+            std::io::ErrorKind::NotFound => ConfigError::NotFound { path },
+            std::io::ErrorKind::PermissionDenied => ConfigError::PermissionDenied { path },
+            _ => ConfigError::Io { path, source },
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Serialization format of a configuration file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl ConfigFormat {
+    /// Detect the format from a file extension, defaulting to JSON
+    pub fn from_path(path: &str) -> Self {
+        let extension = std::path::Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+
+        match extension.as_deref() {
+            Some("toml") => ConfigFormat::Toml,
+            Some("yaml") | Some("yml") => ConfigFormat::Yaml,
+            _ => ConfigFormat::Json,
+        }
+    }
+}
+
+impl std::str::FromStr for ConfigFormat {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(ConfigFormat::Json),
+            "toml" => Ok(ConfigFormat::Toml),
+            "yaml" | "yml" => Ok(ConfigFormat::Yaml),
+            other => Err(ConfigError::UnknownFormat(other.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for ConfigFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigFormat::Json => write!(f, "JSON"),
+            ConfigFormat::Toml => write!(f, "TOML"),
+            ConfigFormat::Yaml => write!(f, "YAML"),
+        }
+    }
+}
+
+/// Log output format
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human-readable output from the TFS tracing setup
+    #[default]
+    Text,
+    /// One JSON object per line, carrying level, target and node name
+    Json,
+}
+
+impl std::str::FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            other => Err(format!(
+                "unknown log format '{}' (expected text or json)",
+                other
+            )),
+        }
+    }
+}
+
+/// Address family preference for resolving vote listener hosts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum IpVersion {
+    V4,
+    V6,
+    /// Whichever family the resolver lists first
+    #[default]
+    Any,
+}
+
+impl IpVersion {
+    pub fn matches(self, addr: &std::net::SocketAddr) -> bool {
+        match self {
+            IpVersion::V4 => addr.is_ipv4(),
+            IpVersion::V6 => addr.is_ipv6(),
+            IpVersion::Any => true,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            IpVersion::V4 => "v4",
+            IpVersion::V6 => "v6",
+            IpVersion::Any => "any",
+        }
+    }
+}
+
+/// Log verbosity accepted by `--log-level`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    /// The level as a tracing filter directive
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Trace => "trace",
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warn => "warn",
+            LogLevel::Error => "error",
+        }
+    }
+}
+
+impl std::str::FromStr for LogLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "trace" => Ok(LogLevel::Trace),
+            "debug" => Ok(LogLevel::Debug),
+            "info" => Ok(LogLevel::Info),
+            "warn" => Ok(LogLevel::Warn),
+            "error" => Ok(LogLevel::Error),
+            other => Err(format!(
+                "unknown log level '{}' (expected trace, debug, info, warn or error)",
+                other
+            )),
+        }
+    }
+}
+
+/// Where the active configuration was loaded from, for audit and debugging
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigSource {
+    /// A config file, with its last-modified time when the filesystem reports one
+    File {
+        path: PathBuf,
+        modified: Option<SystemTime>,
+    },
+    /// Built purely from environment variables (`TVS_CONFIG_FROM_ENV`)
+    Environment,
+    /// Piped in on stdin (`--config -`)
+    Stdin,
+}
+
+impl ConfigSource {
+    /// Describe a config file, resolving its absolute path and modification time
+    pub fn file(path: &str) -> Self {
+        let path = std::fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path));
+        let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+        ConfigSource::File { path, modified }
+    }
+
+    /// The source as `info` and `/debug/stats` report it: `kind` is `file`, `environment` or
+    /// `stdin`, and a file adds its `path` and RFC 3339 `modified` time (null when unknown)
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            ConfigSource::File { path, modified } => serde_json::json!({
+                "kind": "file",
+                "path": path.display().to_string(),
+                "modified": modified.map(|modified| DateTime::<Utc>::from(modified).to_rfc3339()),
+            }),
+            ConfigSource::Environment => serde_json::json!({ "kind": "environment" }),
+            ConfigSource::Stdin => serde_json::json!({ "kind": "stdin" }),
+        }
+    }
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigSource::File {
+                path,
+                modified: Some(modified),
+            } => {
+                let modified: DateTime<Utc> = (*modified).into();
+                write!(
+                    f,
+                    "file {} (modified {})",
+                    path.display(),
+                    modified.to_rfc3339()
+                )
+            }
+            ConfigSource::File {
+                path,
+                modified: None,
+            } => write!(f, "file {}", path.display()),
+            ConfigSource::Environment => write!(f, "environment variables"),
+            ConfigSource::Stdin => write!(f, "stdin"),
+        }
+    }
+}
+
+/// Outcome of config validation
+/// Errors always block startup; warnings are logged unless strict mode promotes them to errors
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+impl ValidationReport {
+    /// Print warnings and turn the report into a startup result
+    pub fn into_result(self, strict: bool) -> Result<(), ConfigError> {
+        let mut errors = self.errors;
+        if strict {
+            errors.extend(
+                self.warnings
+                    .into_iter()
+                    .map(|w| format!("{} (warning promoted by --strict)", w)),
+            );
+        } else {
+            for warning in &self.warnings {
+                eprintln!("⚠ Config warning: {}", warning);
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Validation(errors))
+        }
+    }
+}
+
+/// Smallest accepted `runtime.thread_stack_bytes`; below this even simple futures overflow
+pub const MIN_THREAD_STACK_BYTES: usize = 256 * 1024;
+
+/// Smallest accepted `persistence.max_pool_size`: startup holds one connection for the
+/// migration lock while the migrations check out another
+pub const MIN_POOL_SIZE: u32 = 2;
+
+/// Persistence backend settings
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PersistenceConfig {
+    /// Database connection string. Unset falls back to `POSTGRES_DATABASE_URL`.
+    #[serde(default)]
+    pub database_url: Option<String>,
+
+    /// File holding the database password, e.g. a mounted secret. When set, its contents
+    /// (minus a trailing newline) replace any password in the connection string, so the
+    /// URL can stay in the config without credentials. A missing or empty file is an error
+    #[serde(default)]
+    pub database_password_file: Option<String>,
+
+    /// Close pooled connections older than this many seconds. Unset keeps them indefinitely.
+    #[serde(default)]
+    pub max_connection_lifetime_secs: Option<u64>,
+
+    /// Close pooled connections idle for longer than this many seconds. Unset uses the pool default.
+    #[serde(default)]
+    pub idle_connection_timeout_secs: Option<u64>,
+
+    /// Maximum number of pooled connections, at least 2. Unset uses the pool default (10).
+    #[serde(default)]
+    pub max_pool_size: Option<u32>,
+
+    /// Idle connections the pool keeps open. Unset keeps it equal to `max_pool_size`.
+    #[serde(default)]
+    pub min_idle: Option<u32>,
+
+    /// Seconds to wait for a pooled connection before failing. Unset uses the pool default (30).
+    #[serde(default)]
+    pub connection_timeout_secs: Option<u64>,
+
+    /// Retries after the first failed database connection at startup (default: 5)
+    #[serde(default = "default_db_connect_retries")]
+    pub db_connect_retries: u32,
+
+    /// Delay before the first retry in milliseconds, doubling on each attempt (default: 500)
+    #[serde(default = "default_db_connect_base_delay_ms")]
+    pub db_connect_base_delay_ms: u64,
+
+    /// Use the existing schema without creating it or running migrations, e.g. on a read
+    /// replica (default: false). Also set by `--skip-migrations`
+    #[serde(default)]
+    pub skip_migrations: bool,
+
+    /// Times a vote read that failed with a 5xx is retried (default: 2). 0 disables retries
+    #[serde(default = "default_backend_read_retries")]
+    pub backend_read_retries: u32,
+
+    /// Delay before the first read retry in milliseconds, doubling on each attempt (default: 50)
+    #[serde(default = "default_backend_retry_delay_ms")]
+    pub backend_retry_delay_ms: u64,
+
+    /// Consecutive failed vote requests, each confirmed by a failed backend check, after
+    /// which the vote routes answer 503 without calling the backend (default: 0, off)
+    #[serde(default)]
+    pub breaker_failure_threshold: u32,
+
+    /// Seconds the open circuit breaker waits before letting a trial request through (default: 30)
+    #[serde(default = "default_breaker_cooldown_secs")]
+    pub breaker_cooldown_secs: u64,
+}
+
+impl Default for PersistenceConfig {
+    fn default() -> Self {
+        Self {
+            database_url: None,
+            database_password_file: None,
+            max_connection_lifetime_secs: None,
+            idle_connection_timeout_secs: None,
+            max_pool_size: None,
+            min_idle: None,
+            connection_timeout_secs: None,
+            db_connect_retries: default_db_connect_retries(),
+            db_connect_base_delay_ms: default_db_connect_base_delay_ms(),
+            skip_migrations: false,
+            backend_read_retries: default_backend_read_retries(),
+            backend_retry_delay_ms: default_backend_retry_delay_ms(),
+            breaker_failure_threshold: 0,
+            breaker_cooldown_secs: default_breaker_cooldown_secs(),
+        }
+    }
+}
+
+fn default_db_connect_retries() -> u32 {
+    5
+}
+
+fn default_db_connect_base_delay_ms() -> u64 {
+    500
+}
+
+fn default_backend_read_retries() -> u32 {
+    2
+}
+
+fn default_backend_retry_delay_ms() -> u64 {
+    50
+}
+
+fn default_breaker_cooldown_secs() -> u64 {
+    30
+}
+
+fn default_vote_port() -> u16 {
+    8090
+}
+
+fn default_vote_host() -> String {
+    "127.0.0.1".to_string()
+}
+
+fn default_shutdown_timeout_secs() -> u64 {
+    30
+}
+
+fn default_tfs_ready_timeout_secs() -> u64 {
+    30
+}
+
+fn default_rate_limit_status() -> u16 {
+    429
+}
+
+fn default_max_connections_status() -> u16 {
+    503
+}
+
+fn default_max_connections_retry_after_secs() -> u64 {
+    1
+}
+
+fn default_metrics_checkpoint_interval_secs() -> u64 {
+    60
+}
+
+fn default_max_body_bytes() -> Option<usize> {
+    Some(64 * 1024)
+}
+
+/// Vote root URL used when neither `TVS_ROOT_URL` nor `tvs.root_url` is set
+pub const DEFAULT_ROOT_URL: &str = "http://localhost:8081/vote";
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_vote_service_required() -> bool {
+    true
+}
+
+impl Default for TvsServerConfig {
+    fn default() -> Self {
+        Self {
+            vote_port: default_vote_port(),
+            vote_host: default_vote_host(),
+            bind_address: None,
+            ip_version: IpVersion::default(),
+            ipv6_only: false,
+            enabled: default_enabled(),
+            root_url: None,
+            shutdown_timeout_secs: default_shutdown_timeout_secs(),
+            max_lifetime_secs: None,
+            tfs_ready_timeout_secs: default_tfs_ready_timeout_secs(),
+            listeners: Vec::new(),
+            tls: None,
+            cors_allowed_origins: Vec::new(),
+            rate_limit: None,
+            max_body_bytes: default_max_body_bytes(),
+            max_connections: None,
+            max_connections_status: default_max_connections_status(),
+            max_connections_retry_after_secs: default_max_connections_retry_after_secs(),
+            tcp_keepalive_secs: None,
+            tcp_nodelay: false,
+            max_connections_per_ip: None,
+            trusted_proxies: Vec::new(),
+            auto_port: false,
+            access_log: default_enabled(),
+            access_log_probes: false,
+            expose_node_header: default_enabled(),
+            metrics_checkpoint_path: None,
+            metrics_checkpoint_interval_secs: default_metrics_checkpoint_interval_secs(),
+        }
+    }
+}
+
+impl TvsNodeConfig {
+    /// Read configuration from a file, choosing JSON, TOML or YAML by extension
+    /// (unknown extensions are parsed as JSON). A leading UTF-8 BOM is always tolerated.
+    pub fn read_config(config_path: &str) -> Result<Self, ConfigError> {
+        Self::read_config_with_leniency(config_path, false)
+    }
+
+    /// Read configuration from a file, optionally tolerating `//` and `/* */`
+    /// comments and trailing commas in JSON (as written by many editors)
+    pub fn read_config_with_leniency(
+        config_path: &str,
+        lenient: bool,
+    ) -> Result<Self, ConfigError> {
+        Self::read_config_with_format(config_path, None, lenient)
+    }
+
+    /// Read configuration from a file in an explicit format (`--format`), or detect it
+    /// from the extension when `format` is `None`
+    pub fn read_config_with_format(
+        config_path: &str,
+        format: Option<ConfigFormat>,
+        lenient: bool,
+    ) -> Result<Self, ConfigError> {
+        let config_content = std::fs::read_to_string(config_path)
+            .map_err(|source| ConfigError::from_read(config_path, source))?;
+        let format = format.unwrap_or_else(|| ConfigFormat::from_path(config_path));
+        Self::parse_config(&config_content, format, lenient).map_err(|message| ConfigError::Parse {
+            origin: config_path.to_string(),
+            format,
+            message,
+        })
+    }
+
+    /// Read the whole configuration from `reader` (stdin for `--config -`)
+    /// There is no extension to detect the format from, so it is JSON unless `format` is given
+    pub fn read_config_from_reader(
+        mut reader: impl std::io::Read,
+        format: Option<ConfigFormat>,
+        lenient: bool,
+    ) -> Result<Self, ConfigError> {
+        let mut config_content = String::new();
+        reader
+            .read_to_string(&mut config_content)
+            .map_err(|source| ConfigError::Io {
+                path: "stdin".to_string(),
+                source,
+            })?;
+        if strip_bom(&config_content).trim().is_empty() {
+            return Err(ConfigError::EmptyStdin);
+        }
+
+        let format = format.unwrap_or(ConfigFormat::Json);
+        Self::parse_config(&config_content, format, lenient).map_err(|message| ConfigError::Parse {
+            origin: "stdin".to_string(),
+            format,
+            message,
+        })
+    }
+
+    /// Layer the `--profile` file at `profile_path` over this config, before env overrides
+    /// Objects (`server`, `tvs`, ...) are merged key by key; any other value in the profile
+    /// replaces the base one. The profile is a partial config in the base file's format
+    pub fn with_profile(
+        self,
+        profile: &str,
+        profile_path: &str,
+        format: Option<ConfigFormat>,
+        lenient: bool,
+    ) -> Result<Self, ConfigError> {
+        let content =
+            std::fs::read_to_string(profile_path).map_err(
+                |source| match ConfigError::from_read(profile_path, source) {
+                    ConfigError::NotFound { path } => ConfigError::ProfileNotFound {
+                        profile: profile.to_string(),
+                        path,
+                    },
+                    other => other,
+                },
+            )?;
+        let format = format.unwrap_or_else(|| ConfigFormat::from_path(profile_path));
+        self.merge_document(&content, profile_path, format, lenient)
+    }
+
+    /// Layer another `--config` file over this config, deep-merged like a profile
+    /// Each overlay's format comes from its own extension, so a JSON base can take TOML overlays
+    pub fn with_overlay(self, overlay_path: &str, lenient: bool) -> Result<Self, ConfigError> {
+        let content = std::fs::read_to_string(overlay_path)
+            .map_err(|source| ConfigError::from_read(overlay_path, source))?;
+        self.merge_document(
+            &content,
+            overlay_path,
+            ConfigFormat::from_path(overlay_path),
+            lenient,
+        )
+    }
+
+    /// Deep-merge the partial config in `content` (read from `origin`) over this one
+    fn merge_document(
+        self,
+        content: &str,
+        origin: &str,
+        format: ConfigFormat,
+        lenient: bool,
+    ) -> Result<Self, ConfigError> {
+        let parse_error = |message: String| ConfigError::Parse {
+            origin: origin.to_string(),
+            format,
+            message,
+        };
+
+        let overrides: serde_json::Value =
+            Self::parse_document(content, format, lenient).map_err(parse_error)?;
+        let mut merged = serde_json::to_value(&self).map_err(|e| parse_error(e.to_string()))?;
+        deep_merge(&mut merged, overrides);
+        serde_json::from_value(merged).map_err(|e| parse_error(e.to_string()))
+    }
+
+    /// Parse configuration text in the given format
+    fn parse_config(content: &str, format: ConfigFormat, lenient: bool) -> Result<Self, String> {
+        Self::parse_document(content, format, lenient)
+    }
+
+    /// Parse a whole or partial configuration document in the given format
+    fn parse_document<T: serde::de::DeserializeOwned>(
+        content: &str,
+        format: ConfigFormat,
+        lenient: bool,
+    ) -> Result<T, String> {
+        let content = strip_bom(content);
+        match format {
+            ConfigFormat::Json => Self::parse_json(content, lenient).map_err(|e| {
+                let hint = if !lenient && e.is_syntax() {
+                    " (use --lenient to allow comments and trailing commas)"
+                } else {
+                    ""
+                };
+                format!("{}{}", e, hint)
+            }),
+            ConfigFormat::Toml => toml::from_str(content).map_err(|e| e.to_string()),
+            ConfigFormat::Yaml => serde_yaml::from_str(content).map_err(|e| e.to_string()),
+        }
+    }
+
+    /// Parse configuration from JSON text
+    fn parse_json<T: serde::de::DeserializeOwned>(
+        content: &str,
+        lenient: bool,
+    ) -> Result<T, serde_json::Error> {
+        if lenient {
+            serde_json::from_str(&relax_json(content))
+        } else {
+            serde_json::from_str(content)
+        }
+    }
+
+    /// Build a configuration purely from environment variables, for fileless deployments
+    /// Ports default to 8080/8081/8082 and the vote server section to its defaults;
+    /// `NODE_UUID`, `LOG_LEVEL` and `LOG_DIR` fill the matching `AppConfig` fields
+    pub fn from_env() -> Result<Self, ConfigError> {
+        let mut base = base_config_json();
+
+        for (var, field) in [
+            ("NODE_UUID", "node_uuid"),
+            ("LOG_LEVEL", "log_level"),
+            ("LOG_DIR", "log_dir"),
+        ] {
+            if let Ok(value) = env_var(var) {
+                base[field] = serde_json::Value::String(value);
+            }
+        }
+
+        let mut config: TvsNodeConfig =
+            serde_json::from_value(base).map_err(|e| ConfigError::Parse {
+                origin: "environment".to_string(),
+                format: ConfigFormat::Json,
+                message: e.to_string(),
+            })?;
+        config.apply_env_overrides()?;
+        Ok(config)
+    }
+
+    /// Starting-point config for new deployments: default ports and every section populated
+    /// with its defaults (`persistence` only when built with the postgres feature)
+    pub fn template() -> Self {
+        let mut base = base_config_json();
+        base["node_name"] = serde_json::Value::String("tvs_node_1".to_string());
+        base["runtime"] = serde_json::json!({});
+        base["readiness"] = serde_json::json!({});
+        if cfg!(feature = "postgres") {
+            base["persistence"] = serde_json::json!({});
+        }
+
+        serde_json::from_value(base).expect("config template must deserialize")
+    }
+
+    /// Check whether fileless configuration was requested via `TVS_CONFIG_FROM_ENV`
+    pub fn config_from_env_requested() -> bool {
+        std::env::var("TVS_CONFIG_FROM_ENV")
+            .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false)
+    }
+
+    /// Override config values with environment variables
+    /// This allows Docker containers to override config.json settings via env vars
+    /// Unset variables are no-ops; a variable that is set but can't be parsed is an error
+    pub fn apply_env_overrides(&mut self) -> Result<(), ConfigError> {
+        // TFS server ports
+        if let Some(p) = env_override::<u16>("CLUSTER_MESSAGE_PORT")? {
+            self.tfs.server.cluster_message_port = p;
+        }
+
+        if let Some(p) = env_override::<u16>("APP_PORT")? {
+            self.tfs.server.app_port = p;
+        }
+
+        if let Some(p) = env_override::<u16>("ADMIN_PORT")? {
+            self.tfs.server.admin_port = p;
+        }
+
+        // TVS vote server configuration. Without a section the vote server still starts on
+        // the defaults, so an override fills in a default section and is then validated
+        // like one from the file
+        let vote_port = env_override::<u16>("TVS_VOTE_PORT")?;
+        let vote_host = env_var("TVS_VOTE_HOST").ok();
+        let vote_enabled = env_flag("TVS_VOTE_ENABLED")?;
+        if vote_port.is_some() || vote_host.is_some() || vote_enabled.is_some() {
+            let tvs = self.tvs.get_or_insert_with(TvsServerConfig::default);
+            if let Some(enabled) = vote_enabled {
+                tvs.enabled = enabled;
+            }
+            if let Some(host) = vote_host {
+                tvs.vote_host = host;
+            }
+            if let Some(p) = vote_port {
+                tvs.vote_port = p;
+            }
+        }
+
+        // Node identification
+        if let Ok(name) = env_var("NODE_NAME") {
+            self.tfs.node_name = Some(name);
+        }
+
+        // Logging
+        if let Some((var, raw)) = raw_env_override("LOG_FORMAT") {
+            self.log_format = raw.parse().map_err(|_| ConfigError::InvalidEnv {
+                var,
+                value: raw.clone(),
+                expected: "text or json",
+            })?;
+        }
+
+        // Database connection (twelve-factor style)
+        if let Ok(url) = env_var("DATABASE_URL") {
+            self.persistence_mut().database_url = Some(url);
+        }
+
+        // Database pool sizing
+        if let Some(size) = env_override::<u32>("DB_MAX_POOL_SIZE")? {
+            self.persistence_mut().max_pool_size = Some(size);
+        }
+
+        if let Some(idle) = env_override::<u32>("DB_MIN_IDLE")? {
+            self.persistence_mut().min_idle = Some(idle);
+        }
+
+        if let Some(secs) = env_override::<u64>("DB_CONNECTION_TIMEOUT_SECS")? {
+            self.persistence_mut().connection_timeout_secs = Some(secs);
+        }
+
+        Ok(())
+    }
+
+    /// Add `offset` to every port the node listens on (`--port-offset`), so one config can
+    /// run several local nodes side by side. The vote ports are shifted even without a `tvs`
+    /// section, since the vote server still starts on the default port. A port pushed past
+    /// 65535 is an error
+    pub fn apply_port_offset(&mut self, offset: u16) -> Result<(), ConfigError> {
+        if offset == 0 {
+            return Ok(());
+        }
+
+        let mut errors = Vec::new();
+        let mut shift = |key: String, port: &mut u16| {
+            // 0 lets the OS pick a free vote port, which an offset would only pin down
+            if *port == 0 {
+                return;
+            }
+            match port.checked_add(offset) {
+                Some(shifted) => *port = shifted,
+                None => errors.push(format!(
+                    "{} ({}) plus --port-offset {} is past 65535",
+                    key, port, offset
+                )),
+            }
+        };
+
+        let server = &mut self.tfs.server;
+        shift(
+            "server.cluster_message_port".to_string(),
+            &mut server.cluster_message_port,
+        );
+        shift("server.app_port".to_string(), &mut server.app_port);
+        shift("server.admin_port".to_string(), &mut server.admin_port);
+
+        let tvs = self.tvs.get_or_insert_with(TvsServerConfig::default);
+        shift("tvs.vote_port".to_string(), &mut tvs.vote_port);
+        for (index, listener) in tvs.listeners.iter_mut().enumerate() {
+            shift(format!("tvs.listeners[{}].port", index), &mut listener.port);
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Validation(errors))
+        }
+    }
+
+    /// Get the TFS app config
+    pub fn tfs_config(&self) -> &AppConfig {
+        &self.tfs
+    }
+
+    /// Get the TVS server config, or None if disabled
+    pub fn tvs_config(&self) -> Option<&TvsServerConfig> {
+        self.tvs.as_ref().filter(|c| c.enabled)
+    }
+
+    /// Get the vote server config, falling back to the defaults it runs on when the section
+    /// is absent (even if disabled)
+    pub fn vote_server_config(&self) -> TvsServerConfig {
+        self.tvs.clone().unwrap_or_default()
+    }
+
+    /// Get the runtime config, falling back to defaults when the section is absent
+    pub fn runtime_config(&self) -> RuntimeConfig {
+        self.runtime.clone().unwrap_or_default()
+    }
+
+    /// Get the readiness config, falling back to defaults when the section is absent
+    pub fn readiness_config(&self) -> ReadinessConfig {
+        self.readiness.clone().unwrap_or_default()
+    }
+
+    /// Get the persistence section for modification, creating it if absent
+    fn persistence_mut(&mut self) -> &mut PersistenceConfig {
+        self.persistence
+            .get_or_insert_with(PersistenceConfig::default)
+    }
+
+    /// Get the persistence config, falling back to defaults when the section is absent
+    pub fn persistence_config(&self) -> PersistenceConfig {
+        self.persistence.clone().unwrap_or_default()
+    }
+
+    /// Validate settings that deserialization alone can't check
+    /// Warnings are printed and do not block startup
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        self.validate_with_strictness(false)
+    }
+
+    /// Validate settings, optionally promoting warnings to errors (`--strict`)
+    pub fn validate_with_strictness(&self, strict: bool) -> Result<(), ConfigError> {
+        self.validation_report().into_result(strict)
+    }
+
+    /// Grace period for draining the vote server on shutdown, in seconds
+    pub fn shutdown_timeout_secs(&self) -> u64 {
+        self.tvs
+            .as_ref()
+            .map_or_else(default_shutdown_timeout_secs, |tvs| {
+                tvs.shutdown_timeout_secs
+            })
+    }
+
+    /// Seconds of uptime after which the node shuts itself down, from `runtime.max_uptime_secs`
+    /// or its alias `tvs.max_lifetime_secs`, whichever is shorter; None means unlimited
+    pub fn max_uptime_secs(&self) -> Option<u64> {
+        let lifetime = self.tvs.as_ref().and_then(|tvs| tvs.max_lifetime_secs);
+        match (self.runtime_config().max_uptime_secs, lifetime) {
+            (Some(uptime), Some(lifetime)) => Some(uptime.min(lifetime)),
+            (uptime, lifetime) => uptime.or(lifetime),
+        }
+    }
+
+    /// How long to wait for the TFS node to become ready before starting the vote server, in seconds
+    pub fn tfs_ready_timeout_secs(&self) -> u64 {
+        self.tvs
+            .as_ref()
+            .map_or_else(default_tfs_ready_timeout_secs, |tvs| {
+                tvs.tfs_ready_timeout_secs
+            })
+    }
+
+    /// Base URL for vote URLs: `TVS_ROOT_URL` if set, then `tvs.root_url`, then the default
+    pub fn vote_root_url(&self) -> String {
+        env_var("TVS_ROOT_URL")
+            .ok()
+            .or_else(|| self.tvs.as_ref().and_then(|tvs| tvs.root_url.clone()))
+            .unwrap_or_else(|| DEFAULT_ROOT_URL.to_string())
+    }
+
+    /// The node id TFS will run under, when the config pins both `node_name` and `node_uuid`
+    /// Without a fixed uuid the running node generates one, so the id can't be known ahead
+    pub fn configured_node_id(&self) -> Option<tfs::tfs::node_id::NodeId> {
+        Some(tfs::tfs::node_id::NodeId::new(
+            self.tfs.node_name.clone()?,
+            self.tfs.node_uuid?,
+        ))
+    }
+
+    /// Listeners the vote server binds; empty when it is disabled
+    /// Without a tvs section this is the default listener. `TVS_VOTE_HOST`/`TVS_VOTE_PORT`
+    /// reach it through `apply_env_overrides`, and only apply to the single-listener form;
+    /// `bind_address` wins over the vote host
+    pub fn vote_listeners(&self) -> Vec<ListenerConfig> {
+        let tvs = self.vote_server_config();
+        if !tvs.enabled {
+            return Vec::new();
+        }
+        tvs.active_listeners()
+    }
+
+    /// Collect every validation error and warning for this config
+    pub fn validation_report(&self) -> ValidationReport {
+        let mut report = ValidationReport::default();
+
+        // Every listener needs its own valid port; a collision only surfaces as a bind
+        // failure on the second server otherwise
+        let server = &self.tfs.server;
+        let mut ports = vec![
+            (
+                "server.cluster_message_port".to_string(),
+                server.cluster_message_port,
+            ),
+            ("server.app_port".to_string(), server.app_port),
+            ("server.admin_port".to_string(), server.admin_port),
+        ];
+        // What the vote server actually binds, which is the default listener without a tvs
+        // section
+        let tvs = self.vote_server_config();
+        let vote_listeners = self.vote_listeners();
+        for (i, listener) in vote_listeners.iter().enumerate() {
+            // A Unix socket listener has no port, and port 0 has the OS pick a free one
+            // (see `TvsNodeRunner::vote_server_addr`), so neither can collide
+            if unix_socket_path(&listener.host).is_none() && listener.port != 0 {
+                ports.push((tvs.listener_key(i, "port"), listener.port));
+            }
+        }
+        for (i, (name, port)) in ports.iter().enumerate() {
+            if *port == 0 {
+                report
+                    .errors
+                    .push(format!("{} must be in the range 1-65535 (got 0)", name));
+            }
+            if let Some((other, _)) = ports[..i].iter().find(|(_, p)| p == port) {
+                report
+                    .errors
+                    .push(format!("{} and {} both use port {}", other, name, port));
+            }
+        }
+
+        for (i, listener) in vote_listeners.iter().enumerate() {
+            if let Some(path) = unix_socket_path(&listener.host) {
+                let key = tvs.listener_key(i, "host");
+                if path.as_os_str().is_empty() {
+                    report.errors.push(format!(
+                        "{} needs a socket path after {} (got {:?})",
+                        key, UNIX_SOCKET_PREFIX, listener.host
+                    ));
+                } else if !cfg!(unix) {
+                    report.errors.push(format!(
+                        "{} is a Unix socket, which this platform doesn't support",
+                        key
+                    ));
+                } else if tvs.tls.is_some() {
+                    report.errors.push(format!(
+                        "{} is a Unix socket, which can't be combined with tvs.tls",
+                        key
+                    ));
+                }
+            } else if !is_valid_bind_host(&listener.host) {
+                report.errors.push(format!(
+                    "{} must be an IP address or hostname (got {:?})",
+                    tvs.listener_key(i, "host"),
+                    listener.host
+                ));
+            }
+        }
+        if tvs.enabled && vote_listeners.is_empty() {
+            report.warnings.push(
+                "every entry in tvs.listeners is disabled; the vote server will not start"
+                    .to_string(),
+            );
+        }
+
+        if let Some(root_url) = self.tvs.as_ref().and_then(|tvs| tvs.root_url.as_ref()) {
+            if let Err(e) = check_root_url(root_url) {
+                report.errors.push(format!("tvs.root_url {}", e));
+            }
+        }
+
+        if let Some(tvs) = &self.tvs {
+            for (index, origin) in tvs.cors_allowed_origins.iter().enumerate() {
+                if let Err(e) = crate::cors::check_origin(origin) {
+                    report
+                        .errors
+                        .push(format!("tvs.cors_allowed_origins[{}] {}", index, e));
+                }
+            }
+            if let Some(rate_limit) = &tvs.rate_limit {
+                for (name, value) in [
+                    (
+                        "tvs.rate_limit.requests_per_second",
+                        rate_limit.requests_per_second,
+                    ),
+                    ("tvs.rate_limit.burst", rate_limit.burst),
+                ] {
+                    if value == 0 {
+                        report.errors.push(format!("{} must be positive", name));
+                    }
+                }
+            }
+            let refusal_statuses = [
+                (
+                    "tvs.rate_limit.status",
+                    tvs.rate_limit.as_ref().map(|rate_limit| rate_limit.status),
+                ),
+                (
+                    "tvs.max_connections_status",
+                    Some(tvs.max_connections_status),
+                ),
+            ];
+            for (name, status) in refusal_statuses {
+                if let Some(status) = status.filter(|status| !(400..=599).contains(status)) {
+                    report.errors.push(format!(
+                        "{} must be a 4xx or 5xx status (got {})",
+                        name, status
+                    ));
+                }
+            }
+            if tvs.max_body_bytes == Some(0) {
+                report
+                    .errors
+                    .push("tvs.max_body_bytes must be positive".to_string());
+            }
+            if tvs.max_connections == Some(0) {
+                report
+                    .errors
+                    .push("tvs.max_connections must be positive".to_string());
+            }
+            if tvs.tcp_keepalive_secs == Some(0) {
+                report
+                    .errors
+                    .push("tvs.tcp_keepalive_secs must be positive".to_string());
+            }
+            if tvs.max_connections_per_ip == Some(0) {
+                report
+                    .errors
+                    .push("tvs.max_connections_per_ip must be positive".to_string());
+            }
+            if tvs.metrics_checkpoint_interval_secs == 0 {
+                report
+                    .errors
+                    .push("tvs.metrics_checkpoint_interval_secs must be positive".to_string());
+            }
+            if tvs.metrics_checkpoint_path.is_some() && !cfg!(feature = "metrics") {
+                report.warnings.push(
+                    "tvs.metrics_checkpoint_path is set but this build does not include the metrics feature; no checkpoint is kept"
+                        .to_string(),
+                );
+            }
+            for proxy in &tvs.trusted_proxies {
+                if proxy.parse::<std::net::IpAddr>().is_err() {
+                    report.errors.push(format!(
+                        "tvs.trusted_proxies: {:?} is not an IP address",
+                        proxy
+                    ));
+                }
+            }
+        }
+
+        let persistence = self.persistence_config();
+        for (name, value) in [
+            (
+                "persistence.max_connection_lifetime_secs",
+                persistence.max_connection_lifetime_secs,
+            ),
+            (
+                "persistence.idle_connection_timeout_secs",
+                persistence.idle_connection_timeout_secs,
+            ),
+            (
+                "persistence.connection_timeout_secs",
+                persistence.connection_timeout_secs,
+            ),
+            (
+                "persistence.breaker_cooldown_secs",
+                Some(persistence.breaker_cooldown_secs),
+            ),
+        ] {
+            if value == Some(0) {
+                report.errors.push(format!("{} must be positive", name));
+            }
+        }
+        if let Some(max_size) = persistence
+            .max_pool_size
+            .filter(|size| *size < MIN_POOL_SIZE)
+        {
+            report.errors.push(format!(
+                "persistence.max_pool_size must be at least {} (got {}); startup holds one \
+                 connection for the migration lock while migrating on another",
+                MIN_POOL_SIZE, max_size
+            ));
+        }
+        if let (Some(min_idle), Some(max_size)) = (persistence.min_idle, persistence.max_pool_size)
+        {
+            if min_idle > max_size {
+                report.errors.push(format!(
+                    "persistence.min_idle ({}) must not exceed persistence.max_pool_size ({})",
+                    min_idle, max_size
+                ));
+            }
+        }
+
+        if let Some(stack) = self.runtime_config().thread_stack_bytes {
+            if stack < MIN_THREAD_STACK_BYTES {
+                report.errors.push(format!(
+                    "runtime.thread_stack_bytes must be at least {} (got {})",
+                    MIN_THREAD_STACK_BYTES, stack
+                ));
+            }
+        }
+        if self
+            .runtime_config()
+            .ready_notify
+            .is_some_and(|target| target.trim().is_empty())
+        {
+            report
+                .errors
+                .push("runtime.ready_notify must be a file path or \"systemd\"".to_string());
+        }
+
+        let readiness = self.readiness_config();
+        for (name, value) in [
+            ("readiness.failure_threshold", readiness.failure_threshold),
+            ("readiness.success_threshold", readiness.success_threshold),
+        ] {
+            if value == 0 {
+                report.errors.push(format!("{} must be positive", name));
+            }
+        }
+
+        // Runs after env overrides, so a NODE_NAME from the environment counts
+        let named = self
+            .tfs
+            .node_name
+            .as_deref()
+            .is_some_and(|name| !name.trim().is_empty());
+        if self.require_node_name && !named {
+            report.errors.push(
+                "node_name must be set when require_node_name is true; set node_name or NODE_NAME"
+                    .to_string(),
+            );
+        }
+
+        if self.admin_frontend_enabled == Some(true) && !cfg!(feature = "admin-frontend") {
+            report.warnings.push(
+                "admin_frontend_enabled is true but this build does not include the admin-frontend feature; the admin frontend stays disabled"
+                    .to_string(),
+            );
+        }
+
+        // In-memory votes served on a non-loopback interface look like a production deployment
+        #[cfg(all(feature = "ephemeral", not(feature = "postgres")))]
+        for listener in &vote_listeners {
+            // A Unix socket is only reachable from this host
+            let loopback = unix_socket_path(&listener.host).is_some()
+                || strip_ipv6_brackets(&listener.host)
+                    .parse::<std::net::IpAddr>()
+                    .map(|ip| ip.is_loopback())
+                    .unwrap_or(listener.host == "localhost");
+            if !loopback {
+                report.warnings.push(format!(
+                    "ephemeral (in-memory) persistence is serving votes on {}; all votes are lost on restart",
+                    listener.host
+                ));
+            }
+        }
+
+        report
+    }
+
+    /// Whether the admin frontend should be served: compiled in and not disabled in the config
+    pub fn admin_frontend_active(&self) -> bool {
+        resolve_admin_frontend(
+            self.admin_frontend_enabled,
+            cfg!(feature = "admin-frontend"),
+        )
+    }
+
+    /// Check if TVS vote server should be started; it runs on the defaults without a tvs
+    /// section, and not at all when disabled or every listener is
+    pub fn should_start_vote_server(&self) -> bool {
+        !self.vote_listeners().is_empty()
+    }
+
+    /// Serialize the resolved config as pretty JSON with secret-looking values replaced by `***`
+    pub fn to_redacted_json(&self) -> Result<String, serde_json::Error> {
+        let mut value = serde_json::to_value(self)?;
+        redact_secrets(&mut value);
+        serde_json::to_string_pretty(&value)
+    }
+}
+
+/// Decide whether to serve the admin frontend; the config can only turn off what is compiled in
+fn resolve_admin_frontend(requested: Option<bool>, compiled: bool) -> bool {
+    compiled && requested.unwrap_or(true)
+}
+
+/// Placeholder printed in place of secret values
+pub const REDACTED: &str = "***";
+
+/// Replace the value of every secret-looking key (connection strings, passwords, tokens)
+fn redact_secrets(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                let key = key.to_ascii_lowercase();
+                let secret = key == "database_url"
+                    || ["password", "secret", "token"]
+                        .iter()
+                        .any(|needle| key.contains(needle));
+                if secret && !value.is_null() {
+                    *value = serde_json::Value::String(REDACTED.to_string());
+                } else {
+                    redact_secrets(value);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(redact_secrets),
+        _ => {}
+    }
+}
+
+/// Check that a bind host is an IPv4/IPv6 literal (optionally bracketed) or a syntactically
+/// valid hostname; whether a hostname actually resolves is only known at bind time
+fn is_valid_bind_host(host: &str) -> bool {
+    if strip_ipv6_brackets(host)
+        .parse::<std::net::IpAddr>()
+        .is_ok()
+    {
+        return true;
+    }
+
+    !host.is_empty()
+        && host.len() <= 253
+        && host.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+/// Check that a vote root URL is an absolute http or https URL
+pub fn check_root_url(root_url: &str) -> Result<(), String> {
+    match url::Url::parse(root_url) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") => Ok(()),
+        Ok(url) => Err(format!(
+            "must be an http or https URL (got scheme {:?})",
+            url.scheme()
+        )),
+        Err(e) => Err(format!("is not a valid URL ({:?}): {}", root_url, e)),
+    }
+}
+
+/// Prefix marking a vote host as a Unix domain socket path, e.g. `unix:/run/tvs/vote.sock`
+pub const UNIX_SOCKET_PREFIX: &str = "unix:";
+
+/// Socket path of a `unix:` vote host, or None for a TCP host
+pub fn unix_socket_path(host: &str) -> Option<&Path> {
+    host.strip_prefix(UNIX_SOCKET_PREFIX).map(Path::new)
+}
+
+/// Remove the brackets from a bracketed IPv6 literal (`[::1]` -> `::1`)
+pub fn strip_ipv6_brackets(host: &str) -> &str {
+    host.strip_prefix('[')
+        .and_then(|h| h.strip_suffix(']'))
+        .unwrap_or(host)
+}
+
+/// Minimal config document shared by `from_env` and `template`: default ports and a `tvs` section
+fn base_config_json() -> serde_json::Value {
+    serde_json::json!({
+        "server": {
+            "cluster_message_port": 8080,
+            "app_port": 8081,
+            "admin_port": 8082
+        },
+        "tvs": {}
+    })
+}
+
+/// Environment variable naming the config file when `--config` isn't given
+pub const CONFIG_PATH_VAR: &str = "TVS_NODE_CONFIG";
+
+/// Config file used when neither `--config` nor `TVS_NODE_CONFIG` is set
+pub const DEFAULT_CONFIG_PATH: &str = "config.json";
+
+/// `--config` value that reads the config from stdin instead of a file
+pub const STDIN_CONFIG_PATH: &str = "-";
+
+/// Sibling file holding a profile's overrides: `config.json` with `prod` -> `config.prod.json`
+pub fn profile_path(config_path: &str, profile: &str) -> String {
+    let path = std::path::Path::new(config_path);
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .unwrap_or_default();
+    let name = match path.extension() {
+        Some(extension) => format!("{}.{}.{}", stem, profile, extension.to_string_lossy()),
+        None => format!("{}.{}", stem, profile),
+    };
+    path.with_file_name(name).to_string_lossy().into_owned()
+}
+
+/// Check a `--profile` name; it becomes part of a file name, so no separators or dots
+pub fn parse_profile(name: &str) -> Result<String, String> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(name.to_string())
+    } else {
+        Err(format!(
+            "profile names may only contain letters, digits, '-' and '_' (got {:?})",
+            name
+        ))
+    }
+}
+
+/// Merge `overlay` into `base`: objects merge key by key, recursively; anything else in
+/// `overlay` (including arrays) replaces the base value
+fn deep_merge(base: &mut serde_json::Value, overlay: serde_json::Value) {
+    match (base, overlay) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => deep_merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Pick the config file path: the `--config` flag, then `TVS_NODE_CONFIG`, then `config.json`
+/// An empty variable counts as unset, as container runtimes often pass them through empty
+pub fn resolve_config_path(flag: Option<String>, env: Option<String>) -> String {
+    flag.or(env.filter(|path| !path.is_empty()))
+        .unwrap_or_else(|| DEFAULT_CONFIG_PATH.to_string())
+}
+
+/// Environment variable holding a prefix applied to every override variable name,
+/// so several nodes (or other services) can share one environment without colliding
+pub const ENV_PREFIX_VAR: &str = "TVS_NODE_ENV_PREFIX";
+
+/// Resolve an override variable name, applying the `TVS_NODE_ENV_PREFIX` prefix (default empty)
+pub fn env_key(name: &str) -> String {
+    let prefix = std::env::var(ENV_PREFIX_VAR).unwrap_or_default();
+    format!("{}{}", prefix, name)
+}
+
+/// Read an override variable, honouring the `TVS_NODE_ENV_PREFIX` prefix
+pub fn env_var(name: &str) -> Result<String, std::env::VarError> {
+    std::env::var(env_key(name))
+}
+
+/// Read and parse an override from the environment
+/// Unset variables yield `None`; set but unparseable values are an error naming the variable
+fn env_override<T: std::str::FromStr>(name: &str) -> Result<Option<T>, ConfigError> {
+    let Some((name, raw)) = raw_env_override(name) else {
+        return Ok(None);
+    };
+
+    raw.trim()
+        .parse::<T>()
+        .map(Some)
+        .map_err(|_| ConfigError::InvalidEnv {
+            var: name,
+            value: raw.clone(),
+            expected: std::any::type_name::<T>(),
+        })
+}
+
+/// Look up an override variable, returning its prefixed name and raw value if set
+fn raw_env_override(name: &str) -> Option<(String, String)> {
+    let name = env_key(name);
+    let raw = match std::env::var(&name) {
+        Ok(raw) => raw,
+        Err(std::env::VarError::NotPresent) => return None,
+        Err(std::env::VarError::NotUnicode(raw)) => raw.to_string_lossy().into_owned(),
+    };
+    Some((name, raw))
+}
+
+/// Read a boolean override (`true`/`false`/`1`/`0`, case-insensitive) from the environment
+fn env_flag(name: &str) -> Result<Option<bool>, ConfigError> {
+    let Some((name, raw)) = raw_env_override(name) else {
+        return Ok(None);
+    };
+
+    match raw.trim().to_ascii_lowercase().as_str() {
+        "true" | "1" => Ok(Some(true)),
+        "false" | "0" => Ok(Some(false)),
+        _ => Err(ConfigError::InvalidEnv {
+            var: name,
+            value: raw,
+            expected: "true, false, 1 or 0",
+        }),
+    }
+}
+
+/// Strip a leading UTF-8 byte order mark
+fn strip_bom(content: &str) -> &str {
+    content.strip_prefix('\u{feff}').unwrap_or(content)
+}
+
+/// Rewrite JSON-with-comments into strict JSON
+/// Comments and trailing commas are replaced with whitespace (newlines are kept)
+/// so line/column numbers in serde errors still point at the original file
+fn relax_json(content: &str) -> String {
+    let chars: Vec<char> = content.chars().collect();
+    let mut out = String::with_capacity(content.len());
+    let mut in_string = false;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if in_string {
+            out.push(c);
+            if c == '\\' {
+                if let Some(&escaped) = chars.get(i + 1) {
+                    out.push(escaped);
+                    i += 1;
+                }
+            } else if c == '"' {
+                in_string = false;
+            }
+            i += 1;
+            continue;
+        }
+
+        match (c, chars.get(i + 1)) {
+            ('"', _) => {
+                in_string = true;
+                out.push(c);
+                i += 1;
+            }
+            ('/', Some('/')) => {
+                while i < chars.len() && chars[i] != '\n' {
+                    out.push(' ');
+                    i += 1;
+                }
+            }
+            ('/', Some('*')) => {
+                out.push_str("  ");
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    out.push(if chars[i] == '\n' { '\n' } else { ' ' });
+                    i += 1;
+                }
+                out.push_str("  ");
+                i += 2;
+            }
+            (',', _) if next_significant(&chars, i + 1).is_some_and(|n| n == '}' || n == ']') => {
+                out.push(' ');
+                i += 1;
+            }
+            _ => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+
+    out
+}
+
+/// Find the next character that isn't whitespace or part of a comment
+fn next_significant(chars: &[char], mut i: usize) -> Option<char> {
+    while i < chars.len() {
+        match (chars[i], chars.get(i + 1)) {
+            (c, _) if c.is_whitespace() => i += 1,
+            ('/', Some('/')) => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            ('/', Some('*')) => {
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    i += 1;
+                }
+                i += 2;
+            }
+            (c, _) => return Some(c),
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    #[test]
+    fn test_default_tvs_config() {
+        let config = TvsServerConfig::default();
+        assert_eq!(config.vote_port, 8090);
+        assert_eq!(config.vote_host, "127.0.0.1");
+        assert!(config.enabled);
+    }
+
+    #[test]
+    fn test_tvs_config_parsing() {
+        let json = r#"{
+            "server": {
+                "cluster_message_port": 8080,
+                "app_port": 8081,
+                "admin_port": 8082
+            },
+            "node_name": "test_node",
+            "tvs": {
+                "vote_port": 9000,
+                "vote_host": "0.0.0.0",
+                "enabled": true,
+                "root_url": "https://votes.example.com/vote"
+            }
+        }"#;
+
+        let config: TvsNodeConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.tfs.node_name, Some("test_node".to_string()));
+
+        let tvs = config.tvs_config().unwrap();
+        assert_eq!(tvs.vote_port, 9000);
+        assert_eq!(tvs.vote_host, "0.0.0.0");
+        assert_eq!(
+            tvs.root_url.as_deref(),
+            Some("https://votes.example.com/vote")
+        );
+    }
+
+    #[test]
+    fn test_tvs_config_disabled() {
+        let json = r#"{
+            "server": {
+                "cluster_message_port": 8080,
+                "app_port": 8081,
+                "admin_port": 8082
+            },
+            "tvs": {
+                "enabled": false
+            }
+        }"#;
+
+        let config: TvsNodeConfig = serde_json::from_str(json).unwrap();
+        assert!(config.tvs_config().is_none());
+        assert!(!config.should_start_vote_server());
+    }
+
+    #[test]
+    fn test_tvs_config_missing() {
+        let json = r#"{
+            "server": {
+                "cluster_message_port": 8080,
+                "app_port": 8081,
+                "admin_port": 8082
+            }
+        }"#;
+
+        let config: TvsNodeConfig = serde_json::from_str(json).unwrap();
+        assert!(config.tvs_config().is_none());
+        // The vote server still runs, on the default listener
+        assert!(config.should_start_vote_server());
+    }
+
+    #[test]
+    fn test_validation_without_tvs_section_checks_default_listener() {
+        let config: TvsNodeConfig = serde_json::from_value(serde_json::json!({
+            "server": {"cluster_message_port": 8080, "app_port": 8090, "admin_port": 8082}
+        }))
+        .unwrap();
+        assert!(config.tvs.is_none());
+
+        assert_eq!(
+            config.validation_report().errors,
+            vec!["server.app_port and tvs.vote_port both use port 8090".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_tvs_config_defaults() {
+        let json = r#"{
+            "server": {
+                "cluster_message_port": 8080,
+                "app_port": 8081,
+                "admin_port": 8082
+            },
+            "tvs": {}
+        }"#;
+
+        let config: TvsNodeConfig = serde_json::from_str(json).unwrap();
+        let tvs = config.tvs_config().unwrap();
+        assert_eq!(tvs.vote_port, 8090);
+        assert_eq!(tvs.vote_host, "127.0.0.1");
+        assert!(tvs.enabled);
+        assert!(tvs.root_url.is_none());
+        assert_eq!(tvs.shutdown_timeout_secs, 30);
+        assert_eq!(tvs.tfs_ready_timeout_secs, 30);
+    }
+
+    #[test]
+    fn test_shutdown_timeout_override() {
+        let json = r#"{
+            "server": {
+                "cluster_message_port": 8080,
+                "app_port": 8081,
+                "admin_port": 8082
+            },
+            "tvs": {
+                "shutdown_timeout_secs": 5
+            }
+        }"#;
+
+        let config: TvsNodeConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.tvs_config().unwrap().shutdown_timeout_secs, 5);
+        assert_eq!(TvsServerConfig::default().shutdown_timeout_secs, 30);
+    }
+
+    #[test]
+    fn test_tfs_ready_timeout_override() {
+        let json = r#"{
+            "server": {
+                "cluster_message_port": 8080,
+                "app_port": 8081,
+                "admin_port": 8082
+            },
+            "tvs": {
+                "tfs_ready_timeout_secs": 90
+            }
+        }"#;
+
+        let config: TvsNodeConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.tfs_ready_timeout_secs(), 90);
+
+        let config: TvsNodeConfig = serde_json::from_str(
+            r#"{"server": {"cluster_message_port": 8080, "app_port": 8081, "admin_port": 8082}}"#,
+        )
+        .unwrap();
+        assert_eq!(config.tfs_ready_timeout_secs(), 30);
+    }
+
+    #[test]
+    fn test_parse_multiple_listeners() {
+        let json = r#"{
+            "server": {
+                "cluster_message_port": 8080,
+                "app_port": 8081,
+                "admin_port": 8082
+            },
+            "tvs": {
+                "listeners": [
+                    { "host": "127.0.0.1", "port": 8090 },
+                    { "host": "0.0.0.0", "port": 9090, "enabled": true },
+                    { "port": 9091, "enabled": false }
+                ]
+            }
+        }"#;
+
+        let config: TvsNodeConfig = serde_json::from_str(json).unwrap();
+        let tvs = config.tvs_config().unwrap();
+        assert_eq!(tvs.listeners.len(), 3);
+        assert_eq!(tvs.listeners[2].host, "127.0.0.1");
+        assert_eq!(
+            tvs.active_listeners(),
+            vec![
+                ListenerConfig {
+                    host: "127.0.0.1".to_string(),
+                    port: 8090,
+                    enabled: true
+                },
+                ListenerConfig {
+                    host: "0.0.0.0".to_string(),
+                    port: 9090,
+                    enabled: true
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_tls() {
+        let json = r#"{
+            "server": {
+                "cluster_message_port": 8080,
+                "app_port": 8081,
+                "admin_port": 8082
+            },
+            "tvs": {
+                "tls": {
+                    "cert_path": "/etc/tvs/cert.pem",
+                    "key_path": "/etc/tvs/key.pem"
+                }
+            }
+        }"#;
+
+        let config: TvsNodeConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            config.tvs_config().unwrap().tls,
+            Some(TlsConfig {
+                cert_path: "/etc/tvs/cert.pem".to_string(),
+                key_path: "/etc/tvs/key.pem".to_string(),
+            })
+        );
+        assert!(TvsServerConfig::default().tls.is_none());
+
+        // Both paths are required
+        let missing_key = r#"{
+            "server": {"cluster_message_port": 8080, "app_port": 8081, "admin_port": 8082},
+            "tvs": {"tls": {"cert_path": "/etc/tvs/cert.pem"}}
+        }"#;
+        assert!(serde_json::from_str::<TvsNodeConfig>(missing_key).is_err());
+    }
+
+    #[test]
+    fn test_parse_listeners_toml() {
+        let toml = r#"
+            [server]
+            cluster_message_port = 8080
+            app_port = 8081
+            admin_port = 8082
+
+            [[tvs.listeners]]
+            host = "127.0.0.1"
+            port = 8090
+
+            [[tvs.listeners]]
+            host = "0.0.0.0"
+            port = 9090
+        "#;
+
+        let config = TvsNodeConfig::parse_config(toml, ConfigFormat::Toml, false).unwrap();
+        let ports: Vec<u16> = config
+            .tvs_config()
+            .unwrap()
+            .active_listeners()
+            .iter()
+            .map(|listener| listener.port)
+            .collect();
+        assert_eq!(ports, vec![8090, 9090]);
+    }
+
+    #[test]
+    fn test_single_listener_shim() {
+        let tvs = TvsServerConfig {
+            vote_port: 9000,
+            vote_host: "0.0.0.0".to_string(),
+            ..TvsServerConfig::default()
+        };
+        assert_eq!(
+            tvs.active_listeners(),
+            vec![ListenerConfig {
+                host: "0.0.0.0".to_string(),
+                port: 9000,
+                enabled: true
+            }]
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_bind_address_differs_from_vote_host() {
+        std::env::remove_var("TVS_VOTE_HOST");
+        let json = r#"{
+            "server": {
+                "cluster_message_port": 8080,
+                "app_port": 8081,
+                "admin_port": 8082
+            },
+            "tvs": {
+                "vote_host": "votes.example.com",
+                "bind_address": "0.0.0.0",
+                "vote_port": 9000
+            }
+        }"#;
+
+        let config: TvsNodeConfig = serde_json::from_str(json).unwrap();
+        let tvs = config.tvs_config().unwrap();
+        assert_eq!(tvs.vote_host, "votes.example.com");
+        assert_eq!(tvs.bind_host(), "0.0.0.0");
+        assert_eq!(
+            config.vote_listeners(),
+            vec![ListenerConfig {
+                host: "0.0.0.0".to_string(),
+                port: 9000,
+                enabled: true
+            }]
+        );
+
+        // Without bind_address the vote host is bound, as before
+        let tvs = TvsServerConfig {
+            bind_address: None,
+            ..tvs.clone()
+        };
+        assert_eq!(tvs.bind_host(), "votes.example.com");
+    }
+
+    #[test]
+    fn test_ip_version_parsing() {
+        let tvs: TvsServerConfig = serde_json::from_str("{}").unwrap();
+        assert_eq!(tvs.ip_version, IpVersion::Any);
+        for (value, expected) in [
+            ("v4", IpVersion::V4),
+            ("v6", IpVersion::V6),
+            ("any", IpVersion::Any),
+        ] {
+            let tvs: TvsServerConfig =
+                serde_json::from_str(&format!(r#"{{"ip_version": "{}"}}"#, value)).unwrap();
+            assert_eq!(tvs.ip_version, expected);
+            assert_eq!(expected.as_str(), value);
+        }
+        assert!(serde_json::from_str::<TvsServerConfig>(r#"{"ip_version": "v5"}"#).is_err());
+    }
+
+    #[test]
+    fn test_validate_bind_address() {
+        let mut config = minimal_config();
+        config.tvs.as_mut().unwrap().bind_address = Some("not an address".to_string());
+
+        let report = config.validation_report();
+        assert_eq!(
+            report.errors,
+            vec![
+                "tvs.bind_address must be an IP address or hostname (got \"not an address\")"
+                    .to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_listeners() {
+        let json = r#"{
+            "server": {
+                "cluster_message_port": 8080,
+                "app_port": 8081,
+                "admin_port": 8082
+            },
+            "tvs": {
+                "listeners": [
+                    { "port": 8081, "enabled": false },
+                    { "port": 9090 },
+                    { "host": "not an address", "port": 9090 }
+                ]
+            }
+        }"#;
+
+        let config: TvsNodeConfig = serde_json::from_str(json).unwrap();
+        let report = config.validation_report();
+        assert_eq!(
+            report.errors,
+            vec![
+                "tvs.listeners[1].port and tvs.listeners[2].port both use port 9090".to_string(),
+                "tvs.listeners[2].host must be an IP address or hostname (got \"not an address\")"
+                    .to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unix_socket_path() {
+        assert_eq!(
+            unix_socket_path("unix:/run/tvs/vote.sock"),
+            Some(Path::new("/run/tvs/vote.sock"))
+        );
+        assert_eq!(
+            unix_socket_path("unix:vote.sock"),
+            Some(Path::new("vote.sock"))
+        );
+        assert_eq!(unix_socket_path("127.0.0.1"), None);
+        assert_eq!(unix_socket_path("unixhost.example.com"), None);
+    }
+
+    #[test]
+    fn test_validate_unix_socket_vote_host() {
+        let mut config = minimal_config();
+        let tvs = config.tvs.as_mut().unwrap();
+        tvs.vote_host = "unix:/run/tvs/vote.sock".to_string();
+        // The port of a socket listener is unused, so it can't collide
+        tvs.vote_port = 8080;
+        let report = config.validation_report();
+        assert!(report.errors.is_empty(), "{:?}", report.errors);
+        assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+
+        config.tvs.as_mut().unwrap().vote_host = "unix:".to_string();
+        assert_eq!(
+            config.validation_report().errors,
+            vec!["tvs.vote_host needs a socket path after unix: (got \"unix:\")".to_string()]
+        );
+
+        let tvs = config.tvs.as_mut().unwrap();
+        tvs.vote_host = "unix:/run/tvs/vote.sock".to_string();
+        tvs.tls = Some(TlsConfig {
+            cert_path: "cert.pem".to_string(),
+            key_path: "key.pem".to_string(),
+        });
+        assert!(config.validation_report().errors[0].contains("can't be combined with tvs.tls"));
+    }
+
+    #[test]
+    fn test_validate_all_listeners_disabled() {
+        let json = r#"{
+            "server": {
+                "cluster_message_port": 8080,
+                "app_port": 8081,
+                "admin_port": 8082
+            },
+            "tvs": {
+                "listeners": [{ "port": 9090, "enabled": false }]
+            }
+        }"#;
+
+        let config: TvsNodeConfig = serde_json::from_str(json).unwrap();
+        let report = config.validation_report();
+        assert!(report.errors.is_empty());
+        assert!(report
+            .warnings
+            .iter()
+            .any(|w| w.contains("every entry in tvs.listeners is disabled")));
+    }
+
+    #[test]
+    #[serial]
+    fn test_vote_root_url_precedence() {
+        std::env::remove_var("TVS_ROOT_URL");
+        let mut config = minimal_config();
+        assert_eq!(config.vote_root_url(), DEFAULT_ROOT_URL);
+
+        config.tvs.as_mut().unwrap().root_url = Some("https://votes.example.com/vote".to_string());
+        assert_eq!(config.vote_root_url(), "https://votes.example.com/vote");
+
+        std::env::set_var("TVS_ROOT_URL", "http://10.0.0.5:8081/vote");
+        let root_url = config.vote_root_url();
+        std::env::remove_var("TVS_ROOT_URL");
+        assert_eq!(root_url, "http://10.0.0.5:8081/vote");
+    }
+
+    #[test]
+    fn test_validate_root_url() {
+        let mut config = minimal_config();
+        config.tvs.as_mut().unwrap().root_url = Some("https://votes.example.com/vote".to_string());
+        assert!(config.validate().is_ok());
+
+        config.tvs.as_mut().unwrap().root_url = Some("votes.example.com/vote".to_string());
+        assert!(config.validate().is_err());
+
+        config.tvs.as_mut().unwrap().root_url = Some("ftp://votes.example.com/vote".to_string());
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_cors_allowed_origins() {
+        let mut config = minimal_config();
+        config.tvs.as_mut().unwrap().cors_allowed_origins =
+            vec!["https://vote.example.com".to_string(), "*".to_string()];
+        assert!(config.validate().is_ok());
+
+        config.tvs.as_mut().unwrap().cors_allowed_origins =
+            vec!["https://vote.example.com/".to_string()];
+        match config.validate() {
+            Err(ConfigError::Validation(errors)) => {
+                assert_eq!(errors.len(), 1);
+                assert!(errors[0].starts_with("tvs.cors_allowed_origins[0]"));
+            }
+            other => panic!("expected a validation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_rate_limit_parsing_and_validation() {
+        let json = r#"{
+            "server": {
+                "cluster_message_port": 8080,
+                "app_port": 8081,
+                "admin_port": 8082
+            },
+            "tvs": {
+                "rate_limit": { "requests_per_second": 5, "burst": 20 }
+            }
+        }"#;
+        let mut config: TvsNodeConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            config.tvs_config().unwrap().rate_limit,
+            Some(RateLimitConfig {
+                requests_per_second: 5,
+                burst: 20,
+                status: 429,
+                retry_after_secs: None,
+            })
+        );
+        assert!(config.validate().is_ok());
+
+        config.tvs.as_mut().unwrap().rate_limit = Some(RateLimitConfig::new(0, 20));
+        match config.validate() {
+            Err(ConfigError::Validation(errors)) => assert_eq!(
+                errors,
+                vec!["tvs.rate_limit.requests_per_second must be positive".to_string()]
+            ),
+            other => panic!("expected a validation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_max_connections_validation() {
+        let mut config = minimal_config();
+        assert_eq!(config.tvs_config().unwrap().max_connections, None);
+
+        config.tvs.as_mut().unwrap().max_connections = Some(0);
+        assert_eq!(
+            config.validation_report().errors,
+            vec!["tvs.max_connections must be positive".to_string()]
+        );
+        config.tvs.as_mut().unwrap().max_connections = Some(512);
+        assert!(config.validation_report().errors.is_empty());
+    }
+
+    #[test]
+    fn test_refusal_status_validation() {
+        let mut config = minimal_config();
+        let tvs = config.tvs.as_mut().unwrap();
+        assert_eq!(tvs.max_connections_status, 503);
+        assert_eq!(tvs.max_connections_retry_after_secs, 1);
+
+        tvs.rate_limit = Some(RateLimitConfig {
+            status: 420,
+            ..RateLimitConfig::new(5, 20)
+        });
+        tvs.max_connections_status = 429;
+        assert!(config.validation_report().errors.is_empty());
+
+        let tvs = config.tvs.as_mut().unwrap();
+        tvs.rate_limit.as_mut().unwrap().status = 200;
+        tvs.max_connections_status = 999;
+        assert_eq!(
+            config.validation_report().errors,
+            vec![
+                "tvs.rate_limit.status must be a 4xx or 5xx status (got 200)".to_string(),
+                "tvs.max_connections_status must be a 4xx or 5xx status (got 999)".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tcp_options_default_and_validation() {
+        let mut config = minimal_config();
+        let tvs = config.tvs_config().unwrap();
+        assert_eq!(tvs.tcp_keepalive_secs, None);
+        assert!(!tvs.tcp_nodelay);
+
+        config.tvs.as_mut().unwrap().tcp_keepalive_secs = Some(0);
+        assert_eq!(
+            config.validation_report().errors,
+            vec!["tvs.tcp_keepalive_secs must be positive".to_string()]
+        );
+        config.tvs.as_mut().unwrap().tcp_keepalive_secs = Some(60);
+        assert!(config.validation_report().errors.is_empty());
+    }
+
+    #[test]
+    fn test_per_ip_connection_limit_validation() {
+        let mut config = minimal_config();
+        let tvs = config.tvs.as_mut().unwrap();
+        tvs.max_connections_per_ip = Some(0);
+        tvs.trusted_proxies = vec!["10.0.0.2".to_string(), "lb.internal".to_string()];
+        assert_eq!(
+            config.validation_report().errors,
+            vec![
+                "tvs.max_connections_per_ip must be positive".to_string(),
+                "tvs.trusted_proxies: \"lb.internal\" is not an IP address".to_string(),
+            ]
+        );
+
+        let tvs = config.tvs.as_mut().unwrap();
+        tvs.max_connections_per_ip = Some(16);
+        tvs.trusted_proxies = vec!["10.0.0.2".to_string(), "fd00::2".to_string()];
+        assert!(config.validation_report().errors.is_empty());
+    }
+
+    #[test]
+    fn test_metrics_checkpoint_default_and_validation() {
+        let mut config = minimal_config();
+        let tvs = config.tvs_config().unwrap();
+        assert_eq!(tvs.metrics_checkpoint_path, None);
+        assert_eq!(tvs.metrics_checkpoint_interval_secs, 60);
+
+        let tvs = config.tvs.as_mut().unwrap();
+        tvs.metrics_checkpoint_path = Some("/var/lib/tvs_node/metrics.json".to_string());
+        tvs.metrics_checkpoint_interval_secs = 0;
+        let report = config.validation_report();
+        assert_eq!(
+            report.errors,
+            vec!["tvs.metrics_checkpoint_interval_secs must be positive".to_string()]
+        );
+        // Only a build without the metrics feature has nothing to checkpoint
+        assert_eq!(
+            report
+                .warnings
+                .iter()
+                .any(|w| w.starts_with("tvs.metrics_checkpoint_path is set")),
+            !cfg!(feature = "metrics")
+        );
+    }
+
+    #[test]
+    fn test_max_body_bytes_default_and_validation() {
+        let json = r#"{
+            "server": {
+                "cluster_message_port": 8080,
+                "app_port": 8081,
+                "admin_port": 8082
+            },
+            "tvs": {}
+        }"#;
+        let mut config: TvsNodeConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.tvs_config().unwrap().max_body_bytes, Some(65536));
+        assert_eq!(TvsServerConfig::default().max_body_bytes, Some(65536));
+
+        let unlimited: TvsServerConfig =
+            serde_json::from_str(r#"{"max_body_bytes": null}"#).unwrap();
+        assert_eq!(unlimited.max_body_bytes, None);
+
+        config.tvs.as_mut().unwrap().max_body_bytes = Some(0);
+        match config.validate() {
+            Err(ConfigError::Validation(errors)) => assert_eq!(
+                errors,
+                vec!["tvs.max_body_bytes must be positive".to_string()]
+            ),
+            other => panic!("expected a validation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_access_log_defaults() {
+        let tvs: TvsServerConfig = serde_json::from_str("{}").unwrap();
+        assert!(tvs.access_log);
+        assert!(!tvs.access_log_probes);
+
+        let tvs: TvsServerConfig =
+            serde_json::from_str(r#"{"access_log": false, "access_log_probes": true}"#).unwrap();
+        assert!(!tvs.access_log);
+        assert!(tvs.access_log_probes);
+    }
+
+    #[test]
+    fn test_runtime_config_parsing() {
+        let json = r#"{
+            "server": {
+                "cluster_message_port": 8080,
+                "app_port": 8081,
+                "admin_port": 8082
+            },
+            "runtime": {
+                "nice": 10,
+                "max_uptime_secs": 86400
+            }
+        }"#;
+
+        let config: TvsNodeConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.runtime_config().nice, Some(10));
+        assert_eq!(config.runtime_config().max_uptime_secs, Some(86400));
+    }
+
+    #[test]
+    fn test_runtime_config_missing() {
+        let json = r#"{
+            "server": {
+                "cluster_message_port": 8080,
+                "app_port": 8081,
+                "admin_port": 8082
+            }
+        }"#;
+
+        let config: TvsNodeConfig = serde_json::from_str(json).unwrap();
+        assert!(config.runtime.is_none());
+        assert_eq!(config.runtime_config().nice, None);
+        assert_eq!(config.runtime_config().max_uptime_secs, None);
+        assert_eq!(config.max_uptime_secs(), None);
+    }
+
+    #[test]
+    fn test_max_lifetime_secs_is_an_alias_of_max_uptime_secs() {
+        let mut config = minimal_config();
+        config.tvs.as_mut().unwrap().max_lifetime_secs = Some(3600);
+        assert_eq!(config.max_uptime_secs(), Some(3600));
+
+        // Set in both places, the shorter limit applies
+        config.runtime = Some(RuntimeConfig {
+            max_uptime_secs: Some(86400),
+            ..RuntimeConfig::default()
+        });
+        assert_eq!(config.max_uptime_secs(), Some(3600));
+        config.tvs.as_mut().unwrap().max_lifetime_secs = None;
+        assert_eq!(config.max_uptime_secs(), Some(86400));
+    }
+
+    #[test]
+    fn test_config_with_bom() {
+        let json = "\u{feff}{\"server\": {\"cluster_message_port\": 8080, \"app_port\": 8081, \"admin_port\": 8082}}";
+
+        let config = TvsNodeConfig::parse_config(json, ConfigFormat::Json, false).unwrap();
+        assert_eq!(config.tfs.server.app_port, 8081);
+    }
+
+    #[test]
+    fn test_lenient_config_comments_and_trailing_commas() {
+        let json = r#"{
+            // Ports used by the TFS server
+            "server": {
+                "cluster_message_port": 8080,
+                "app_port": 8081, /* public API */
+                "admin_port": 8082,
+            },
+            "node_name": "http://not-a-comment//node",
+            "tvs": {
+                "vote_port": 9000,
+            },
+        }"#;
+
+        assert!(TvsNodeConfig::parse_config(json, ConfigFormat::Json, false).is_err());
+
+        let config = TvsNodeConfig::parse_config(json, ConfigFormat::Json, true).unwrap();
+        assert_eq!(config.tfs.server.admin_port, 8082);
+        assert_eq!(
+            config.tfs.node_name,
+            Some("http://not-a-comment//node".to_string())
+        );
+        assert_eq!(config.tvs_config().unwrap().vote_port, 9000);
+    }
+
+    #[test]
+    fn test_lenient_config_preserves_line_numbers() {
+        let json = "{\n  // comment\n  \"server\": @\n}";
+
+        let err = TvsNodeConfig::parse_json::<TvsNodeConfig>(json, true).unwrap_err();
+        assert_eq!(err.line(), 3);
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_from_env() {
+        std::env::set_var("APP_PORT", "9081");
+        std::env::set_var("NODE_NAME", "env_node");
+        std::env::set_var("NODE_UUID", "22222222-2222-2222-2222-222222222222");
+        std::env::set_var("TVS_VOTE_PORT", "9090");
+
+        let config = TvsNodeConfig::from_env();
+
+        std::env::remove_var("APP_PORT");
+        std::env::remove_var("NODE_NAME");
+        std::env::remove_var("NODE_UUID");
+        std::env::remove_var("TVS_VOTE_PORT");
+
+        let config = config.unwrap();
+        assert_eq!(config.tfs.server.cluster_message_port, 8080);
+        assert_eq!(config.tfs.server.app_port, 9081);
+        assert_eq!(config.tfs.server.admin_port, 8082);
+        assert_eq!(config.tfs.node_name, Some("env_node".to_string()));
+
+        let tvs = config.tvs_config().unwrap();
+        assert_eq!(tvs.vote_port, 9090);
+        assert_eq!(tvs.vote_host, "127.0.0.1");
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_from_env_requested() {
+        std::env::remove_var("TVS_CONFIG_FROM_ENV");
+        assert!(!TvsNodeConfig::config_from_env_requested());
+
+        std::env::set_var("TVS_CONFIG_FROM_ENV", "1");
+        assert!(TvsNodeConfig::config_from_env_requested());
+
+        std::env::set_var("TVS_CONFIG_FROM_ENV", "0");
+        assert!(!TvsNodeConfig::config_from_env_requested());
+
+        std::env::remove_var("TVS_CONFIG_FROM_ENV");
+    }
+
+    #[test]
+    fn test_persistence_config_parsing() {
+        let json = r#"{
+            "server": {
+                "cluster_message_port": 8080,
+                "app_port": 8081,
+                "admin_port": 8082
+            },
+            "persistence": {
+                "max_connection_lifetime_secs": 1800,
+                "idle_connection_timeout_secs": 300
+            }
+        }"#;
+
+        let config: TvsNodeConfig = serde_json::from_str(json).unwrap();
+        let persistence = config.persistence_config();
+        assert_eq!(persistence.max_connection_lifetime_secs, Some(1800));
+        assert_eq!(persistence.idle_connection_timeout_secs, Some(300));
+        assert_eq!(persistence.db_connect_retries, 5);
+        assert_eq!(persistence.db_connect_base_delay_ms, 500);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_persistence_pool_sizing_parsing() {
+        let json = r#"{
+            "server": {
+                "cluster_message_port": 8080,
+                "app_port": 8081,
+                "admin_port": 8082
+            },
+            "persistence": {
+                "max_pool_size": 32,
+                "min_idle": 4,
+                "connection_timeout_secs": 10
+            }
+        }"#;
+
+        let config: TvsNodeConfig = serde_json::from_str(json).unwrap();
+        let persistence = config.persistence_config();
+        assert_eq!(persistence.max_pool_size, Some(32));
+        assert_eq!(persistence.min_idle, Some(4));
+        assert_eq!(persistence.connection_timeout_secs, Some(10));
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_persistence_min_idle_above_max_is_rejected() {
+        let mut config = minimal_config();
+        config.persistence = Some(PersistenceConfig {
+            max_pool_size: Some(4),
+            min_idle: Some(8),
+            ..PersistenceConfig::default()
+        });
+
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("persistence.min_idle"));
+    }
+
+    #[test]
+    fn test_max_pool_size_leaves_room_for_the_migration_lock() {
+        let mut config = minimal_config();
+        for size in [0, 1] {
+            config.persistence = Some(PersistenceConfig {
+                max_pool_size: Some(size),
+                min_idle: Some(0),
+                ..PersistenceConfig::default()
+            });
+            let report = config.validation_report();
+            assert_eq!(
+                report.errors,
+                vec![format!(
+                    "persistence.max_pool_size must be at least 2 (got {}); startup holds one \
+                     connection for the migration lock while migrating on another",
+                    size
+                )]
+            );
+        }
+
+        config.persistence.as_mut().unwrap().max_pool_size = Some(MIN_POOL_SIZE);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    #[serial]
+    fn test_env_override_pool_sizing() {
+        std::env::set_var("DB_MAX_POOL_SIZE", "50");
+        std::env::set_var("DB_MIN_IDLE", "5");
+        std::env::set_var("DB_CONNECTION_TIMEOUT_SECS", "15");
+
+        // No persistence section in the file; the overrides create one
+        let mut config = minimal_config();
+        let result = config.apply_env_overrides();
+
+        std::env::remove_var("DB_MAX_POOL_SIZE");
+        std::env::remove_var("DB_MIN_IDLE");
+        std::env::remove_var("DB_CONNECTION_TIMEOUT_SECS");
+
+        assert!(result.is_ok());
+        let persistence = config.persistence_config();
+        assert_eq!(persistence.max_pool_size, Some(50));
+        assert_eq!(persistence.min_idle, Some(5));
+        assert_eq!(persistence.connection_timeout_secs, Some(15));
+    }
+
+    #[test]
+    #[serial]
+    fn test_env_override_database_url() {
+        std::env::set_var("DATABASE_URL", "postgres://tvs:secret@db:5432/tfs_tvs_db");
+
+        let mut config = minimal_config();
+        let result = config.apply_env_overrides();
+
+        std::env::remove_var("DATABASE_URL");
+
+        assert!(result.is_ok());
+        assert_eq!(
+            config.persistence_config().database_url.as_deref(),
+            Some("postgres://tvs:secret@db:5432/tfs_tvs_db")
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_database_url_unset_by_default() {
+        std::env::remove_var("DATABASE_URL");
+
+        let mut config = minimal_config();
+        config.apply_env_overrides().unwrap();
+
+        assert!(config.persistence.is_none());
+        assert!(config.persistence_config().database_url.is_none());
+    }
+
+    #[test]
+    fn test_redacted_json_round_trip() {
+        let mut config = minimal_config();
+        config.persistence = Some(PersistenceConfig {
+            database_url: Some("postgres://tvs:secret@db:5432/tfs_tvs_db".to_string()),
+            max_pool_size: Some(20),
+            ..PersistenceConfig::default()
+        });
+
+        let printed = config.to_redacted_json().unwrap();
+        assert!(!printed.contains("secret@db"));
+
+        let reparsed: TvsNodeConfig = serde_json::from_str(&printed).unwrap();
+        let persistence = reparsed.persistence_config();
+        assert_eq!(persistence.database_url.as_deref(), Some(REDACTED));
+        assert_eq!(persistence.max_pool_size, Some(20));
+        assert_eq!(reparsed.tfs.server.app_port, 8081);
+        assert_eq!(reparsed.tvs_config().unwrap().vote_port, 8090);
+    }
+
+    #[test]
+    fn test_redact_secrets_leaves_unset_values() {
+        let mut value = serde_json::json!({
+            "persistence": { "database_url": null },
+            "auth": { "api_token": "abc", "users": [{ "password": "hunter2" }] },
+            "node_name": "tvs_node_1"
+        });
+        redact_secrets(&mut value);
+
+        assert!(value["persistence"]["database_url"].is_null());
+        assert_eq!(value["auth"]["api_token"], REDACTED);
+        assert_eq!(value["auth"]["users"][0]["password"], REDACTED);
+        assert_eq!(value["node_name"], "tvs_node_1");
+    }
+
+    #[test]
+    fn test_persistence_connect_retry_parsing() {
+        let json = r#"{
+            "server": {
+                "cluster_message_port": 8080,
+                "app_port": 8081,
+                "admin_port": 8082
+            },
+            "persistence": {
+                "db_connect_retries": 10,
+                "db_connect_base_delay_ms": 250
+            }
+        }"#;
+
+        let config: TvsNodeConfig = serde_json::from_str(json).unwrap();
+        let persistence = config.persistence_config();
+        assert_eq!(persistence.db_connect_retries, 10);
+        assert_eq!(persistence.db_connect_base_delay_ms, 250);
+        assert!(persistence.max_connection_lifetime_secs.is_none());
+    }
+
+    #[test]
+    fn test_persistence_config_rejects_zero_durations() {
+        let json = r#"{
+            "server": {
+                "cluster_message_port": 8080,
+                "app_port": 8081,
+                "admin_port": 8082
+            },
+            "persistence": {
+                "idle_connection_timeout_secs": 0
+            }
+        }"#;
+
+        let config: TvsNodeConfig = serde_json::from_str(json).unwrap();
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("idle_connection_timeout_secs"));
+    }
+
+    #[test]
+    fn test_runtime_thread_stack_validation() {
+        let mut config: TvsNodeConfig = serde_json::from_str(
+            r#"{
+                "server": {
+                    "cluster_message_port": 8080,
+                    "app_port": 8081,
+                    "admin_port": 8082
+                },
+                "runtime": {
+                    "thread_stack_bytes": 8388608
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.runtime_config().thread_stack_bytes,
+            Some(8 * 1024 * 1024)
+        );
+        assert!(config.validate().is_ok());
+
+        config.runtime.as_mut().unwrap().thread_stack_bytes = Some(4096);
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("thread_stack_bytes"));
+    }
+
+    #[test]
+    fn test_runtime_ready_notify_validation() {
+        let mut config = minimal_config();
+        for target in ["systemd", "/run/tvs_node/ready.json"] {
+            config.runtime = Some(RuntimeConfig {
+                ready_notify: Some(target.to_string()),
+                ..RuntimeConfig::default()
+            });
+            assert!(config.validation_report().errors.is_empty(), "{}", target);
+        }
+
+        config.runtime.as_mut().unwrap().ready_notify = Some(" ".to_string());
+        assert_eq!(
+            config.validation_report().errors,
+            vec!["runtime.ready_notify must be a file path or \"systemd\"".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_readiness_thresholds_default_and_validation() {
+        let mut config = minimal_config();
+        assert_eq!(config.readiness_config(), ReadinessConfig::default());
+        assert_eq!(config.readiness_config().failure_threshold, 1);
+
+        config.readiness =
+            Some(serde_json::from_value(serde_json::json!({ "failure_threshold": 3 })).unwrap());
+        assert_eq!(config.readiness_config().success_threshold, 1);
+        assert!(config.validate().is_ok());
+
+        config.readiness = Some(ReadinessConfig {
+            failure_threshold: 0,
+            success_threshold: 0,
+        });
+        assert_eq!(
+            config.validation_report().errors,
+            vec![
+                "readiness.failure_threshold must be positive".to_string(),
+                "readiness.success_threshold must be positive".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validation_report_errors_and_warnings() {
+        let report = ValidationReport {
+            errors: vec![],
+            warnings: vec!["questionable".to_string()],
+        };
+        assert!(report.into_result(false).is_ok());
+
+        let report = ValidationReport {
+            errors: vec![],
+            warnings: vec!["questionable".to_string()],
+        };
+        let err = report.into_result(true).unwrap_err();
+        assert!(err.to_string().contains("questionable"));
+
+        let report = ValidationReport {
+            errors: vec!["broken".to_string()],
+            warnings: vec![],
+        };
+        assert!(report.into_result(false).is_err());
+    }
+
+    #[cfg(all(feature = "ephemeral", not(feature = "postgres")))]
+    #[test]
+    fn test_ephemeral_on_public_interface_warns() {
+        let json = r#"{
+            "server": {
+                "cluster_message_port": 8080,
+                "app_port": 8081,
+                "admin_port": 8082
+            },
+            "tvs": {
+                "vote_host": "0.0.0.0"
+            }
+        }"#;
+
+        let config: TvsNodeConfig = serde_json::from_str(json).unwrap();
+        let report = config.validation_report();
+        assert!(report.errors.is_empty());
+        assert_eq!(report.warnings.len(), 1);
+
+        assert!(config.validate().is_ok());
+        assert!(config.validate_with_strictness(true).is_err());
+    }
+
+    #[test]
+    fn test_config_source_display() {
+        let source = ConfigSource::File {
+            path: PathBuf::from("/etc/tvs/config.json"),
+            modified: Some(SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(86400)),
+        };
+        assert_eq!(
+            source.to_string(),
+            "file /etc/tvs/config.json (modified 1970-01-02T00:00:00+00:00)"
+        );
+
+        assert_eq!(
+            ConfigSource::Environment.to_string(),
+            "environment variables"
+        );
+    }
+
+    #[test]
+    fn test_config_source_for_missing_file() {
+        let source = ConfigSource::file("does-not-exist.json");
+        assert_eq!(
+            source,
+            ConfigSource::File {
+                path: PathBuf::from("does-not-exist.json"),
+                modified: None,
+            }
+        );
+    }
+
+    fn minimal_config() -> TvsNodeConfig {
+        serde_json::from_str(
+            r#"{
+                "server": {
+                    "cluster_message_port": 8080,
+                    "app_port": 8081,
+                    "admin_port": 8082
+                },
+                "tvs": {}
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    #[serial]
+    fn test_env_override_invalid_app_port() {
+        std::env::set_var("APP_PORT", "abc");
+        let mut config = minimal_config();
+        let result = config.apply_env_overrides();
+        std::env::remove_var("APP_PORT");
+
+        match result {
+            Err(ConfigError::InvalidEnv { var, value, .. }) => {
+                assert_eq!(var, "APP_PORT");
+                assert_eq!(value, "abc");
+            }
+            other => panic!("expected an invalid env error, got {:?}", other),
+        }
+        assert_eq!(config.tfs.server.app_port, 8081);
+    }
+
+    #[test]
+    #[serial]
+    fn test_env_override_invalid_vote_port() {
+        std::env::set_var("TVS_VOTE_PORT", "90o0");
+        let mut config = minimal_config();
+        let result = config.apply_env_overrides();
+        std::env::remove_var("TVS_VOTE_PORT");
+
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("TVS_VOTE_PORT"));
+        assert!(err.contains("90o0"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_env_override_unset_is_noop() {
+        let mut config = minimal_config();
+        assert!(config.apply_env_overrides().is_ok());
+        assert_eq!(config.tfs.server.app_port, 8081);
+        assert_eq!(config.tvs_config().unwrap().vote_port, 8090);
+    }
+
+    #[test]
+    #[serial]
+    fn test_env_override_valid_value() {
+        std::env::set_var("ADMIN_PORT", "9082");
+        let mut config = minimal_config();
+        let result = config.apply_env_overrides();
+        std::env::remove_var("ADMIN_PORT");
+
+        assert!(result.is_ok());
+        assert_eq!(config.tfs.server.admin_port, 9082);
+    }
+
+    #[test]
+    fn test_config_format_from_path() {
+        assert_eq!(ConfigFormat::from_path("config.json"), ConfigFormat::Json);
+        assert_eq!(ConfigFormat::from_path("config.toml"), ConfigFormat::Toml);
+        assert_eq!(ConfigFormat::from_path("config.yaml"), ConfigFormat::Yaml);
+        assert_eq!(
+            ConfigFormat::from_path("/etc/tvs/config.YML"),
+            ConfigFormat::Yaml
+        );
+        assert_eq!(
+            ConfigFormat::from_path("/etc/tvs/config"),
+            ConfigFormat::Json
+        );
+        assert_eq!(ConfigFormat::from_path("config.conf"), ConfigFormat::Json);
+    }
+
+    #[test]
+    fn test_toml_and_yaml_match_json() {
+        let json = r#"{
+            "server": {
+                "cluster_message_port": 8080,
+                "app_port": 8081,
+                "admin_port": 8082
+            },
+            "node_name": "test_node",
+            "tvs": {
+                "vote_port": 9000,
+                "vote_host": "0.0.0.0",
+                "enabled": true
+            }
+        }"#;
+
+        let toml = r#"
+            node_name = "test_node"
+
+            [server]
+            cluster_message_port = 8080
+            app_port = 8081
+            admin_port = 8082
+
+            [tvs]
+            vote_port = 9000
+            vote_host = "0.0.0.0"
+            enabled = true
+        "#;
 
-    /// Enable the vote server (default: true if tvs section exists)
-    #[serde(default = "default_enabled")]
-    pub enabled: bool,
-}
+        let yaml = r#"
+server:
+  cluster_message_port: 8080
+  app_port: 8081
+  admin_port: 8082
+node_name: test_node
+tvs:
+  vote_port: 9000
+  vote_host: "0.0.0.0"
+  enabled: true
+"#;
 
-fn default_vote_port() -> u16 {
-    8090
-}
+        let from_json = TvsNodeConfig::parse_config(json, ConfigFormat::Json, false).unwrap();
+        let from_toml = TvsNodeConfig::parse_config(toml, ConfigFormat::Toml, false).unwrap();
+        let from_yaml = TvsNodeConfig::parse_config(yaml, ConfigFormat::Yaml, false).unwrap();
 
-fn default_vote_host() -> String {
-    "127.0.0.1".to_string()
-}
+        let expected = serde_json::to_value(&from_json).unwrap();
+        assert_eq!(serde_json::to_value(&from_toml).unwrap(), expected);
+        assert_eq!(serde_json::to_value(&from_yaml).unwrap(), expected);
+    }
 
-fn default_enabled() -> bool {
-    true
-}
+    #[test]
+    fn test_parse_error_names_format() {
+        let path = std::env::temp_dir().join("tvs_node_invalid_config.toml");
+        std::fs::write(&path, "[server\napp_port = 8081").unwrap();
 
-impl Default for TvsServerConfig {
-    fn default() -> Self {
-        Self {
-            vote_port: default_vote_port(),
-            vote_host: default_vote_host(),
-            enabled: default_enabled(),
-        }
+        let err = TvsNodeConfig::read_config(path.to_str().unwrap()).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(err.to_string().contains("as TOML"));
     }
-}
 
-#[allow(dead_code)]
-impl TvsNodeConfig {
-    /// Read configuration from a JSON file
-    pub fn read_config(config_path: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        let config_content = std::fs::read_to_string(config_path)?;
-        let config: TvsNodeConfig = serde_json::from_str(&config_content)?;
-        Ok(config)
+    #[test]
+    fn test_config_format_from_str() {
+        assert_eq!("json".parse::<ConfigFormat>().unwrap(), ConfigFormat::Json);
+        assert_eq!("TOML".parse::<ConfigFormat>().unwrap(), ConfigFormat::Toml);
+        assert_eq!("yml".parse::<ConfigFormat>().unwrap(), ConfigFormat::Yaml);
+
+        let err = "xml".parse::<ConfigFormat>().unwrap_err();
+        assert!(matches!(err, ConfigError::UnknownFormat(ref f) if f == "xml"));
+        assert!(err.to_string().contains("unknown config format 'xml'"));
     }
 
-    /// Override config values with environment variables
-    /// This allows Docker containers to override config.json settings via env vars
-    pub fn apply_env_overrides(&mut self) {
-        // TFS server ports
-        if let Ok(port) = std::env::var("CLUSTER_MESSAGE_PORT") {
-            if let Ok(p) = port.parse::<u16>() {
-                self.tfs.server.cluster_message_port = p;
-            }
-        }
+    #[test]
+    fn test_read_config_with_explicit_format() {
+        // Mounted configs (e.g. Kubernetes) often have no extension to detect
+        let path = std::env::temp_dir().join("tvs_node_format_override_config");
+        std::fs::write(
+            &path,
+            "[server]\ncluster_message_port = 8080\napp_port = 8081\nadmin_port = 8082\n",
+        )
+        .unwrap();
+        let path_str = path.to_str().unwrap();
 
-        if let Ok(port) = std::env::var("APP_PORT") {
-            if let Ok(p) = port.parse::<u16>() {
-                self.tfs.server.app_port = p;
-            }
-        }
+        let detected = TvsNodeConfig::read_config(path_str);
+        let forced =
+            TvsNodeConfig::read_config_with_format(path_str, Some(ConfigFormat::Toml), false);
+        std::fs::remove_file(&path).unwrap();
 
-        if let Ok(port) = std::env::var("ADMIN_PORT") {
-            if let Ok(p) = port.parse::<u16>() {
-                self.tfs.server.admin_port = p;
-            }
-        }
+        assert!(detected.unwrap_err().to_string().contains("as JSON"));
+        assert_eq!(forced.unwrap().tfs.server.app_port, 8081);
+    }
 
-        // TVS vote server configuration
-        if let Some(ref mut tvs) = self.tvs {
-            if let Ok(host) = std::env::var("TVS_VOTE_HOST") {
-                tvs.vote_host = host;
-            }
+    #[test]
+    fn test_read_config_from_reader() {
+        let json = r#"{
+            "server": {
+                "cluster_message_port": 8080,
+                "app_port": 8081,
+                "admin_port": 8082
+            },
+            "node_name": "piped_node"
+        }"#;
 
-            if let Ok(port) = std::env::var("TVS_VOTE_PORT") {
-                if let Ok(p) = port.parse::<u16>() {
-                    tvs.vote_port = p;
-                }
-            }
-        }
+        let config = TvsNodeConfig::read_config_from_reader(json.as_bytes(), None, false).unwrap();
+        assert_eq!(config.tfs.node_name, Some("piped_node".to_string()));
+        assert_eq!(config.tfs.server.app_port, 8081);
 
-        // Node identification
-        if let Ok(name) = std::env::var("NODE_NAME") {
-            self.tfs.node_name = Some(name);
-        }
-    }
+        let toml = "node_name = \"piped_node\"\n[server]\ncluster_message_port = 8080\napp_port = 8081\nadmin_port = 8082\n";
+        let config = TvsNodeConfig::read_config_from_reader(
+            toml.as_bytes(),
+            Some(ConfigFormat::Toml),
+            false,
+        )
+        .unwrap();
+        assert_eq!(config.tfs.node_name, Some("piped_node".to_string()));
 
-    /// Get the TFS app config
-    pub fn tfs_config(&self) -> &AppConfig {
-        &self.tfs
+        // Without --format the input is JSON, so TOML doesn't parse
+        let err = TvsNodeConfig::read_config_from_reader(toml.as_bytes(), None, false).unwrap_err();
+        assert!(
+            matches!(err, ConfigError::Parse { ref origin, format: ConfigFormat::Json, .. } if origin == "stdin")
+        );
     }
 
-    /// Get the TVS server config, or None if disabled
-    pub fn tvs_config(&self) -> Option<&TvsServerConfig> {
-        self.tvs.as_ref().filter(|c| c.enabled)
+    #[test]
+    fn test_read_config_from_empty_reader() {
+        for input in ["", "  \n", "\u{feff}"] {
+            let err =
+                TvsNodeConfig::read_config_from_reader(input.as_bytes(), None, false).unwrap_err();
+            assert!(matches!(err, ConfigError::EmptyStdin));
+            assert!(err.to_string().contains("no config received on stdin"));
+        }
+        assert_eq!(ConfigSource::Stdin.to_string(), "stdin");
     }
 
-    /// Check if TVS vote server should be started
-    pub fn should_start_vote_server(&self) -> bool {
-        self.tvs_config().is_some()
-    }
-}
+    #[test]
+    fn test_config_error_variants() {
+        let err = TvsNodeConfig::read_config("does-not-exist.json").unwrap_err();
+        assert!(matches!(err, ConfigError::NotFound { ref path } if path == "does-not-exist.json"));
+        assert_eq!(
+            err.to_string(),
+            "config file 'does-not-exist.json' not found; create one with `tvs_node generate-config`"
+        );
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let path = std::env::temp_dir().join("tvs_node_missing_field_config.json");
+        std::fs::write(&path, r#"{"node_name": "no_server_section"}"#).unwrap();
+        let err = TvsNodeConfig::read_config(path.to_str().unwrap()).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            err,
+            ConfigError::Parse {
+                format: ConfigFormat::Json,
+                ..
+            }
+        ));
+
+        let mut config = minimal_config();
+        config.runtime = Some(RuntimeConfig {
+            thread_stack_bytes: Some(1),
+            ..Default::default()
+        });
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::Validation(ref errors)) if errors.len() == 1
+        ));
+    }
 
     #[test]
-    fn test_default_tvs_config() {
-        let config = TvsServerConfig::default();
-        assert_eq!(config.vote_port, 8090);
-        assert_eq!(config.vote_host, "127.0.0.1");
-        assert!(config.enabled);
+    fn test_config_read_error_classification() {
+        use std::io::{Error, ErrorKind};
+
+        assert!(matches!(
+            ConfigError::from_read("a.json", Error::from(ErrorKind::NotFound)),
+            ConfigError::NotFound { .. }
+        ));
+
+        // Tests usually run as a user who can read anything, so build the error directly
+        let denied = ConfigError::from_read("a.json", Error::from(ErrorKind::PermissionDenied));
+        assert!(matches!(denied, ConfigError::PermissionDenied { ref path } if path == "a.json"));
+        assert!(denied.to_string().contains("permission denied"));
+
+        assert!(matches!(
+            ConfigError::from_read("a.json", Error::from(ErrorKind::InvalidData)),
+            ConfigError::Io { .. }
+        ));
+        assert_eq!(
+            crate::error::exit_code(&ConfigError::NotFound {
+                path: "a.json".to_string()
+            }),
+            2
+        );
     }
 
     #[test]
-    fn test_tvs_config_parsing() {
+    fn test_validate_valid_ports() {
         let json = r#"{
             "server": {
                 "cluster_message_port": 8080,
                 "app_port": 8081,
                 "admin_port": 8082
             },
-            "node_name": "test_node",
             "tvs": {
-                "vote_port": 9000,
-                "vote_host": "0.0.0.0",
-                "enabled": true
+                "vote_port": 8090,
+                "vote_host": "::1"
             }
         }"#;
 
         let config: TvsNodeConfig = serde_json::from_str(json).unwrap();
-        assert_eq!(config.tfs.node_name, Some("test_node".to_string()));
-
-        let tvs = config.tvs_config().unwrap();
-        assert_eq!(tvs.vote_port, 9000);
-        assert_eq!(tvs.vote_host, "0.0.0.0");
+        assert!(config.validate().is_ok());
     }
 
     #[test]
-    fn test_tvs_config_disabled() {
+    fn test_validate_colliding_ports() {
         let json = r#"{
             "server": {
                 "cluster_message_port": 8080,
@@ -162,45 +3374,597 @@ mod tests {
                 "admin_port": 8082
             },
             "tvs": {
-                "enabled": false
+                "vote_port": 8081
             }
         }"#;
 
         let config: TvsNodeConfig = serde_json::from_str(json).unwrap();
-        assert!(config.tvs_config().is_none());
-        assert!(!config.should_start_vote_server());
+        match config.validate() {
+            Err(ConfigError::Validation(errors)) => {
+                assert_eq!(
+                    errors,
+                    vec!["server.app_port and tvs.vote_port both use port 8081".to_string()]
+                );
+            }
+            other => panic!("expected a validation error, got {:?}", other),
+        }
     }
 
     #[test]
-    fn test_tvs_config_missing() {
+    fn test_validate_disabled_vote_server_ignores_vote_port() {
         let json = r#"{
             "server": {
                 "cluster_message_port": 8080,
                 "app_port": 8081,
                 "admin_port": 8082
+            },
+            "tvs": {
+                "vote_port": 8081,
+                "enabled": false
             }
         }"#;
 
         let config: TvsNodeConfig = serde_json::from_str(json).unwrap();
-        assert!(config.tvs_config().is_none());
-        assert!(!config.should_start_vote_server());
+        assert!(config.validate().is_ok());
     }
 
     #[test]
-    fn test_tvs_config_defaults() {
+    fn test_is_valid_bind_host() {
+        for host in [
+            "127.0.0.1",
+            "0.0.0.0",
+            "::",
+            "::1",
+            "[::1]",
+            "localhost",
+            "votes.internal",
+        ] {
+            assert!(is_valid_bind_host(host), "{} should be accepted", host);
+        }
+        for host in [
+            "",
+            "not an address",
+            "-bad.example",
+            "a..b",
+            "[votes.internal]",
+        ] {
+            assert!(!is_valid_bind_host(host), "{} should be rejected", host);
+        }
+    }
+
+    #[test]
+    fn test_log_format_parsing() {
+        let config = minimal_config();
+        assert_eq!(config.log_format, LogFormat::Text);
+
+        for (value, expected) in [("text", LogFormat::Text), ("json", LogFormat::Json)] {
+            let json = format!(
+                r#"{{
+                    "server": {{
+                        "cluster_message_port": 8080,
+                        "app_port": 8081,
+                        "admin_port": 8082
+                    }},
+                    "log_format": "{}"
+                }}"#,
+                value
+            );
+            let config: TvsNodeConfig = serde_json::from_str(&json).unwrap();
+            assert_eq!(config.log_format, expected);
+            assert_eq!(value.parse::<LogFormat>().unwrap(), expected);
+        }
+
+        assert!("xml".parse::<LogFormat>().is_err());
+    }
+
+    #[test]
+    fn test_log_level_parsing() {
+        for (value, expected) in [
+            ("trace", LogLevel::Trace),
+            ("debug", LogLevel::Debug),
+            ("INFO", LogLevel::Info),
+            ("warn", LogLevel::Warn),
+            ("error", LogLevel::Error),
+        ] {
+            let level = value.parse::<LogLevel>().unwrap();
+            assert_eq!(level, expected);
+            assert_eq!(level.as_str(), value.to_ascii_lowercase());
+        }
+
+        let err = "verbose".parse::<LogLevel>().unwrap_err();
+        assert!(err.contains("verbose"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_env_override_log_format() {
+        std::env::set_var("LOG_FORMAT", "JSON");
+        let mut config = minimal_config();
+        let result = config.apply_env_overrides();
+        std::env::remove_var("LOG_FORMAT");
+
+        assert!(result.is_ok());
+        assert_eq!(config.log_format, LogFormat::Json);
+
+        std::env::set_var("LOG_FORMAT", "logfmt");
+        let result = config.apply_env_overrides();
+        std::env::remove_var("LOG_FORMAT");
+
+        assert!(matches!(
+            result,
+            Err(ConfigError::InvalidEnv { ref var, .. }) if var == "LOG_FORMAT"
+        ));
+    }
+
+    #[test]
+    fn test_resolve_admin_frontend() {
+        assert!(resolve_admin_frontend(None, true));
+        assert!(resolve_admin_frontend(Some(true), true));
+        assert!(!resolve_admin_frontend(Some(false), true));
+        assert!(!resolve_admin_frontend(None, false));
+        assert!(!resolve_admin_frontend(Some(true), false));
+    }
+
+    #[test]
+    fn test_admin_frontend_enabled_parsing() {
+        let mut config: TvsNodeConfig = serde_json::from_str(
+            r#"{
+                "server": {
+                    "cluster_message_port": 8080,
+                    "app_port": 8081,
+                    "admin_port": 8082
+                },
+                "admin_frontend_enabled": false
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(config.admin_frontend_enabled, Some(false));
+        assert!(!config.admin_frontend_active());
+
+        // Requesting a frontend the build doesn't have is only worth a warning
+        config.admin_frontend_enabled = Some(true);
+        let report = config.validation_report();
+        assert!(report.errors.is_empty());
+        assert_eq!(
+            report.warnings.len(),
+            usize::from(!cfg!(feature = "admin-frontend"))
+        );
+        assert_eq!(
+            config.admin_frontend_active(),
+            cfg!(feature = "admin-frontend")
+        );
+    }
+
+    #[test]
+    fn test_validate_zero_port_and_bad_host() {
         let json = r#"{
             "server": {
-                "cluster_message_port": 8080,
+                "cluster_message_port": 0,
                 "app_port": 8081,
                 "admin_port": 8082
             },
-            "tvs": {}
+            "tvs": {
+                "vote_host": "not an address"
+            }
         }"#;
 
         let config: TvsNodeConfig = serde_json::from_str(json).unwrap();
+        let report = config.validation_report();
+        assert_eq!(report.errors.len(), 2);
+        assert!(report.errors[0].contains("server.cluster_message_port"));
+        assert!(report.errors[1].contains("tvs.vote_host"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_env_override_enables_absent_vote_server() {
+        std::env::set_var("TVS_VOTE_ENABLED", "1");
+        std::env::set_var("TVS_VOTE_PORT", "9090");
+
+        let mut config = minimal_config();
+        config.tvs = None;
+        let result = config.apply_env_overrides();
+
+        std::env::remove_var("TVS_VOTE_ENABLED");
+        std::env::remove_var("TVS_VOTE_PORT");
+
+        assert!(result.is_ok());
         let tvs = config.tvs_config().unwrap();
+        assert!(tvs.enabled);
+        assert_eq!(tvs.vote_port, 9090);
+        assert_eq!(tvs.vote_host, "127.0.0.1");
+    }
+
+    #[test]
+    #[serial]
+    fn test_absent_tvs_section_uses_documented_defaults() {
+        for var in ["TVS_VOTE_ENABLED", "TVS_VOTE_HOST", "TVS_VOTE_PORT"] {
+            std::env::remove_var(var);
+        }
+        let mut config = minimal_config();
+        config.tvs = None;
+        config.apply_env_overrides().unwrap();
+
+        assert!(config.tvs.is_none());
+        let tvs = config.vote_server_config();
         assert_eq!(tvs.vote_port, 8090);
         assert_eq!(tvs.vote_host, "127.0.0.1");
         assert!(tvs.enabled);
+        assert_eq!(
+            config.vote_listeners(),
+            vec![ListenerConfig {
+                host: "127.0.0.1".to_string(),
+                port: 8090,
+                enabled: true
+            }]
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_env_override_without_tvs_section_is_validated() {
+        std::env::set_var("TVS_VOTE_HOST", "not a host!");
+
+        let mut config = minimal_config();
+        config.tvs = None;
+        let result = config.apply_env_overrides();
+        std::env::remove_var("TVS_VOTE_HOST");
+        result.unwrap();
+
+        assert_eq!(config.vote_listeners()[0].host, "not a host!");
+        match config.validate() {
+            Err(ConfigError::Validation(errors)) => {
+                assert!(errors[0].contains("tvs.vote_host"), "{:?}", errors)
+            }
+            other => panic!("expected a validation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_env_override_disables_vote_server() {
+        std::env::set_var("TVS_VOTE_ENABLED", "False");
+
+        let mut config = minimal_config();
+        let result = config.apply_env_overrides();
+
+        std::env::remove_var("TVS_VOTE_ENABLED");
+
+        assert!(result.is_ok());
+        assert!(!config.tvs.as_ref().unwrap().enabled);
+        assert!(!config.should_start_vote_server());
+    }
+
+    #[test]
+    #[serial]
+    fn test_env_override_invalid_vote_enabled() {
+        std::env::set_var("TVS_VOTE_ENABLED", "maybe");
+
+        let mut config = minimal_config();
+        let result = config.apply_env_overrides();
+
+        std::env::remove_var("TVS_VOTE_ENABLED");
+
+        assert!(matches!(
+            result,
+            Err(ConfigError::InvalidEnv { ref var, .. }) if var == "TVS_VOTE_ENABLED"
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn test_env_override_with_prefix() {
+        std::env::set_var(ENV_PREFIX_VAR, "TVSNODE_");
+        std::env::set_var("TVSNODE_APP_PORT", "9081");
+        std::env::set_var("TVSNODE_TVS_VOTE_HOST", "0.0.0.0");
+        std::env::set_var("TVSNODE_NODE_NAME", "prefixed_node");
+        // Unprefixed variables belong to someone else once a prefix is set
+        std::env::set_var("ADMIN_PORT", "9999");
+
+        let mut config = minimal_config();
+        let result = config.apply_env_overrides();
+
+        std::env::remove_var(ENV_PREFIX_VAR);
+        std::env::remove_var("TVSNODE_APP_PORT");
+        std::env::remove_var("TVSNODE_TVS_VOTE_HOST");
+        std::env::remove_var("TVSNODE_NODE_NAME");
+        std::env::remove_var("ADMIN_PORT");
+
+        assert!(result.is_ok());
+        assert_eq!(config.tfs.server.app_port, 9081);
+        assert_eq!(config.tfs.server.admin_port, 8082);
+        assert_eq!(config.tfs.node_name, Some("prefixed_node".to_string()));
+        assert_eq!(config.tvs_config().unwrap().vote_host, "0.0.0.0");
+    }
+
+    #[test]
+    #[serial]
+    fn test_env_override_invalid_value_names_prefixed_var() {
+        std::env::set_var(ENV_PREFIX_VAR, "TVSNODE_");
+        std::env::set_var("TVSNODE_CLUSTER_MESSAGE_PORT", "nope");
+
+        let mut config = minimal_config();
+        let result = config.apply_env_overrides();
+
+        std::env::remove_var(ENV_PREFIX_VAR);
+        std::env::remove_var("TVSNODE_CLUSTER_MESSAGE_PORT");
+
+        assert!(matches!(
+            result,
+            Err(ConfigError::InvalidEnv { ref var, .. }) if var == "TVSNODE_CLUSTER_MESSAGE_PORT"
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn test_env_key_without_prefix() {
+        std::env::remove_var(ENV_PREFIX_VAR);
+        assert_eq!(env_key("APP_PORT"), "APP_PORT");
+    }
+
+    #[test]
+    fn test_resolve_config_path_precedence() {
+        let flag = Some("flag.json".to_string());
+        let env = Some("env.json".to_string());
+
+        assert_eq!(resolve_config_path(flag.clone(), env.clone()), "flag.json");
+        assert_eq!(resolve_config_path(flag, None), "flag.json");
+        assert_eq!(resolve_config_path(None, env), "env.json");
+        assert_eq!(resolve_config_path(None, None), DEFAULT_CONFIG_PATH);
+        assert_eq!(
+            resolve_config_path(None, Some(String::new())),
+            DEFAULT_CONFIG_PATH
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_require_node_name_missing() {
+        std::env::remove_var("NODE_NAME");
+        let mut config: TvsNodeConfig = serde_json::from_value(serde_json::json!({
+            "server": {"cluster_message_port": 8080, "app_port": 8081, "admin_port": 8082},
+            "require_node_name": true
+        }))
+        .unwrap();
+        config.apply_env_overrides().unwrap();
+
+        match config.validate() {
+            Err(ConfigError::Validation(errors)) => {
+                assert_eq!(errors.len(), 1);
+                assert!(errors[0].contains("require_node_name"));
+            }
+            other => panic!("expected a validation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_require_node_name_present() {
+        std::env::remove_var("NODE_NAME");
+        let json = serde_json::json!({
+            "server": {"cluster_message_port": 8080, "app_port": 8081, "admin_port": 8082},
+            "require_node_name": true
+        });
+
+        let mut from_file = json.clone();
+        from_file["node_name"] = serde_json::json!("tvs_node_1");
+        let config: TvsNodeConfig = serde_json::from_value(from_file).unwrap();
+        assert!(config.validate().is_ok());
+
+        std::env::set_var("NODE_NAME", "env_node");
+        let mut from_env: TvsNodeConfig = serde_json::from_value(json).unwrap();
+        let overridden = from_env.apply_env_overrides();
+        std::env::remove_var("NODE_NAME");
+        overridden.unwrap();
+        assert!(from_env.validate().is_ok());
+    }
+
+    #[test]
+    fn test_vote_service_required_default() {
+        assert!(minimal_config().vote_service_required);
+
+        let config: TvsNodeConfig = serde_json::from_value(serde_json::json!({
+            "server": {"cluster_message_port": 8080, "app_port": 8081, "admin_port": 8082},
+            "vote_service_required": false
+        }))
+        .unwrap();
+        assert!(!config.vote_service_required);
+    }
+
+    #[test]
+    fn test_deep_merge_nested_sections() {
+        let mut base = serde_json::json!({
+            "server": {"cluster_message_port": 8080, "app_port": 8081, "admin_port": 8082},
+            "tvs": {"vote_port": 8090, "vote_host": "127.0.0.1", "cors_allowed_origins": ["a"]},
+            "log_level": "info"
+        });
+        deep_merge(
+            &mut base,
+            serde_json::json!({
+                "server": {"app_port": 9081},
+                "tvs": {"vote_host": "0.0.0.0", "cors_allowed_origins": ["b", "c"]},
+                "log_level": "warn"
+            }),
+        );
+
+        assert_eq!(
+            base,
+            serde_json::json!({
+                "server": {"cluster_message_port": 8080, "app_port": 9081, "admin_port": 8082},
+                "tvs": {"vote_port": 8090, "vote_host": "0.0.0.0", "cors_allowed_origins": ["b", "c"]},
+                "log_level": "warn"
+            })
+        );
+    }
+
+    #[test]
+    fn test_deep_merge_absent_sections() {
+        // A section only the profile has is added whole; one it leaves out is kept as-is
+        let mut base = serde_json::json!({
+            "server": {"app_port": 8081},
+            "tvs": null
+        });
+        deep_merge(
+            &mut base,
+            serde_json::json!({"tvs": {"vote_port": 9090}, "runtime": {"nice": 5}}),
+        );
+
+        assert_eq!(
+            base,
+            serde_json::json!({
+                "server": {"app_port": 8081},
+                "tvs": {"vote_port": 9090},
+                "runtime": {"nice": 5}
+            })
+        );
+    }
+
+    #[test]
+    fn test_profile_path() {
+        assert_eq!(profile_path("config.json", "prod"), "config.prod.json");
+        assert_eq!(
+            profile_path("/etc/tvs/node.toml", "staging"),
+            "/etc/tvs/node.staging.toml"
+        );
+        assert_eq!(profile_path("config", "dev"), "config.dev");
+    }
+
+    #[test]
+    fn test_parse_profile() {
+        assert_eq!(parse_profile("prod").unwrap(), "prod");
+        assert_eq!(parse_profile("eu-west_2").unwrap(), "eu-west_2");
+        assert!(parse_profile("").is_err());
+        assert!(parse_profile("../prod").is_err());
+        assert!(parse_profile("prod.json").is_err());
+    }
+
+    #[test]
+    fn test_with_profile_overrides_base() {
+        let path = std::env::temp_dir().join("tvs_node_profile_config.prod.json");
+        std::fs::write(
+            &path,
+            r#"{"server": {"app_port": 9081}, "tvs": {"vote_host": "0.0.0.0"}}"#,
+        )
+        .unwrap();
+        let base: TvsNodeConfig = serde_json::from_value(serde_json::json!({
+            "server": {"cluster_message_port": 8080, "app_port": 8081, "admin_port": 8082},
+            "tvs": {"vote_port": 8090}
+        }))
+        .unwrap();
+
+        let config = base.with_profile("prod", path.to_str().unwrap(), None, false);
+        std::fs::remove_file(&path).unwrap();
+
+        let config = config.unwrap();
+        assert_eq!(config.tfs.server.app_port, 9081);
+        assert_eq!(config.tfs.server.cluster_message_port, 8080);
+        let tvs = config.tvs_config().unwrap();
+        assert_eq!(tvs.vote_port, 8090);
+        assert_eq!(tvs.vote_host, "0.0.0.0");
+    }
+
+    #[test]
+    fn test_with_missing_profile_is_an_error() {
+        let base = TvsNodeConfig::template();
+        let path = std::env::temp_dir().join("tvs_node_no_such_config.prod.json");
+
+        let err = base
+            .with_profile("prod", path.to_str().unwrap(), None, false)
+            .unwrap_err();
+        assert!(
+            matches!(err, ConfigError::ProfileNotFound { ref profile, .. } if profile == "prod")
+        );
+        assert!(err
+            .to_string()
+            .contains("tvs_node_no_such_config.prod.json"));
+    }
+
+    #[test]
+    fn test_with_overlay_changes_only_vote_port() {
+        let dir = std::env::temp_dir();
+        let base_path = dir.join("tvs_node_overlay_base.json");
+        let overlay_path = dir.join("tvs_node_overlay_staging.toml");
+        std::fs::write(
+            &base_path,
+            r#"{
+                "server": {"cluster_message_port": 8080, "app_port": 8081, "admin_port": 8082},
+                "node_name": "base_node",
+                "tvs": {"vote_port": 8090, "vote_host": "0.0.0.0", "max_body_bytes": 1024}
+            }"#,
+        )
+        .unwrap();
+        std::fs::write(&overlay_path, "[tvs]\nvote_port = 9090\n").unwrap();
+
+        let base = TvsNodeConfig::read_config(base_path.to_str().unwrap()).unwrap();
+        let merged = base
+            .clone()
+            .with_overlay(overlay_path.to_str().unwrap(), false);
+        std::fs::remove_file(&base_path).unwrap();
+        std::fs::remove_file(&overlay_path).unwrap();
+
+        let mut merged = serde_json::to_value(merged.unwrap()).unwrap();
+        assert_eq!(merged["tvs"]["vote_port"], 9090);
+        // Everything else is the base's, down to the other keys of the merged section
+        merged["tvs"]["vote_port"] = 8090.into();
+        assert_eq!(merged, serde_json::to_value(base).unwrap());
+    }
+
+    #[test]
+    fn test_with_missing_overlay_is_an_error() {
+        let path = std::env::temp_dir().join("tvs_node_no_such_overlay.toml");
+        let err = TvsNodeConfig::template()
+            .with_overlay(path.to_str().unwrap(), false)
+            .unwrap_err();
+        assert!(matches!(err, ConfigError::NotFound { .. }));
+    }
+
+    #[test]
+    fn test_port_offset_shifts_every_port() {
+        let mut config = minimal_config();
+        config.tvs.as_mut().unwrap().listeners = vec![ListenerConfig {
+            host: "0.0.0.0".to_string(),
+            port: 9443,
+            enabled: true,
+        }];
+        config.apply_port_offset(10).unwrap();
+
+        assert_eq!(config.tfs.server.cluster_message_port, 8090);
+        assert_eq!(config.tfs.server.app_port, 8091);
+        assert_eq!(config.tfs.server.admin_port, 8092);
+        let tvs = config.tvs_config().unwrap();
+        assert_eq!(tvs.vote_port, 8100);
+        assert_eq!(tvs.listeners[0].port, 9453);
+    }
+
+    #[test]
+    fn test_port_offset_without_tvs_section_shifts_default_vote_port() {
+        let mut config = minimal_config();
+        config.tvs = None;
+        config.apply_port_offset(1).unwrap();
+        assert_eq!(config.vote_listeners()[0].port, default_vote_port() + 1);
+
+        let mut untouched = minimal_config();
+        untouched.tvs = None;
+        untouched.apply_port_offset(0).unwrap();
+        assert!(untouched.tvs.is_none());
+    }
+
+    #[test]
+    fn test_port_offset_overflow_is_rejected() {
+        let mut config = minimal_config();
+        config.tfs.server.admin_port = 65530;
+
+        match config.apply_port_offset(6) {
+            Err(ConfigError::Validation(errors)) => assert_eq!(
+                errors,
+                vec!["server.admin_port (65530) plus --port-offset 6 is past 65535".to_string()]
+            ),
+            other => panic!("expected a validation error, got {:?}", other),
+        }
+
+        let mut config = minimal_config();
+        config.tfs.server.admin_port = 65530;
+        assert!(config.apply_port_offset(5).is_ok());
+        assert_eq!(config.tfs.server.admin_port, 65535);
     }
 }
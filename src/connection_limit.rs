@@ -0,0 +1,332 @@
+use std::{
+    collections::{HashMap, HashSet},
+    future::Ready,
+    io,
+    net::{IpAddr, SocketAddr},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll},
+};
+
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    net::{TcpListener, TcpStream},
+};
+
+use crate::{config::TvsServerConfig, socket_options::SocketOptions};
+
+/// Open vote connections per client IP, shared by every listener
+/// Trusted proxies are exempt: each of their connections carries some other client
+#[derive(Debug, Clone)]
+pub struct PerIpLimit {
+    max: usize,
+    exempt: Arc<HashSet<IpAddr>>,
+    open: Arc<Mutex<HashMap<IpAddr, usize>>>,
+}
+
+impl PerIpLimit {
+    pub fn new(max: usize, exempt: impl IntoIterator<Item = IpAddr>) -> Self {
+        Self {
+            max,
+            exempt: Arc::new(exempt.into_iter().collect()),
+            open: Arc::default(),
+        }
+    }
+
+    /// Open a connection from `ip`, or None when it already has `max` open
+    /// The slot is given back when the returned guard is dropped
+    pub fn try_claim(&self, ip: IpAddr) -> Option<ConnectionSlot> {
+        if self.exempt.contains(&ip) {
+            return Some(ConnectionSlot(None));
+        }
+        let mut open = self.open.lock().unwrap_or_else(|e| e.into_inner());
+        let count = open.entry(ip).or_insert(0);
+        if *count >= self.max {
+            return None;
+        }
+        *count += 1;
+        Some(ConnectionSlot(Some((self.clone(), ip))))
+    }
+
+    /// Connections currently open from `ip` (exempt addresses aren't counted)
+    #[cfg(test)]
+    fn open_from(&self, ip: IpAddr) -> usize {
+        let open = self.open.lock().unwrap_or_else(|e| e.into_inner());
+        open.get(&ip).copied().unwrap_or(0)
+    }
+
+    fn release(&self, ip: IpAddr) {
+        let mut open = self.open.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(count) = open.get_mut(&ip) {
+            *count -= 1;
+            // Forget idle clients, so the map only holds addresses with open connections
+            if *count == 0 {
+                open.remove(&ip);
+            }
+        }
+    }
+}
+
+/// A claimed connection slot, released on drop; empty for an exempt address
+#[derive(Debug)]
+pub struct ConnectionSlot(Option<(PerIpLimit, IpAddr)>);
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        if let Some((limit, ip)) = self.0.take() {
+            limit.release(ip);
+        }
+    }
+}
+
+/// TCP vote connections open across every listener, as reported on `/debug/stats`
+#[derive(Debug, Clone, Default)]
+pub struct OpenConnections(Arc<AtomicUsize>);
+
+impl OpenConnections {
+    pub fn get(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    fn open(&self) -> OpenConnection {
+        self.0.fetch_add(1, Ordering::Relaxed);
+        OpenConnection(self.clone())
+    }
+}
+
+/// Counts one connection in `OpenConnections` until dropped
+#[derive(Debug)]
+struct OpenConnection(OpenConnections);
+
+impl Drop for OpenConnection {
+    fn drop(&mut self) {
+        (self.0).0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// What the vote listeners do with each accepted TCP connection
+#[derive(Debug, Clone, Default)]
+pub struct AcceptPolicy {
+    pub socket_options: SocketOptions,
+    /// None serves any number of connections per client
+    pub per_ip: Option<PerIpLimit>,
+    pub connections: OpenConnections,
+}
+
+impl AcceptPolicy {
+    pub fn from_config(tvs: &TvsServerConfig) -> Self {
+        // Validation has already rejected trusted proxies that aren't IP addresses
+        let trusted = tvs
+            .trusted_proxies
+            .iter()
+            .filter_map(|proxy| proxy.parse().ok());
+        Self {
+            socket_options: SocketOptions::from_config(tvs),
+            per_ip: tvs
+                .max_connections_per_ip
+                .map(|max| PerIpLimit::new(max, trusted)),
+            connections: OpenConnections::default(),
+        }
+    }
+
+    /// Admit `stream` from `peer`, setting its socket options, or None to close it because
+    /// the client is over its connection limit
+    fn admit(&self, stream: TcpStream, peer: SocketAddr) -> Option<LimitedStream> {
+        let slot = match &self.per_ip {
+            Some(limit) => match limit.try_claim(peer.ip()) {
+                Some(slot) => Some(slot),
+                None => {
+                    tracing::debug!(client_ip = %peer.ip(), "Closing vote connection over tvs.max_connections_per_ip");
+                    return None;
+                }
+            },
+            None => None,
+        };
+        self.socket_options.apply_or_warn(&stream);
+        Some(LimitedStream {
+            inner: stream,
+            _slot: slot,
+            _open: self.connections.open(),
+        })
+    }
+}
+
+/// An accepted connection holding its client's slot, and its place in the open count,
+/// until it closes
+#[derive(Debug)]
+pub struct LimitedStream {
+    inner: TcpStream,
+    _slot: Option<ConnectionSlot>,
+    _open: OpenConnection,
+}
+
+impl AsyncRead for LimitedStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for LimitedStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+}
+
+/// The plain HTTP vote listener: `TcpListener` with the accept policy applied
+pub struct LimitedListener {
+    inner: TcpListener,
+    policy: AcceptPolicy,
+}
+
+impl LimitedListener {
+    pub fn new(inner: TcpListener, policy: AcceptPolicy) -> Self {
+        Self { inner, policy }
+    }
+}
+
+impl axum::serve::Listener for LimitedListener {
+    type Io = LimitedStream;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        loop {
+            // Accept errors are logged and retried by the inner listener
+            let (stream, peer) = axum::serve::Listener::accept(&mut self.inner).await;
+            if let Some(stream) = self.policy.admit(stream, peer) {
+                return (stream, peer);
+            }
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<Self::Addr> {
+        self.inner.local_addr()
+    }
+}
+
+/// The same policy on the TLS listener, ahead of the handshake; a refused connection is
+/// closed by axum-server
+impl<S> axum_server::accept::Accept<TcpStream, S> for AcceptPolicy {
+    type Stream = LimitedStream;
+    type Service = S;
+    type Future = Ready<io::Result<(LimitedStream, S)>>;
+
+    fn accept(&self, stream: TcpStream, service: S) -> Self::Future {
+        let admitted = stream
+            .peer_addr()
+            .and_then(|peer| {
+                self.admit(stream, peer).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::ConnectionRefused,
+                        "over tvs.max_connections_per_ip",
+                    )
+                })
+            })
+            .map(|stream| (stream, service));
+        std::future::ready(admitted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+    use tokio::io::AsyncReadExt;
+
+    #[test]
+    fn test_per_ip_limit_counts_each_client() {
+        let limit = PerIpLimit::new(2, []);
+        let client = IpAddr::from(Ipv4Addr::new(203, 0, 113, 7));
+        let other = IpAddr::from(Ipv4Addr::new(203, 0, 113, 8));
+
+        let first = limit.try_claim(client).unwrap();
+        let _second = limit.try_claim(client).unwrap();
+        assert!(limit.try_claim(client).is_none());
+        // Another client has slots of its own
+        assert!(limit.try_claim(other).is_some());
+
+        // A closed connection gives its slot back
+        drop(first);
+        assert_eq!(limit.open_from(client), 1);
+        assert!(limit.try_claim(client).is_some());
+    }
+
+    #[test]
+    fn test_trusted_proxy_is_exempt() {
+        let proxy = IpAddr::from(Ipv4Addr::new(10, 0, 0, 2));
+        let limit = PerIpLimit::new(1, [proxy]);
+        let slots: Vec<_> = (0..5).map(|_| limit.try_claim(proxy).unwrap()).collect();
+        assert_eq!(slots.len(), 5);
+        assert_eq!(limit.open_from(proxy), 0);
+    }
+
+    #[tokio::test]
+    async fn test_listener_closes_connections_over_the_limit() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let policy = AcceptPolicy {
+            per_ip: Some(PerIpLimit::new(1, [])),
+            ..AcceptPolicy::default()
+        };
+        let connections = policy.connections.clone();
+        let mut listener = LimitedListener::new(listener, policy);
+
+        let _first = TcpStream::connect(addr).await.unwrap();
+        let (held, _) = axum::serve::Listener::accept(&mut listener).await;
+        assert_eq!(connections.get(), 1);
+
+        // The second connection from the same address is accepted by the OS, then closed
+        let mut second = TcpStream::connect(addr).await.unwrap();
+        let accepting = tokio::spawn(async move {
+            let accepted = axum::serve::Listener::accept(&mut listener).await;
+            (listener, accepted)
+        });
+        let mut buf = [0u8; 1];
+        let read = tokio::time::timeout(std::time::Duration::from_secs(5), second.read(&mut buf))
+            .await
+            .unwrap();
+        assert!(
+            matches!(read, Ok(0) | Err(_)),
+            "second connection still open: {:?}",
+            read
+        );
+
+        // Once the first one closes, the client can connect again
+        drop(held);
+        let _third = TcpStream::connect(addr).await.unwrap();
+        let (_listener, (_stream, peer)) = accepting.await.unwrap();
+        assert_eq!(peer.ip(), IpAddr::from(Ipv4Addr::LOCALHOST));
+        // The refused connection was never counted as open
+        assert_eq!(connections.get(), 1);
+    }
+}
@@ -0,0 +1,246 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    Router,
+};
+
+/// Methods a preflight may ask for; covers every vote route
+const ALLOWED_METHODS: &str = "GET, POST, PUT, DELETE, OPTIONS";
+
+/// How long browsers may cache a preflight answer, in seconds
+const PREFLIGHT_MAX_AGE: &str = "600";
+
+/// Origins allowed to call the vote server from a browser
+#[derive(Debug, Clone)]
+pub struct CorsPolicy {
+    allowed_origins: Arc<Vec<String>>,
+}
+
+impl CorsPolicy {
+    pub fn new(allowed_origins: Vec<String>) -> Self {
+        Self {
+            allowed_origins: Arc::new(allowed_origins),
+        }
+    }
+
+    /// `Access-Control-Allow-Origin` value for a request from `origin`, or `None` if that
+    /// origin isn't allowed. `*` in the list allows any origin; otherwise the origin must
+    /// match an entry exactly (ignoring ASCII case, as scheme and host are case-insensitive)
+    pub fn allow_origin(&self, origin: &str) -> Option<String> {
+        if self.allowed_origins.iter().any(|allowed| allowed == "*") {
+            return Some("*".to_string());
+        }
+        self.allowed_origins
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(origin))
+            .then(|| origin.to_string())
+    }
+}
+
+/// Answer CORS preflights and add CORS headers to responses for the allowed origins
+/// Returns `router` unchanged when no origins are configured
+pub fn apply(router: Router, allowed_origins: Vec<String>) -> Router {
+    if allowed_origins.is_empty() {
+        return router;
+    }
+    router.layer(middleware::from_fn_with_state(
+        CorsPolicy::new(allowed_origins),
+        handle,
+    ))
+}
+
+async fn handle(State(policy): State<CorsPolicy>, request: Request, next: Next) -> Response {
+    let allowed = request
+        .headers()
+        .get(header::ORIGIN)
+        .and_then(|origin| origin.to_str().ok())
+        .and_then(|origin| policy.allow_origin(origin));
+    let Some(allowed) = allowed.and_then(|origin| HeaderValue::from_str(&origin).ok()) else {
+        // Not a cross-origin request, or one from an origin we don't serve: no CORS headers,
+        // so the browser blocks it as before
+        return next.run(request).await;
+    };
+
+    let preflight = request.method() == Method::OPTIONS
+        && request
+            .headers()
+            .contains_key(header::ACCESS_CONTROL_REQUEST_METHOD);
+    let mut response = if preflight {
+        let mut response = StatusCode::NO_CONTENT.into_response();
+        let headers = response.headers_mut();
+        headers.insert(
+            header::ACCESS_CONTROL_ALLOW_METHODS,
+            HeaderValue::from_static(ALLOWED_METHODS),
+        );
+        // Echo the requested headers; the vote API has no header the SPA must not send
+        if let Some(requested) = request
+            .headers()
+            .get(header::ACCESS_CONTROL_REQUEST_HEADERS)
+        {
+            headers.insert(header::ACCESS_CONTROL_ALLOW_HEADERS, requested.clone());
+        }
+        headers.insert(
+            header::ACCESS_CONTROL_MAX_AGE,
+            HeaderValue::from_static(PREFLIGHT_MAX_AGE),
+        );
+        response
+    } else {
+        next.run(request).await
+    };
+
+    set_allow_origin(response.headers_mut(), allowed);
+    response
+}
+
+/// Set the allowed origin; a reflected origin also needs `Vary: Origin` so caches don't
+/// serve one origin's response to another
+fn set_allow_origin(headers: &mut HeaderMap, allowed: HeaderValue) {
+    if allowed != "*" {
+        headers.append(header::VARY, HeaderValue::from_static("origin"));
+    }
+    headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, allowed);
+}
+
+/// Check that a configured origin is `*` or a bare `scheme://host[:port]` origin
+/// Anything with a path or trailing slash would never match a browser's `Origin` header
+pub fn check_origin(origin: &str) -> Result<(), String> {
+    if origin == "*" {
+        return Ok(());
+    }
+    let url = url::Url::parse(origin)
+        .map_err(|e| format!("is not a valid origin ({:?}): {}", origin, e))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!(
+            "must be \"*\" or an http or https origin (got scheme {:?})",
+            url.scheme()
+        ));
+    }
+    if url.origin().ascii_serialization() != origin.to_ascii_lowercase() {
+        return Err(format!(
+            "must be a bare origin like https://vote.example.com, without a path or default port (got {:?})",
+            origin
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::get;
+
+    fn policy(origins: &[&str]) -> CorsPolicy {
+        CorsPolicy::new(origins.iter().map(|o| o.to_string()).collect())
+    }
+
+    #[test]
+    fn test_exact_origin_is_reflected() {
+        let policy = policy(&["https://vote.example.com", "http://localhost:3000"]);
+
+        assert_eq!(
+            policy.allow_origin("https://vote.example.com"),
+            Some("https://vote.example.com".to_string())
+        );
+        assert_eq!(
+            policy.allow_origin("http://localhost:3000"),
+            Some("http://localhost:3000".to_string())
+        );
+        assert_eq!(
+            policy.allow_origin("HTTPS://Vote.Example.com"),
+            Some("HTTPS://Vote.Example.com".to_string())
+        );
+    }
+
+    #[test]
+    fn test_other_origins_are_rejected() {
+        let policy = policy(&["https://vote.example.com"]);
+
+        assert_eq!(policy.allow_origin("https://evil.example.com"), None);
+        assert_eq!(policy.allow_origin("http://vote.example.com"), None);
+        assert_eq!(policy.allow_origin("https://vote.example.com:8443"), None);
+        assert_eq!(policy.allow_origin("https://vote.example.com.evil"), None);
+    }
+
+    #[test]
+    fn test_wildcard_allows_any_origin() {
+        let policy = policy(&["https://vote.example.com", "*"]);
+
+        assert_eq!(
+            policy.allow_origin("https://anything.example"),
+            Some("*".to_string())
+        );
+    }
+
+    #[test]
+    fn test_check_origin() {
+        assert_eq!(check_origin("*"), Ok(()));
+        assert_eq!(check_origin("https://vote.example.com"), Ok(()));
+        assert_eq!(check_origin("http://localhost:3000"), Ok(()));
+        assert!(check_origin("https://vote.example.com/").is_err());
+        assert!(check_origin("https://vote.example.com/app").is_err());
+        assert!(check_origin("ftp://vote.example.com").is_err());
+        assert!(check_origin("vote.example.com").is_err());
+    }
+
+    /// Serve a one-route router behind the CORS layer, returning the route's URL
+    async fn serve(allowed_origins: &[&str]) -> (crate::vote_server::VoteServerRunner, String) {
+        let router = apply(
+            Router::new().route("/vote", get(|| async { "ok" })),
+            allowed_origins.iter().map(|o| o.to_string()).collect(),
+        );
+        let runner = crate::vote_server::VoteServerRunner::serve(router, "127.0.0.1", 0)
+            .await
+            .unwrap();
+        let url = format!("http://{}/vote", runner.local_addr());
+        (runner, url)
+    }
+
+    #[tokio::test]
+    async fn test_preflight_is_answered_for_allowed_origin() {
+        let (runner, url) = serve(&["https://vote.example.com"]).await;
+
+        let response = reqwest::Client::new()
+            .request(reqwest::Method::OPTIONS, &url)
+            .header("origin", "https://vote.example.com")
+            .header("access-control-request-method", "POST")
+            .header("access-control-request-headers", "content-type")
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), reqwest::StatusCode::NO_CONTENT);
+        let headers = response.headers();
+        assert_eq!(
+            headers["access-control-allow-origin"],
+            "https://vote.example.com"
+        );
+        assert_eq!(headers["access-control-allow-methods"], ALLOWED_METHODS);
+        assert_eq!(headers["access-control-allow-headers"], "content-type");
+        assert_eq!(headers["vary"], "origin");
+
+        runner.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_disallowed_origin_gets_no_cors_headers() {
+        let (runner, url) = serve(&["https://vote.example.com"]).await;
+
+        let response = reqwest::Client::new()
+            .get(&url)
+            .header("origin", "https://evil.example.com")
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        assert!(!response
+            .headers()
+            .contains_key("access-control-allow-origin"));
+
+        runner.shutdown().await.unwrap();
+    }
+}
@@ -0,0 +1,177 @@
+use crate::{
+    config::{check_root_url, ConfigSource, TvsNodeConfig},
+    vote_server::BindOptions,
+};
+
+/// Outcome of one diagnostic check
+struct Check {
+    name: String,
+    result: Result<(), String>,
+}
+
+impl Check {
+    fn new(name: impl Into<String>, result: Result<(), String>) -> Self {
+        Self {
+            name: name.into(),
+            result,
+        }
+    }
+}
+
+/// Diagnose the environment for `loaded` without starting any servers
+/// Prints one line per check and fails if any check failed
+pub fn run(
+    loaded: Result<(TvsNodeConfig, ConfigSource), Box<dyn std::error::Error>>,
+    strict: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let checks = match loaded {
+        Ok((config, source)) => {
+            let mut checks = vec![Check::new(format!("Config loads from {}", source), Ok(()))];
+            checks.extend(config_checks(&config, strict));
+            checks
+        }
+        // Nothing else can be checked without a config
+        Err(e) => vec![Check::new("Config loads", Err(e.to_string()))],
+    };
+
+    let mut failed = 0;
+    for check in &checks {
+        match &check.result {
+            Ok(()) => println!("✓ {}", check.name),
+            Err(e) => {
+                failed += 1;
+                println!("✗ {}: {}", check.name, e);
+            }
+        }
+    }
+
+    if failed > 0 {
+        return Err(format!("{} of {} check(s) failed", failed, checks.len()).into());
+    }
+    println!("✓ All {} checks passed", checks.len());
+    Ok(())
+}
+
+/// Every check that needs a loaded config
+fn config_checks(config: &TvsNodeConfig, strict: bool) -> Vec<Check> {
+    let mut checks = vec![Check::new(
+        "Config is valid",
+        config
+            .validate_with_strictness(strict)
+            .map_err(|e| e.to_string()),
+    )];
+
+    // The TFS servers listen on every interface, so probe the unspecified address; it
+    // also catches a port already taken on a single interface
+    let server = &config.tfs.server;
+    let any = BindOptions::default();
+    let mut ports = vec![
+        (
+            "server.cluster_message_port",
+            "0.0.0.0".to_string(),
+            server.cluster_message_port,
+            any,
+        ),
+        (
+            "server.app_port",
+            "0.0.0.0".to_string(),
+            server.app_port,
+            any,
+        ),
+    ];
+    if config.admin_frontend_active() {
+        ports.push((
+            "server.admin_port",
+            "0.0.0.0".to_string(),
+            server.admin_port,
+            any,
+        ));
+    }
+    let vote_bind = BindOptions::from_config(&config.vote_server_config());
+    // A Unix socket has no port to probe, and a stale socket file is replaced at startup
+    for listener in config.vote_listeners() {
+        if crate::config::unix_socket_path(&listener.host).is_none() {
+            ports.push(("vote listener", listener.host, listener.port, vote_bind));
+        }
+    }
+    for (name, host, port, bind) in ports {
+        checks.push(Check::new(
+            format!("{} {}:{} is bindable", name, host, port),
+            port_bindable(&host, port, bind),
+        ));
+    }
+
+    #[cfg(feature = "postgres")]
+    {
+        let persistence = config.persistence_config();
+        checks.push(Check::new(
+            format!(
+                "PostgreSQL reachable at {}",
+                crate::persistence::database_host(&persistence)
+            ),
+            crate::persistence::establish_pool(&persistence)
+                .map_err(|e| e.to_string())
+                .and_then(|pool| crate::persistence::ping(&pool).map_err(|e| e.to_string())),
+        ));
+    }
+
+    let root_url = config.vote_root_url();
+    checks.push(Check::new(
+        format!("Vote root URL {} is well-formed", root_url),
+        check_root_url(&root_url),
+    ));
+
+    checks
+}
+
+/// Check that `host:port` can be bound right now, the way the node would bind it; the
+/// listener is released immediately
+fn port_bindable(host: &str, port: u16, bind: BindOptions) -> Result<(), String> {
+    let addr = crate::vote_server::resolve_bind_addr(host, port, bind.ip_version)
+        .map_err(|e| e.to_string())?;
+    crate::socket_options::bind_listener(addr, bind.ipv6_only)
+        .map(drop)
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn test_port_bindable_when_free() {
+        // Reserve an ephemeral port, then release it for the check
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+
+        assert_eq!(
+            port_bindable("127.0.0.1", port, BindOptions::default()),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_port_bindable_when_taken() {
+        let taken = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = taken.local_addr().unwrap().port();
+
+        assert!(port_bindable("127.0.0.1", port, BindOptions::default()).is_err());
+        assert!(port_bindable("0.0.0.0", port, BindOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_port_bindable_rejects_unresolvable_host() {
+        let err = port_bindable("not a host!", 8090, BindOptions::default()).unwrap_err();
+        assert!(err.contains("not a host!"));
+    }
+
+    #[test]
+    fn test_failed_config_fails_doctor() {
+        let err = run(Err("config file missing".into()), false).unwrap_err();
+        assert_eq!(err.to_string(), "1 of 1 check(s) failed");
+    }
+}
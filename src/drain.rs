@@ -0,0 +1,115 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use axum::{
+    extract::{Request, State},
+    http::{header, Method, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    Router,
+};
+
+/// `Retry-After` sent with a refused vote; a maintenance window outlasts any useful backoff,
+/// so this only keeps clients from retrying in a tight loop
+const RETRY_AFTER_SECS: u64 = 30;
+
+/// Vote drain flag shared by the vote handlers, SIGUSR1 and `TvsNodeHandle`
+/// While it is set, requests that change state get 503 and reads are still served
+#[derive(Clone, Default)]
+pub struct DrainSwitch(Arc<AtomicBool>);
+
+impl DrainSwitch {
+    pub fn is_draining(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// Turn draining on or off, returning whether it was on before
+    pub fn set(&self, draining: bool) -> bool {
+        self.0.swap(draining, Ordering::SeqCst)
+    }
+
+    /// Flip draining, returning the new state
+    pub fn toggle(&self) -> bool {
+        !self.0.fetch_xor(true, Ordering::SeqCst)
+    }
+}
+
+/// Refuse everything but reads on `router` with 503 while `switch` is draining
+pub fn apply(router: Router, switch: DrainSwitch) -> Router {
+    router.layer(middleware::from_fn_with_state(switch, refuse_writes))
+}
+
+async fn refuse_writes(
+    State(switch): State<DrainSwitch>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let read = matches!(
+        *request.method(),
+        Method::GET | Method::HEAD | Method::OPTIONS
+    );
+    if read || !switch.is_draining() {
+        return next.run(request).await;
+    }
+
+    (
+        StatusCode::SERVICE_UNAVAILABLE,
+        [(header::RETRY_AFTER, RETRY_AFTER_SECS.to_string())],
+        "node is draining and not accepting new votes",
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::{get, post};
+
+    #[test]
+    fn test_toggle_and_set() {
+        let switch = DrainSwitch::default();
+        assert!(!switch.is_draining());
+        assert!(switch.toggle());
+        assert!(switch.is_draining());
+        assert!(!switch.toggle());
+        assert!(!switch.is_draining());
+        assert!(!switch.set(true));
+        assert!(switch.set(false));
+        assert!(!switch.is_draining());
+    }
+
+    #[tokio::test]
+    async fn test_draining_refuses_votes_but_serves_reads() {
+        let switch = DrainSwitch::default();
+        let router = apply(
+            Router::new()
+                .route("/cast_vote/{uuid}", post(|| async { "recorded" }))
+                .route("/votes/active", get(|| async { "[]" })),
+            switch.clone(),
+        );
+        let runner = crate::vote_server::VoteServerRunner::serve(router, "127.0.0.1", 0)
+            .await
+            .unwrap();
+        let base = format!("http://{}", runner.local_addr());
+        let client = reqwest::Client::new();
+        let cast = || client.post(format!("{}/cast_vote/1", base)).send();
+
+        assert_eq!(cast().await.unwrap().status(), reqwest::StatusCode::OK);
+
+        switch.set(true);
+        let refused = cast().await.unwrap();
+        assert_eq!(refused.status(), reqwest::StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(refused.headers()["retry-after"], "30");
+        let read = reqwest::get(format!("{}/votes/active", base))
+            .await
+            .unwrap();
+        assert_eq!(read.status(), reqwest::StatusCode::OK);
+
+        switch.set(false);
+        assert_eq!(cast().await.unwrap().status(), reqwest::StatusCode::OK);
+
+        runner.shutdown().await.unwrap();
+    }
+}
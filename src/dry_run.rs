@@ -0,0 +1,317 @@
+use tfs::tfs::node_id::NodeId;
+
+use crate::config::TvsNodeConfig;
+
+/// How one dry-run stage ended
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Passed,
+    Failed(String),
+    /// Not run, because an earlier stage failed
+    Skipped,
+}
+
+/// Body of a stage
+type StageFn<'a, S> = Box<dyn FnOnce(&mut S) -> Result<(), String> + 'a>;
+
+/// One step of the dry run, working on state shared with the later steps
+struct Stage<'a, S> {
+    name: String,
+    run: StageFn<'a, S>,
+}
+
+impl<'a, S> Stage<'a, S> {
+    fn new(name: impl Into<String>, run: impl FnOnce(&mut S) -> Result<(), String> + 'a) -> Self {
+        Self {
+            name: name.into(),
+            run: Box::new(run),
+        }
+    }
+}
+
+/// Run `stages` in order against `state`; each builds on the last, so everything after
+/// the first failure is skipped
+fn run_stages<S>(state: &mut S, stages: Vec<Stage<'_, S>>) -> Vec<(String, Outcome)> {
+    let mut failed = false;
+    stages
+        .into_iter()
+        .map(|stage| {
+            let outcome = if failed {
+                Outcome::Skipped
+            } else {
+                match (stage.run)(state) {
+                    Ok(()) => Outcome::Passed,
+                    Err(e) => {
+                        failed = true;
+                        Outcome::Failed(e)
+                    }
+                }
+            };
+            (stage.name, outcome)
+        })
+        .collect()
+}
+
+/// What the stages set up for the ones after them
+#[derive(Default)]
+struct Wiring {
+    #[cfg(feature = "postgres")]
+    pool: Option<tfs_postgres::DbPool>,
+    #[cfg(feature = "postgres")]
+    session: Option<tfs_postgres::DbSession>,
+}
+
+/// Go as far through startup as possible without binding a port: validate the config,
+/// pick the persistence backend and configure the vote services. Under postgres this
+/// connects, migrates a disposable schema and wires the services to it, then drops it
+/// Prints one line per stage and fails if any stage failed
+pub fn run(config: &TvsNodeConfig, strict: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let node_id = dry_run_node_id(config);
+    let mut wiring = Wiring::default();
+    let outcomes = run_stages(&mut wiring, stages(config, strict, &node_id));
+
+    for (name, outcome) in &outcomes {
+        match outcome {
+            Outcome::Passed => println!("✓ {}", name),
+            Outcome::Failed(e) => println!("✗ {}: {}", name, e),
+            Outcome::Skipped => println!("- {} (skipped)", name),
+        }
+    }
+
+    // Whatever the migrations got to, the schema was only ever for this run
+    #[cfg(feature = "postgres")]
+    if let Some(pool) = wiring.pool.take() {
+        let schema = crate::persistence::schema_name(&node_id);
+        match crate::persistence::drop_schema(&pool, &schema) {
+            Ok(()) => println!("✓ Dropped disposable schema {}", schema),
+            Err(e) => println!("⚠ Could not drop disposable schema {}: {}", schema, e),
+        }
+    }
+
+    let passed = outcomes
+        .iter()
+        .filter(|(_, outcome)| *outcome == Outcome::Passed)
+        .count();
+    if passed < outcomes.len() {
+        return Err(format!(
+            "dry run failed ({} of {} stage(s) passed)",
+            passed,
+            outcomes.len()
+        )
+        .into());
+    }
+    println!("✓ Dry run passed: the node would start with this config");
+    Ok(())
+}
+
+/// A throwaway node id, so the dry run never touches the real node's schema or services
+fn dry_run_node_id(config: &TvsNodeConfig) -> NodeId {
+    let name = config.tfs.node_name.as_deref().unwrap_or("tvs_node");
+    NodeId::new(format!("{}_dry_run", name), uuid::Uuid::new_v4())
+}
+
+/// The stages for this build's persistence backend
+// A build without a backend only gets the generic stages
+#[cfg_attr(
+    not(any(feature = "ephemeral", feature = "postgres")),
+    allow(unused_mut, unused_variables)
+)]
+fn stages<'a>(
+    config: &'a TvsNodeConfig,
+    strict: bool,
+    node_id: &'a NodeId,
+) -> Vec<Stage<'a, Wiring>> {
+    let mut stages = vec![
+        Stage::new("Config is valid", move |_: &mut Wiring| {
+            config
+                .validate_with_strictness(strict)
+                .map_err(|e| e.to_string())
+        }),
+        Stage::new("Persistence backend is compiled in", |_: &mut Wiring| {
+            crate::server_builder::select_backend(
+                cfg!(feature = "postgres"),
+                cfg!(feature = "ephemeral"),
+            )
+            .map(drop)
+            .map_err(|e| e.to_string())
+        }),
+    ];
+
+    #[cfg(feature = "postgres")]
+    {
+        let persistence = config.persistence_config();
+        stages.push(Stage::new(
+            format!(
+                "PostgreSQL connects at {}",
+                crate::persistence::database_host(&persistence)
+            ),
+            move |wiring: &mut Wiring| {
+                let pool = crate::persistence::establish_pool(&persistence).map_err(describe)?;
+                crate::persistence::ping(&pool).map_err(|e| e.to_string())?;
+                wiring.pool = Some(pool);
+                Ok(())
+            },
+        ));
+        stages.push(Stage::new(
+            format!(
+                "Migrations run against disposable schema {}",
+                crate::persistence::schema_name(node_id)
+            ),
+            move |wiring: &mut Wiring| {
+                let pool = wiring.pool.as_ref().ok_or("no connection pool")?;
+                let session =
+                    crate::persistence::run_migrations(pool, node_id, false).map_err(describe)?;
+                wiring.session = Some(session);
+                Ok(())
+            },
+        ));
+        stages.push(Stage::new(
+            "PostgreSQL vote services configure",
+            move |wiring: &mut Wiring| {
+                let session = wiring.session.take().ok_or("no database session")?;
+                tvs::services::vote_service::configure_vote_service(
+                    node_id,
+                    Box::new(tvs_postgres::PostgresVoteService::new(session.clone())),
+                )
+                .map_err(describe)?;
+                tvs::services::vote_url_service::configure_vote_url_service(
+                    node_id,
+                    Box::new(tvs_postgres::PostgresVoteUrlService::with_root_url(
+                        session,
+                        config.vote_root_url(),
+                    )),
+                )
+                .map_err(describe)
+            },
+        ));
+    }
+
+    // The in-memory vote service is built on a running TFS node, so only the URL service
+    // can be wired up here
+    #[cfg(all(feature = "ephemeral", not(feature = "postgres")))]
+    stages.push(Stage::new(
+        "Ephemeral vote URL service configures",
+        move |_: &mut Wiring| {
+            tvs::services::vote_url_service::configure_ephemeral_vote_url_service(
+                node_id,
+                config.vote_root_url(),
+            )
+            .map_err(describe)
+        },
+    ));
+
+    stages
+}
+
+#[cfg(any(feature = "ephemeral", feature = "postgres"))]
+fn describe(e: impl Into<Box<dyn std::error::Error>>) -> String {
+    e.into().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stages_run_in_order_on_shared_state() {
+        let mut seen = Vec::new();
+        let outcomes = run_stages(
+            &mut seen,
+            vec![
+                Stage::new("first", |seen: &mut Vec<&str>| {
+                    seen.push("first");
+                    Ok(())
+                }),
+                Stage::new("second", |seen: &mut Vec<&str>| {
+                    assert_eq!(seen, &["first"]);
+                    seen.push("second");
+                    Ok(())
+                }),
+            ],
+        );
+
+        assert_eq!(seen, vec!["first", "second"]);
+        assert_eq!(
+            outcomes,
+            vec![
+                ("first".to_string(), Outcome::Passed),
+                ("second".to_string(), Outcome::Passed)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stages_after_a_failure_are_skipped() {
+        let mut ran = 0;
+        let outcomes = run_stages(
+            &mut ran,
+            vec![
+                Stage::new("connect", |_: &mut i32| Err("refused".to_string())),
+                Stage::new("migrate", |ran: &mut i32| {
+                    *ran += 1;
+                    Ok(())
+                }),
+            ],
+        );
+
+        assert_eq!(ran, 0);
+        assert_eq!(
+            outcomes,
+            vec![
+                (
+                    "connect".to_string(),
+                    Outcome::Failed("refused".to_string())
+                ),
+                ("migrate".to_string(), Outcome::Skipped)
+            ]
+        );
+    }
+
+    fn config(json: serde_json::Value) -> TvsNodeConfig {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[cfg(all(feature = "ephemeral", not(feature = "postgres")))]
+    #[test]
+    fn test_ephemeral_dry_run_passes() {
+        let config = config(serde_json::json!({
+            "server": {"cluster_message_port": 8080, "app_port": 8081, "admin_port": 8082},
+            "node_name": "tvs_node_1",
+            "tvs": {}
+        }));
+
+        let node_id = dry_run_node_id(&config);
+        let mut wiring = Wiring::default();
+        let outcomes = run_stages(&mut wiring, stages(&config, false, &node_id));
+        let names: Vec<&str> = outcomes.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "Config is valid",
+                "Persistence backend is compiled in",
+                "Ephemeral vote URL service configures"
+            ]
+        );
+        assert!(outcomes
+            .iter()
+            .all(|(_, outcome)| *outcome == Outcome::Passed));
+        assert!(run(&config, false).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_config_stops_the_dry_run() {
+        let config = config(serde_json::json!({
+            "server": {"cluster_message_port": 0, "app_port": 8081, "admin_port": 8082}
+        }));
+
+        let node_id = dry_run_node_id(&config);
+        let outcomes = run_stages(&mut Wiring::default(), stages(&config, false, &node_id));
+        assert!(
+            matches!(outcomes[0].1, Outcome::Failed(ref e) if e.contains("cluster_message_port"))
+        );
+        assert!(outcomes[1..]
+            .iter()
+            .all(|(_, outcome)| *outcome == Outcome::Skipped));
+        assert!(run(&config, false).is_err());
+    }
+}
@@ -0,0 +1,113 @@
+use std::fmt;
+
+use crate::config::ConfigError;
+
+/// Errors from building or running a node, grouped by the stage that failed
+#[derive(Debug)]
+pub enum NodeError {
+    /// The configuration is invalid
+    Config(ConfigError),
+    /// The TFS web server couldn't be started
+    TfsStartup(Box<dyn std::error::Error>),
+    /// The vote backend (database connection, migrations, service registration) couldn't be set up
+    Persistence(Box<dyn std::error::Error>),
+    /// The vote server couldn't be started
+    VoteServer(Box<dyn std::error::Error>),
+    /// A vote listener's port is already taken by another process
+    PortInUse { host: String, port: u16 },
+    /// A server failed after startup
+    Runtime(Box<dyn std::error::Error>),
+}
+
+impl NodeError {
+    /// Process exit code for this failure, distinct per category
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            NodeError::Runtime(_) => 1,
+            NodeError::Config(_) => 2,
+            NodeError::TfsStartup(_) => 3,
+            NodeError::Persistence(_) => 4,
+            NodeError::VoteServer(_) | NodeError::PortInUse { .. } => 5,
+        }
+    }
+}
+
+impl fmt::Display for NodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NodeError::Config(e) => write!(f, "{}", e),
+            NodeError::TfsStartup(e) => write!(f, "Failed to start TFS server: {}", e),
+            NodeError::Persistence(e) => write!(f, "Failed to set up persistence: {}", e),
+            NodeError::VoteServer(e) => write!(f, "Failed to start TVS vote server: {}", e),
+            NodeError::PortInUse { host, port } => write!(
+                f,
+                "Failed to start TVS vote server: {}:{} is already in use; stop whatever holds it, pick another tvs.vote_port, or start with --auto-port",
+                host, port
+            ),
+            NodeError::Runtime(e) => write!(f, "Server error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for NodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            NodeError::Config(e) => Some(e),
+            NodeError::PortInUse { .. } => None,
+            NodeError::TfsStartup(e)
+            | NodeError::Persistence(e)
+            | NodeError::VoteServer(e)
+            | NodeError::Runtime(e) => Some(e.as_ref()),
+        }
+    }
+}
+
+impl From<ConfigError> for NodeError {
+    fn from(e: ConfigError) -> Self {
+        NodeError::Config(e)
+    }
+}
+
+/// Exit code for any error reaching `main`: the `NodeError` category if there is one,
+/// 2 for other configuration errors, 1 otherwise
+pub fn exit_code(error: &(dyn std::error::Error + 'static)) -> u8 {
+    if let Some(e) = error.downcast_ref::<NodeError>() {
+        e.exit_code()
+    } else if error.is::<ConfigError>() {
+        2
+    } else {
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_codes_are_distinct() {
+        let errors = [
+            NodeError::Config(ConfigError::Validation(vec!["bad".to_string()])),
+            NodeError::TfsStartup("tfs".into()),
+            NodeError::Persistence("db".into()),
+            NodeError::VoteServer("bind".into()),
+            NodeError::Runtime("crash".into()),
+        ];
+        let mut codes: Vec<u8> = errors.iter().map(NodeError::exit_code).collect();
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes.len(), errors.len());
+    }
+
+    #[test]
+    fn test_exit_code_from_boxed_error() {
+        let node: Box<dyn std::error::Error> = Box::new(NodeError::Persistence("db".into()));
+        let config: Box<dyn std::error::Error> =
+            Box::new(ConfigError::UnknownFormat("ini".to_string()));
+        let other: Box<dyn std::error::Error> = "other".into();
+
+        assert_eq!(exit_code(node.as_ref()), 4);
+        assert_eq!(exit_code(config.as_ref()), 2);
+        assert_eq!(exit_code(other.as_ref()), 1);
+    }
+}
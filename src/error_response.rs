@@ -0,0 +1,206 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::Request,
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    Json, Router,
+};
+use serde_json::{json, Value};
+
+/// Largest error body read back for its message; longer ones fall back to the reason phrase
+const MAX_ERROR_BODY_BYTES: usize = 64 * 1024;
+
+/// Stable machine-readable code for an error status, as sent in `error.code`
+pub fn code_for(status: StatusCode) -> &'static str {
+    match status {
+        StatusCode::BAD_REQUEST
+        | StatusCode::UNPROCESSABLE_ENTITY
+        | StatusCode::UNSUPPORTED_MEDIA_TYPE => "invalid_vote",
+        StatusCode::NOT_FOUND => "not_found",
+        StatusCode::METHOD_NOT_ALLOWED => "method_not_allowed",
+        StatusCode::CONFLICT => "conflict",
+        StatusCode::PAYLOAD_TOO_LARGE => "payload_too_large",
+        StatusCode::TOO_MANY_REQUESTS => "rate_limited",
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT => {
+            "backend_unavailable"
+        }
+        status if status.is_client_error() => "bad_request",
+        _ => "internal_error",
+    }
+}
+
+/// Rewrite every error response from `router` into `{"error":{"code":"...","message":"..."}}`
+/// The status and other headers (e.g. `Retry-After`) are kept; the original body becomes the message
+pub fn apply(router: Router) -> Router {
+    router.layer(middleware::from_fn(envelope))
+}
+
+async fn envelope(request: Request, next: Next) -> Response {
+    let response = next.run(request).await;
+    let status = response.status();
+    if !(status.is_client_error() || status.is_server_error()) {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let body = to_bytes(body, MAX_ERROR_BODY_BYTES)
+        .await
+        .unwrap_or_default();
+    let parsed = serde_json::from_slice::<Value>(&body).ok();
+    if parsed.as_ref().is_some_and(is_envelope) {
+        return Response::from_parts(parts, Body::from(body));
+    }
+
+    let message = parsed
+        .as_ref()
+        .and_then(json_message)
+        .map(str::to_string)
+        .or_else(|| text_message(&body))
+        .unwrap_or_else(|| status.canonical_reason().unwrap_or("error").to_string());
+
+    parts.headers.remove(header::CONTENT_TYPE);
+    parts.headers.remove(header::CONTENT_LENGTH);
+    let enveloped = Json(json!({ "error": { "code": code_for(status), "message": message } }));
+    (parts, enveloped).into_response()
+}
+
+/// Already in the envelope shape, e.g. from a handler that builds its own
+fn is_envelope(body: &Value) -> bool {
+    body["error"]["code"].is_string()
+}
+
+/// Message from a JSON error body using a common `message` or `error` string field
+fn json_message(body: &Value) -> Option<&str> {
+    body["message"].as_str().or_else(|| body["error"].as_str())
+}
+
+/// Body text as the message, unless it's empty or not text
+fn text_message(body: &[u8]) -> Option<String> {
+    let text = std::str::from_utf8(body).ok()?.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::{get, post};
+
+    #[test]
+    fn test_code_for_statuses() {
+        let cases = [
+            (StatusCode::BAD_REQUEST, "invalid_vote"),
+            (StatusCode::UNPROCESSABLE_ENTITY, "invalid_vote"),
+            (StatusCode::UNSUPPORTED_MEDIA_TYPE, "invalid_vote"),
+            (StatusCode::NOT_FOUND, "not_found"),
+            (StatusCode::PAYLOAD_TOO_LARGE, "payload_too_large"),
+            (StatusCode::TOO_MANY_REQUESTS, "rate_limited"),
+            (StatusCode::SERVICE_UNAVAILABLE, "backend_unavailable"),
+            (StatusCode::GATEWAY_TIMEOUT, "backend_unavailable"),
+            (StatusCode::FORBIDDEN, "bad_request"),
+            (StatusCode::INTERNAL_SERVER_ERROR, "internal_error"),
+        ];
+        for (status, code) in cases {
+            assert_eq!(code_for(status), code, "{}", status);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_errors_are_enveloped() {
+        let router = apply(
+            Router::new()
+                .route(
+                    "/cast_vote/{uuid}",
+                    post(|Json(vote): Json<Value>| async move { Json(vote) }),
+                )
+                .route(
+                    "/backend",
+                    get(|| async {
+                        (
+                            StatusCode::INTERNAL_SERVER_ERROR,
+                            Json(json!({ "error": "vote store unreachable" })),
+                        )
+                    }),
+                )
+                .route(
+                    "/busy",
+                    get(|| async {
+                        (
+                            StatusCode::SERVICE_UNAVAILABLE,
+                            [(header::RETRY_AFTER, "30")],
+                            "node is draining",
+                        )
+                    }),
+                )
+                .route(
+                    "/own",
+                    get(|| async {
+                        (
+                            StatusCode::CONFLICT,
+                            Json(
+                                json!({ "error": { "code": "already_voted", "message": "once" } }),
+                            ),
+                        )
+                    }),
+                ),
+        );
+        let runner = crate::vote_server::VoteServerRunner::serve(router, "127.0.0.1", 0)
+            .await
+            .unwrap();
+        let base = format!("http://{}", runner.local_addr());
+        let client = reqwest::Client::new();
+
+        let ok = client
+            .post(format!("{}/cast_vote/1", base))
+            .json(&json!({ "choice": "a" }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(ok.status(), reqwest::StatusCode::OK);
+        assert_eq!(ok.json::<Value>().await.unwrap(), json!({ "choice": "a" }));
+
+        // A rejected extractor's plain-text explanation becomes the message
+        let invalid = client
+            .post(format!("{}/cast_vote/1", base))
+            .header("content-type", "application/json")
+            .body("{not json")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(invalid.status(), reqwest::StatusCode::BAD_REQUEST);
+        assert_eq!(invalid.headers()["content-type"], "application/json");
+        let body: Value = invalid.json().await.unwrap();
+        assert_eq!(body["error"]["code"], "invalid_vote");
+        assert!(!body["error"]["message"].as_str().unwrap().is_empty());
+
+        let backend = reqwest::get(format!("{}/backend", base)).await.unwrap();
+        assert_eq!(backend.status(), reqwest::StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(
+            backend.json::<Value>().await.unwrap(),
+            json!({ "error": { "code": "internal_error", "message": "vote store unreachable" } })
+        );
+
+        let busy = reqwest::get(format!("{}/busy", base)).await.unwrap();
+        assert_eq!(busy.status(), reqwest::StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(busy.headers()["retry-after"], "30");
+        assert_eq!(
+            busy.json::<Value>().await.unwrap(),
+            json!({ "error": { "code": "backend_unavailable", "message": "node is draining" } })
+        );
+
+        let missing = reqwest::get(format!("{}/nowhere", base)).await.unwrap();
+        assert_eq!(missing.status(), reqwest::StatusCode::NOT_FOUND);
+        assert_eq!(
+            missing.json::<Value>().await.unwrap(),
+            json!({ "error": { "code": "not_found", "message": "Not Found" } })
+        );
+
+        let own = reqwest::get(format!("{}/own", base)).await.unwrap();
+        assert_eq!(
+            own.json::<Value>().await.unwrap()["error"]["code"],
+            "already_voted"
+        );
+
+        runner.shutdown().await.unwrap();
+    }
+}
@@ -0,0 +1,257 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+use diesel::{
+    sql_types::{Array, Text},
+    Connection, PgConnection, QueryableByName, RunQueryDsl,
+};
+use serde::Serialize;
+
+use crate::{config::TvsNodeConfig, error::NodeError};
+
+/// Rows fetched per round trip; the cursor keeps memory flat however many votes there are
+const BATCH_SIZE: usize = 1000;
+
+/// The tables `tvs_postgres` keeps votes in; the only ones exported or imported, so no other
+/// table in the node's schema can be read or written through them
+/// Listed in dependency order, `votes` first and the tables derived from it after, and
+/// exported in this order, so a restore into an empty schema never inserts a row ahead of
+/// one it could reference
+pub const VOTE_TABLES: [&str; 4] = [
+    "votes",
+    "vote_url_mappings",
+    "vote_count_urls",
+    "vote_results",
+];
+
+/// One exported row: the TVS table it came from and its columns
+#[derive(Debug, Serialize)]
+pub struct VoteRecord<'a> {
+    pub table: &'a str,
+    pub record: serde_json::Value,
+}
+
+/// Write `record` as one line of JSON
+pub fn write_record(out: &mut impl Write, record: &VoteRecord) -> std::io::Result<()> {
+    serde_json::to_writer(&mut *out, record)?;
+    out.write_all(b"\n")
+}
+
+#[derive(QueryableByName)]
+struct TableName {
+    #[diesel(sql_type = Text)]
+    table_name: String,
+}
+
+#[derive(QueryableByName)]
+struct JsonRow {
+    #[diesel(sql_type = Text)]
+    line: String,
+}
+
+/// Dump every row of the node's vote tables to `output` (stdout when None) as JSON lines,
+/// without starting TFS or binding any ports
+/// Status goes to stderr so stdout carries only the export
+pub fn run(config: &TvsNodeConfig, output: Option<&str>) -> Result<(), NodeError> {
+    let node_id = crate::migrate::node_id(config, "export votes")?;
+    let schema = crate::persistence::schema_name(&node_id);
+    let persistence = config.persistence_config();
+    eprintln!(
+        "  Connecting to PostgreSQL at {}",
+        crate::persistence::database_host(&persistence)
+    );
+    // A single attempt; an export can just be rerun
+    let pool = crate::persistence::establish_pool(&persistence).map_err(persistence_error)?;
+    let mut conn = pool.get().map_err(|e| persistence_error(e.into()))?;
+
+    let destination = output.unwrap_or("stdout");
+    let (tables, rows) = match output {
+        Some(path) => write_replacing(path, |out| {
+            conn.transaction(|conn| export(conn, &schema, out))
+        }),
+        None => {
+            let mut out = BufWriter::new(std::io::stdout().lock());
+            conn.transaction(|conn| export(conn, &schema, &mut out))
+                .and_then(|counts| {
+                    out.flush()?;
+                    Ok(counts)
+                })
+        }
+    }
+    .map_err(persistence_error)?;
+
+    eprintln!(
+        "✓ Exported {} vote record(s) from {} table(s) in schema {} to {}",
+        rows, tables, schema, destination
+    );
+    Ok(())
+}
+
+/// Write `path` through `write`, which goes to `<path>.tmp` first; that replaces `path` only
+/// once `write` succeeded and was flushed, so a failed export leaves the previous one intact
+fn write_replacing<T>(
+    path: &str,
+    write: impl FnOnce(&mut BufWriter<File>) -> Result<T, Box<dyn std::error::Error>>,
+) -> Result<T, Box<dyn std::error::Error>> {
+    let temp = format!("{}.tmp", path);
+    let file = File::create(&temp).map_err(|e| format!("could not create {}: {}", temp, e))?;
+    let mut out = BufWriter::new(file);
+    let written = write(&mut out).and_then(|value| {
+        out.flush()?;
+        drop(out);
+        std::fs::rename(&temp, path)
+            .map_err(|e| format!("could not replace {} with {}: {}", path, temp, e))?;
+        Ok(value)
+    });
+    if written.is_err() {
+        // Best effort: whatever is left of the partial export is of no use
+        let _ = std::fs::remove_file(&temp);
+    }
+    written
+}
+
+/// Export each of `VOTE_TABLES` present in `schema`, returning the table and row counts
+/// A cursor needs a transaction, which the caller opens; it also gives one consistent snapshot
+fn export(
+    conn: &mut PgConnection,
+    schema: &str,
+    out: &mut impl Write,
+) -> Result<(usize, usize), Box<dyn std::error::Error>> {
+    let present: Vec<String> = diesel::sql_query(
+        "SELECT table_name::text AS table_name FROM information_schema.tables \
+         WHERE table_schema = $1 AND table_type = 'BASE TABLE' AND table_name = ANY($2)",
+    )
+    .bind::<Text, _>(schema)
+    .bind::<Array<Text>, _>(VOTE_TABLES.to_vec())
+    .load::<TableName>(conn)?
+    .into_iter()
+    .map(|TableName { table_name }| table_name)
+    .collect();
+    let tables: Vec<&str> = VOTE_TABLES
+        .into_iter()
+        .filter(|table| present.iter().any(|name| name == table))
+        .collect();
+
+    let mut rows = 0;
+    for &table_name in &tables {
+        diesel::sql_query(format!(
+            "DECLARE vote_export NO SCROLL CURSOR FOR \
+             SELECT row_to_json(t)::text AS line FROM {}.{} t",
+            quote_ident(schema),
+            quote_ident(table_name)
+        ))
+        .execute(conn)?;
+        loop {
+            let batch = diesel::sql_query(format!("FETCH {} FROM vote_export", BATCH_SIZE))
+                .load::<JsonRow>(conn)?;
+            for JsonRow { line } in &batch {
+                let record = VoteRecord {
+                    table: table_name,
+                    record: serde_json::from_str(line)?,
+                };
+                write_record(out, &record)?;
+            }
+            rows += batch.len();
+            if batch.len() < BATCH_SIZE {
+                break;
+            }
+        }
+        diesel::sql_query("CLOSE vote_export").execute(conn)?;
+    }
+    Ok((tables.len(), rows))
+}
+
+/// Quote a schema or table name for interpolation into SQL
+pub fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn persistence_error(e: Box<dyn std::error::Error>) -> NodeError {
+    NodeError::Persistence(e.to_string().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vote_record_is_one_json_line() {
+        let record = VoteRecord {
+            table: "votes",
+            record: serde_json::json!({
+                "vote_id": "7f1c",
+                "choice": "yes\nno",
+                "cast_at": "2026-10-14T09:30:00Z"
+            }),
+        };
+        let mut out = Vec::new();
+        write_record(&mut out, &record).unwrap();
+
+        let line = String::from_utf8(out).unwrap();
+        // A newline inside a value is escaped, so each record stays on its own line
+        assert_eq!(line.matches('\n').count(), 1);
+        assert!(line.ends_with('\n'));
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&line).unwrap(),
+            serde_json::json!({
+                "table": "votes",
+                "record": {
+                    "vote_id": "7f1c",
+                    "choice": "yes\nno",
+                    "cast_at": "2026-10-14T09:30:00Z"
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_vote_tables_start_with_votes() {
+        assert_eq!(VOTE_TABLES[0], "votes");
+    }
+
+    fn export_path(name: &str) -> String {
+        let path = std::env::temp_dir().join(name);
+        let _ = std::fs::remove_file(&path);
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_write_replacing_replaces_on_success() {
+        let path = export_path("tvs_node_export_replaced.jsonl");
+        std::fs::write(&path, "previous export\n").unwrap();
+
+        let rows = write_replacing(&path, |out| {
+            out.write_all(b"new export\n")?;
+            Ok(1)
+        })
+        .unwrap();
+        assert_eq!(rows, 1);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "new export\n");
+        assert!(!std::path::Path::new(&format!("{}.tmp", path)).exists());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_failed_write_keeps_previous_export() {
+        let path = export_path("tvs_node_export_kept.jsonl");
+        std::fs::write(&path, "previous export\n").unwrap();
+
+        let failed = write_replacing(&path, |out| -> Result<(), Box<dyn std::error::Error>> {
+            out.write_all(b"half an export")?;
+            Err("connection lost".into())
+        });
+        assert_eq!(failed.unwrap_err().to_string(), "connection lost");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "previous export\n");
+        // The partial file doesn't linger next to it
+        assert!(!std::path::Path::new(&format!("{}.tmp", path)).exists());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_quote_ident() {
+        assert_eq!(quote_ident("votes"), "\"votes\"");
+        assert_eq!(quote_ident("odd\"name"), "\"odd\"\"name\"");
+    }
+}
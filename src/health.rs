@@ -0,0 +1,151 @@
+use axum::{http::StatusCode, routing::get, Json, Router};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use crate::vote_server::VoteBackend;
+
+/// State of one part of the node, as reported by `/health`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ComponentStatus {
+    Ok,
+    /// Not running by configuration; doesn't make the node unhealthy
+    Disabled,
+    /// Running but failing its probe
+    Degraded,
+}
+
+impl ComponentStatus {
+    /// Rank for picking the worst status; a disabled component counts as healthy
+    fn severity(self) -> u8 {
+        match self {
+            ComponentStatus::Ok | ComponentStatus::Disabled => 0,
+            ComponentStatus::Degraded => 1,
+        }
+    }
+}
+
+/// Overall node status: the worst of the components, `ok` when none is degraded
+pub fn aggregate(components: &[ComponentStatus]) -> ComponentStatus {
+    match components
+        .iter()
+        .copied()
+        .max_by_key(|status| status.severity())
+    {
+        Some(ComponentStatus::Degraded) => ComponentStatus::Degraded,
+        _ => ComponentStatus::Ok,
+    }
+}
+
+/// Combined health of the TFS node and the vote server, served on the vote port
+/// The TFS side is the startup readiness probe; the TVS side is the vote backend's probe.
+/// The vote server serving this is running, so `tvs` is never `disabled` here
+pub fn routes(
+    node: String,
+    app_interface: tfs::tfs_app_interface::TFSAppInterface,
+    app_port: u16,
+    backend: VoteBackend,
+) -> Router {
+    Router::new().route(
+        "/health",
+        get(move || health(node, app_interface, app_port, backend)),
+    )
+}
+
+async fn health(
+    node: String,
+    app_interface: tfs::tfs_app_interface::TFSAppInterface,
+    app_port: u16,
+    backend: VoteBackend,
+) -> (StatusCode, Json<Value>) {
+    let tfs = crate::readiness::probe_tfs(&app_interface, app_port).await;
+    let tvs = backend.check().await;
+    report(&node, tfs, Some(tvs))
+}
+
+/// Status code and body for the probe results; `tvs` is None when the vote server is disabled
+/// Degraded components carry their probe error under `errors`
+fn report(
+    node: &str,
+    tfs: Result<(), String>,
+    tvs: Option<Result<(), String>>,
+) -> (StatusCode, Json<Value>) {
+    let mut errors = Map::new();
+    let mut status_of = |name: &str, probe: Option<Result<(), String>>| match probe {
+        None => ComponentStatus::Disabled,
+        Some(Ok(())) => ComponentStatus::Ok,
+        Some(Err(e)) => {
+            errors.insert(name.to_string(), Value::String(e));
+            ComponentStatus::Degraded
+        }
+    };
+    let tfs = status_of("tfs", Some(tfs));
+    let tvs = status_of("tvs", tvs);
+
+    let status = aggregate(&[tfs, tvs]);
+    let code = match status {
+        ComponentStatus::Degraded => StatusCode::SERVICE_UNAVAILABLE,
+        _ => StatusCode::OK,
+    };
+    let mut body = json!({ "status": status, "node": node, "tfs": tfs, "tvs": tvs });
+    if !errors.is_empty() {
+        body["errors"] = Value::Object(errors);
+    }
+    (code, Json(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ComponentStatus::{Degraded, Disabled, Ok as Up};
+
+    #[test]
+    fn test_aggregate_every_combination() {
+        let cases = [
+            (Up, Up, Up),
+            (Up, Disabled, Up),
+            (Up, Degraded, Degraded),
+            (Degraded, Up, Degraded),
+            (Degraded, Disabled, Degraded),
+            (Degraded, Degraded, Degraded),
+        ];
+        for (tfs, tvs, expected) in cases {
+            assert_eq!(
+                aggregate(&[tfs, tvs]),
+                expected,
+                "tfs {:?}, tvs {:?}",
+                tfs,
+                tvs
+            );
+        }
+    }
+
+    #[test]
+    fn test_report_healthy() {
+        let (code, Json(body)) = report("tvs_node_1", Ok(()), Some(Ok(())));
+        assert_eq!(code, StatusCode::OK);
+        assert_eq!(
+            body,
+            json!({ "status": "ok", "node": "tvs_node_1", "tfs": "ok", "tvs": "ok" })
+        );
+
+        let (code, Json(body)) = report("tvs_node_1", Ok(()), None);
+        assert_eq!(code, StatusCode::OK);
+        assert_eq!(body["tvs"], "disabled");
+        assert_eq!(body["status"], "ok");
+    }
+
+    #[test]
+    fn test_report_degraded_component() {
+        let (code, Json(body)) = report(
+            "tvs_node_1",
+            Ok(()),
+            Some(Err("database unreachable".to_string())),
+        );
+        assert_eq!(code, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["status"], "degraded");
+        assert_eq!(body["tfs"], "ok");
+        assert_eq!(body["tvs"], "degraded");
+        assert_eq!(body["errors"], json!({ "tvs": "database unreachable" }));
+    }
+}
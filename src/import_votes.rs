@@ -0,0 +1,347 @@
+use std::io::BufRead;
+
+use diesel::{
+    connection::{AnsiTransactionManager, TransactionManager},
+    sql_types::Text,
+    PgConnection, RunQueryDsl,
+};
+use serde::Deserialize;
+
+use crate::{config::TvsNodeConfig, error::NodeError};
+
+/// Records inserted per transaction; a failure only loses the batch it happened in
+const BATCH_SIZE: usize = 1000;
+
+type BoxError = Box<dyn std::error::Error>;
+
+/// One line of `export-votes` output: the TVS table a row belongs to and its columns
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct ImportRecord {
+    pub table: String,
+    pub record: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Parse line `line_no` (1-based) of the input; blank lines are skipped
+/// Only the `VOTE_TABLES` that `export-votes` writes are accepted
+pub fn parse_line(line_no: usize, line: &str) -> Result<Option<ImportRecord>, String> {
+    if line.trim().is_empty() {
+        return Ok(None);
+    }
+    let record: ImportRecord =
+        serde_json::from_str(line).map_err(|e| format!("line {}: {}", line_no, e))?;
+    if !crate::export_votes::VOTE_TABLES.contains(&record.table.as_str()) {
+        return Err(format!(
+            "line {}: table {:?} is not a vote table",
+            line_no, record.table
+        ));
+    }
+    Ok(Some(record))
+}
+
+/// How many records were written, and how many were left alone because their id was taken
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ImportCounts {
+    pub inserted: usize,
+    pub skipped: usize,
+}
+
+impl ImportCounts {
+    fn count(&mut self, inserted: bool) {
+        if inserted {
+            self.inserted += 1;
+        } else {
+            self.skipped += 1;
+        }
+    }
+
+    fn add(&mut self, other: ImportCounts) {
+        self.inserted += other.inserted;
+        self.skipped += other.skipped;
+    }
+}
+
+/// Where imported records are written, one transaction per batch
+trait RecordSink {
+    fn begin(&mut self) -> Result<(), BoxError>;
+    fn commit(&mut self) -> Result<(), BoxError>;
+    fn rollback(&mut self) -> Result<(), BoxError>;
+    /// Insert `record`, returning false when a row with the same id is already there
+    fn insert(&mut self, record: &ImportRecord) -> Result<bool, BoxError>;
+}
+
+/// Writes into the node's schema; a duplicate id is skipped by `ON CONFLICT DO NOTHING`
+struct PgSink<'a> {
+    conn: &'a mut PgConnection,
+    schema: String,
+}
+
+impl RecordSink for PgSink<'_> {
+    fn begin(&mut self) -> Result<(), BoxError> {
+        Ok(AnsiTransactionManager::begin_transaction(self.conn)?)
+    }
+
+    fn commit(&mut self) -> Result<(), BoxError> {
+        Ok(AnsiTransactionManager::commit_transaction(self.conn)?)
+    }
+
+    fn rollback(&mut self) -> Result<(), BoxError> {
+        Ok(AnsiTransactionManager::rollback_transaction(self.conn)?)
+    }
+
+    fn insert(&mut self, record: &ImportRecord) -> Result<bool, BoxError> {
+        // json_populate_record maps the exported columns back onto the table's own types
+        let table = format!(
+            "{}.{}",
+            crate::export_votes::quote_ident(&self.schema),
+            crate::export_votes::quote_ident(&record.table)
+        );
+        let rows = diesel::sql_query(format!(
+            "INSERT INTO {table} SELECT * FROM json_populate_record(NULL::{table}, $1::json) \
+             ON CONFLICT DO NOTHING"
+        ))
+        .bind::<Text, _>(serde_json::Value::Object(record.record.clone()).to_string())
+        .execute(self.conn)?;
+        Ok(rows > 0)
+    }
+}
+
+/// Import every line of `input` into `sink`, BATCH_SIZE records per transaction
+/// A line that can't be read or parsed, or a failed insert, rolls back the batch it is in
+/// and stops the import; batches committed before it stay, which can leave the tables
+/// exported ahead of the failing line imported and the rest not. Rerunning the import is
+/// safe: committed rows are skipped by their id, so a rerun of the whole file completes it
+fn import(input: impl BufRead, sink: &mut impl RecordSink) -> Result<ImportCounts, String> {
+    let mut lines = input.lines().enumerate();
+    let mut total = ImportCounts::default();
+    loop {
+        sink.begin().map_err(|e| e.to_string())?;
+        match import_batch(&mut lines, sink) {
+            Ok((batch, more)) => {
+                sink.commit().map_err(|e| e.to_string())?;
+                total.add(batch);
+                if !more {
+                    return Ok(total);
+                }
+            }
+            Err(e) => {
+                let rolled_back = match sink.rollback() {
+                    Ok(()) => "the batch it was in was rolled back".to_string(),
+                    Err(rollback) => format!("rolling the batch back also failed: {}", rollback),
+                };
+                return Err(format!(
+                    "{}; {} ({} record(s) inserted and {} skipped before it are kept)",
+                    e, rolled_back, total.inserted, total.skipped
+                ));
+            }
+        }
+    }
+}
+
+/// Insert up to BATCH_SIZE records, returning their counts and whether input remains
+fn import_batch(
+    lines: &mut impl Iterator<Item = (usize, std::io::Result<String>)>,
+    sink: &mut impl RecordSink,
+) -> Result<(ImportCounts, bool), String> {
+    let mut counts = ImportCounts::default();
+    while counts.inserted + counts.skipped < BATCH_SIZE {
+        let Some((index, line)) = lines.next() else {
+            return Ok((counts, false));
+        };
+        let line_no = index + 1;
+        let line = line.map_err(|e| format!("line {}: {}", line_no, e))?;
+        if let Some(record) = parse_line(line_no, &line)? {
+            let inserted = sink
+                .insert(&record)
+                .map_err(|e| format!("line {}: {}", line_no, e))?;
+            counts.count(inserted);
+        }
+    }
+    Ok((counts, true))
+}
+
+/// Read `export-votes` output from `input` and write it into the node's vote tables,
+/// without starting TFS or binding any ports
+/// Records whose id is already present are skipped, so an interrupted import can be rerun
+pub fn run(config: &TvsNodeConfig, input: &str) -> Result<(), NodeError> {
+    let node_id = crate::migrate::node_id(config, "import votes")?;
+    let schema = crate::persistence::schema_name(&node_id);
+    let file = std::fs::File::open(input)
+        .map_err(|e| NodeError::Persistence(format!("could not open {}: {}", input, e).into()))?;
+
+    let persistence = config.persistence_config();
+    println!(
+        "  Connecting to PostgreSQL at {}",
+        crate::persistence::database_host(&persistence)
+    );
+    let pool = crate::persistence::with_connect_retries(&persistence, || {
+        let pool = crate::persistence::establish_pool(&persistence)?;
+        // Seeding a fresh database needs the tables first
+        crate::persistence::run_migrations(&pool, &node_id, persistence.skip_migrations)?;
+        Ok(pool)
+    })?;
+    let mut conn = pool
+        .get()
+        .map_err(|e| NodeError::Persistence(Box::new(e)))?;
+
+    let mut sink = PgSink {
+        conn: &mut conn,
+        schema: schema.clone(),
+    };
+    let counts = import(std::io::BufReader::new(file), &mut sink)
+        .map_err(|e| NodeError::Persistence(e.into()))?;
+
+    println!(
+        "✓ Imported {} into schema {}: {} inserted, {} skipped (id already present)",
+        input, schema, counts.inserted, counts.skipped
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    /// Keeps vote ids in memory; rows inserted in the open batch only count once committed
+    #[derive(Default)]
+    struct MemorySink {
+        committed: HashSet<String>,
+        pending: HashSet<String>,
+        rollbacks: usize,
+    }
+
+    impl RecordSink for MemorySink {
+        fn begin(&mut self) -> Result<(), BoxError> {
+            self.pending.clear();
+            Ok(())
+        }
+
+        fn commit(&mut self) -> Result<(), BoxError> {
+            self.committed.extend(self.pending.drain());
+            Ok(())
+        }
+
+        fn rollback(&mut self) -> Result<(), BoxError> {
+            self.pending.clear();
+            self.rollbacks += 1;
+            Ok(())
+        }
+
+        fn insert(&mut self, record: &ImportRecord) -> Result<bool, BoxError> {
+            let id = format!("{}/{}", record.table, record.record["id"]);
+            Ok(!self.committed.contains(&id) && self.pending.insert(id))
+        }
+    }
+
+    fn line(id: usize) -> String {
+        format!(
+            r#"{{"table":"votes","record":{{"id":"v{}","choice":"yes"}}}}"#,
+            id
+        )
+    }
+
+    #[test]
+    fn test_parse_line() {
+        assert_eq!(
+            parse_line(
+                1,
+                r#"{"table":"votes","record":{"id":"v1","choice":"yes"}}"#
+            )
+            .unwrap(),
+            Some(ImportRecord {
+                table: "votes".to_string(),
+                record: serde_json::json!({"id": "v1", "choice": "yes"})
+                    .as_object()
+                    .unwrap()
+                    .clone(),
+            })
+        );
+        assert_eq!(parse_line(2, "   ").unwrap(), None);
+
+        let err = parse_line(3, r#"{"table":"votes","record":"#).unwrap_err();
+        assert!(err.starts_with("line 3:"), "{}", err);
+        let err = parse_line(4, r#"{"table":"votes","record":[1]}"#).unwrap_err();
+        assert!(err.starts_with("line 4:"), "{}", err);
+        let err = parse_line(5, r#"{"table":"nodes","record":{"id":"n1"}}"#).unwrap_err();
+        assert_eq!(err, "line 5: table \"nodes\" is not a vote table");
+        // Only the listed tables, not anything that happens to mention votes
+        let err = parse_line(6, r#"{"table":"vote_audit","record":{"id":"a1"}}"#).unwrap_err();
+        assert_eq!(err, "line 6: table \"vote_audit\" is not a vote table");
+    }
+
+    #[test]
+    fn test_duplicate_ids_are_skipped() {
+        let mut sink = MemorySink::default();
+        sink.committed.insert(r#"votes/"v2""#.to_string());
+        let input = [line(1), line(2), String::new(), line(3), line(1)].join("\n");
+
+        let counts = import(input.as_bytes(), &mut sink).unwrap();
+        // v2 was already there, and the second v1 repeats the first
+        assert_eq!(
+            counts,
+            ImportCounts {
+                inserted: 2,
+                skipped: 2
+            }
+        );
+
+        // Running it again inserts nothing
+        let counts = import(input.as_bytes(), &mut sink).unwrap();
+        assert_eq!(
+            counts,
+            ImportCounts {
+                inserted: 0,
+                skipped: 4
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_error_rolls_back_its_batch() {
+        let mut sink = MemorySink::default();
+        let mut lines: Vec<String> = (0..BATCH_SIZE + 2).map(line).collect();
+        lines.push("not json".to_string());
+
+        let err = import(lines.join("\n").as_bytes(), &mut sink).unwrap_err();
+        assert!(
+            err.starts_with(&format!("line {}:", BATCH_SIZE + 3)),
+            "{}",
+            err
+        );
+        assert!(
+            err.contains(&format!("{} record(s) inserted", BATCH_SIZE)),
+            "{}",
+            err
+        );
+        // The first batch was committed; the two records before the bad line were not
+        assert_eq!(sink.rollbacks, 1);
+        assert_eq!(sink.committed.len(), BATCH_SIZE);
+        assert!(!sink
+            .committed
+            .contains(&format!("votes/\"v{}\"", BATCH_SIZE)));
+    }
+
+    #[test]
+    fn test_rerun_after_failure_completes_import() {
+        let mut sink = MemorySink::default();
+        let mut lines: Vec<String> = (0..BATCH_SIZE + 2).map(line).collect();
+        // A later table fails after the first batch of votes was committed
+        lines.push(r#"{"table":"vote_results","record":"#.to_string());
+        import(lines.join("\n").as_bytes(), &mut sink).unwrap_err();
+        assert_eq!(sink.committed.len(), BATCH_SIZE);
+
+        // Rerunning the fixed file skips what was committed and inserts the rest
+        *lines.last_mut().unwrap() =
+            r#"{"table":"vote_results","record":{"id":"r1","count":3}}"#.to_string();
+        let counts = import(lines.join("\n").as_bytes(), &mut sink).unwrap();
+        assert_eq!(
+            counts,
+            ImportCounts {
+                inserted: 3,
+                skipped: BATCH_SIZE
+            }
+        );
+        assert_eq!(sink.committed.len(), BATCH_SIZE + 3);
+        assert!(sink.committed.contains(r#"vote_results/"r1""#));
+    }
+}
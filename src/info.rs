@@ -0,0 +1,139 @@
+use serde_json::{json, Value};
+
+use crate::config::{ConfigSource, TvsNodeConfig};
+
+/// Cargo features this binary was compiled with, in a fixed order
+/// New features belong here too, so startup logs and `info` report them
+pub fn enabled_features() -> Vec<&'static str> {
+    [
+        ("ephemeral", cfg!(feature = "ephemeral")),
+        ("postgres", cfg!(feature = "postgres")),
+        ("admin-frontend", cfg!(feature = "admin-frontend")),
+        ("metrics", cfg!(feature = "metrics")),
+    ]
+    .into_iter()
+    .filter_map(|(feature, enabled)| enabled.then_some(feature))
+    .collect()
+}
+
+/// Build and node metadata for fleet inventory: version, commit, features, node id, ports
+/// and where the config was loaded from
+/// `node_id` is null unless the config pins both `node_name` and `node_uuid`, and
+/// `config_source` is null when the caller doesn't know the source
+pub fn render(config: &TvsNodeConfig, source: Option<&ConfigSource>) -> Value {
+    let server = &config.tfs.server;
+    let vote_listeners: Vec<Value> = config
+        .vote_listeners()
+        .into_iter()
+        .map(|listener| json!({ "host": listener.host, "port": listener.port }))
+        .collect();
+
+    json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "git_commit": env!("TVS_NODE_GIT_COMMIT"),
+        "features": enabled_features(),
+        "node_id": config.configured_node_id().map(|id| id.to_string()),
+        "node_name": config.tfs.node_name,
+        "ports": {
+            "cluster_message": server.cluster_message_port,
+            "app": server.app_port,
+            // Without the admin frontend the admin routes share the cluster port
+            "admin": config.admin_frontend_active().then_some(server.admin_port),
+            "vote": vote_listeners,
+        },
+        "config_source": source.map(ConfigSource::to_json),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enabled_features_match_build() {
+        let features = enabled_features();
+        for (feature, enabled) in [
+            ("ephemeral", cfg!(feature = "ephemeral")),
+            ("postgres", cfg!(feature = "postgres")),
+            ("admin-frontend", cfg!(feature = "admin-frontend")),
+            ("metrics", cfg!(feature = "metrics")),
+        ] {
+            assert_eq!(features.contains(&feature), enabled, "feature {}", feature);
+        }
+    }
+
+    #[test]
+    fn test_info_contains_version_and_features() {
+        let config: TvsNodeConfig = serde_json::from_value(json!({
+            "server": {"cluster_message_port": 8080, "app_port": 8081, "admin_port": 8082},
+            "node_name": "tvs_node_1",
+            "tvs": {"vote_port": 8090}
+        }))
+        .unwrap();
+
+        let info = render(&config, None);
+        assert_eq!(info["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(info["features"], json!(enabled_features()));
+        assert_eq!(
+            info["features"]
+                .as_array()
+                .unwrap()
+                .contains(&json!("postgres")),
+            cfg!(feature = "postgres")
+        );
+        assert_eq!(info["node_id"], Value::Null);
+        assert_eq!(info["ports"]["app"], 8081);
+        assert_eq!(info["ports"]["vote"][0]["port"], 8090);
+        assert_eq!(info["config_source"], Value::Null);
+    }
+
+    #[test]
+    fn test_info_reports_config_source() {
+        let config: TvsNodeConfig = serde_json::from_value(json!({
+            "server": {"cluster_message_port": 8080, "app_port": 8081, "admin_port": 8082}
+        }))
+        .unwrap();
+        let source = ConfigSource::File {
+            path: "/etc/tvs_node/config.json".into(),
+            modified: Some(
+                std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_791_970_200),
+            ),
+        };
+        assert_eq!(
+            render(&config, Some(&source))["config_source"],
+            json!({
+                "kind": "file",
+                "path": "/etc/tvs_node/config.json",
+                "modified": "2026-10-14T09:30:00+00:00"
+            })
+        );
+
+        let source = ConfigSource::File {
+            path: "/etc/tvs_node/config.json".into(),
+            modified: None,
+        };
+        assert_eq!(render(&config, Some(&source))["config_source"]["modified"], Value::Null);
+        assert_eq!(
+            render(&config, Some(&ConfigSource::Environment))["config_source"],
+            json!({ "kind": "environment" })
+        );
+        assert_eq!(
+            render(&config, Some(&ConfigSource::Stdin))["config_source"],
+            json!({ "kind": "stdin" })
+        );
+    }
+
+    #[test]
+    fn test_info_derives_node_id() {
+        let uuid = uuid::Uuid::new_v4();
+        let config: TvsNodeConfig = serde_json::from_value(json!({
+            "server": {"cluster_message_port": 8080, "app_port": 8081, "admin_port": 8082},
+            "node_name": "tvs_node_1",
+            "node_uuid": uuid
+        }))
+        .unwrap();
+
+        let expected = tfs::tfs::node_id::NodeId::new("tvs_node_1".to_string(), uuid);
+        assert_eq!(render(&config, None)["node_id"], expected.to_string());
+    }
+}
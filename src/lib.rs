@@ -0,0 +1,54 @@
+//! The TVS node: a TFS web server with the TVS vote server alongside it, configured from
+//! one file
+//!
+//! The `tvs_node` binary is a thin CLI over this crate. A process that embeds a node instead
+//! builds a `TvsNodeRunner` from a `TvsNodeConfig` and controls it through a `TvsNodeHandle`
+
+pub mod access_log;
+pub mod backend_guard;
+pub mod body_limit;
+pub mod concurrency_limit;
+pub mod config;
+pub mod connection_limit;
+pub mod cors;
+pub mod doctor;
+pub mod drain;
+pub mod dry_run;
+pub mod env_file;
+pub mod error;
+pub mod error_response;
+#[cfg(feature = "postgres")]
+pub mod export_votes;
+pub mod health;
+#[cfg(feature = "postgres")]
+pub mod import_votes;
+pub mod info;
+pub mod logging;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(feature = "metrics")]
+pub mod metrics_checkpoint;
+#[cfg(feature = "postgres")]
+pub mod migrate;
+pub mod persistence;
+#[cfg(feature = "postgres")]
+pub mod pool_stats;
+pub mod rate_limit;
+pub mod readiness;
+pub mod ready_notify;
+pub mod reload;
+pub mod response_headers;
+pub mod runtime;
+pub mod scaffold;
+pub mod schema;
+pub mod server_builder;
+pub mod socket_options;
+pub mod startup_timing;
+pub mod stats;
+pub mod template;
+pub mod tls;
+pub mod vote_server;
+
+pub use config::TvsNodeConfig;
+pub use error::NodeError;
+pub use server_builder::{TvsNodeHandle, TvsNodeRunner};
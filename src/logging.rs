@@ -0,0 +1,200 @@
+use std::{fmt, sync::OnceLock};
+
+use chrono::Utc;
+use serde_json::{json, Map, Value};
+use tracing::{
+    field::{Field, Visit},
+    Event, Subscriber,
+};
+use tracing_subscriber::{
+    fmt::{format::Writer, FmtContext, FormatEvent, FormatFields},
+    registry::LookupSpan,
+    EnvFilter,
+};
+
+/// Swaps the active level filter; only set once the JSON subscriber is installed
+type LevelReloader = Box<dyn Fn(&str) -> Result<(), String> + Send + Sync>;
+
+static LEVEL_RELOADER: OnceLock<LevelReloader> = OnceLock::new();
+
+/// Install a global subscriber writing one JSON object per line to stdout, used instead of
+/// the upstream `init_tracing` when `log_format = "json"`
+/// `RUST_LOG` wins over `log_level`, matching the text logger
+pub fn init_json_tracing(node: &str, log_level: Option<&str>) {
+    let filter = EnvFilter::try_from_default_env()
+        .or_else(|_| EnvFilter::try_new(log_level.unwrap_or("info")))
+        .unwrap_or_else(|_| EnvFilter::new("info"));
+
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .event_format(NodeJsonFormat::new(node))
+        .with_filter_reloading();
+    let handle = builder.reload_handle();
+    if let Err(e) = builder.try_init() {
+        println!("⚠ Could not install JSON logging: {}", e);
+        return;
+    }
+
+    let _ = LEVEL_RELOADER.set(Box::new(move |level| {
+        let filter = EnvFilter::try_new(level).map_err(|e| e.to_string())?;
+        handle.reload(filter).map_err(|e| e.to_string())
+    }));
+}
+
+/// Install a plain subscriber writing to stderr for the subcommands that exit without
+/// starting the node (`migrate`, `export-votes`, ...), so the status lines they share with
+/// startup are shown; stderr keeps them out of an export written to stdout
+/// The messages carry their own ✓/⚠ markers, so level, target and timestamp are left out
+pub fn init_cli_tracing(log_level: Option<&str>) {
+    let filter = EnvFilter::try_from_default_env()
+        .or_else(|_| EnvFilter::try_new(log_level.unwrap_or("info")))
+        .unwrap_or_else(|_| EnvFilter::new("info"));
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .without_time()
+        .with_level(false)
+        .with_target(false)
+        .try_init();
+}
+
+/// Replace the log level filter at runtime (e.g. on SIGHUP)
+/// Only the JSON logger supports this; the text logger is installed upstream without a reload hook
+pub fn set_log_level(level: &str) -> Result<(), String> {
+    match LEVEL_RELOADER.get() {
+        Some(reload) => reload(level),
+        None => Err("changing the level at runtime needs log_format = \"json\"".to_string()),
+    }
+}
+
+/// Formats events as JSON with timestamp, level, target, the node name and the event fields
+pub struct NodeJsonFormat {
+    node: String,
+}
+
+impl NodeJsonFormat {
+    pub fn new(node: &str) -> Self {
+        Self {
+            node: node.to_string(),
+        }
+    }
+}
+
+impl<S, N> FormatEvent<S, N> for NodeJsonFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        _ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let mut fields = Map::new();
+        event.record(&mut JsonVisitor(&mut fields));
+
+        let metadata = event.metadata();
+        let line = json!({
+            "timestamp": Utc::now().to_rfc3339(),
+            "level": metadata.level().as_str(),
+            "target": metadata.target(),
+            "node": self.node,
+            "fields": fields,
+        });
+        writeln!(writer, "{}", line)
+    }
+}
+
+/// Collects event fields into a JSON map, keeping numbers and booleans typed
+struct JsonVisitor<'a>(&'a mut Map<String, Value>);
+
+impl Visit for JsonVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), json!(format!("{:?}", value)));
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// Shared in-memory sink for captured log output
+    #[derive(Clone, Default)]
+    pub(crate) struct Capture(Arc<Mutex<Vec<u8>>>);
+
+    impl Capture {
+        /// Run `f` with a JSON subscriber writing into a fresh capture, returning one
+        /// parsed object per event
+        pub(crate) fn json_events(f: impl FnOnce()) -> Vec<Value> {
+            let capture = Capture::default();
+            let writer = capture.clone();
+            let subscriber = tracing_subscriber::fmt()
+                .event_format(NodeJsonFormat::new("tvs_node_1"))
+                .with_writer(move || writer.clone())
+                .finish();
+            tracing::subscriber::with_default(subscriber, f);
+
+            let output = String::from_utf8(capture.0.lock().unwrap().clone()).unwrap();
+            output
+                .lines()
+                .map(|line| serde_json::from_str(line).unwrap())
+                .collect()
+        }
+    }
+
+    impl std::io::Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_json_format_includes_node_and_fields() {
+        let capture = Capture::default();
+        let writer = capture.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .event_format(NodeJsonFormat::new("tvs_node_1"))
+            .with_writer(move || writer.clone())
+            .finish();
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::warn!(vote_port = 8090u64, "vote server started");
+        });
+
+        let output = String::from_utf8(capture.0.lock().unwrap().clone()).unwrap();
+        let line: Value = serde_json::from_str(output.trim()).unwrap();
+        assert_eq!(line["level"], "WARN");
+        assert_eq!(line["node"], "tvs_node_1");
+        assert_eq!(line["target"], module_path!());
+        assert_eq!(line["fields"]["message"], "vote server started");
+        assert_eq!(line["fields"]["vote_port"], 8090);
+    }
+}
@@ -1,32 +1,409 @@
-use clap::Parser;
+use std::process::ExitCode;
 
-use crate::{config::TvsNodeConfig, server_builder::TvsNodeRunner};
+use clap::{error::ErrorKind, CommandFactory, Parser, Subcommand};
 
-mod config;
-mod server_builder;
+use tvs_node::{
+    config,
+    config::{
+        ConfigError, ConfigFormat, ConfigSource, LogLevel, TvsNodeConfig, CONFIG_PATH_VAR,
+        STDIN_CONFIG_PATH,
+    },
+    doctor, dry_run, env_file, error,
+    error::NodeError,
+    info, logging,
+    reload::ConfigReloader,
+    runtime, scaffold,
+    scaffold::ScaffoldTarget,
+    schema,
+    server_builder::TvsNodeRunner,
+    template,
+};
+#[cfg(feature = "postgres")]
+use tvs_node::{export_votes, import_votes, migrate};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
-    /// Path to the configuration file
-    #[arg(short, long, default_value = "config.json")]
-    config: String,
+    /// Path to the configuration file, or `-` to read it from stdin; repeat to deep-merge
+    /// more files over it in order, later ones winning [default: $TVS_NODE_CONFIG, then config.json]
+    #[arg(short, long, global = true)]
+    config: Vec<String>,
+
+    /// Configuration format (json, toml or yaml); detected from the file extension by default
+    #[arg(short, long, global = true)]
+    format: Option<ConfigFormat>,
+
+    /// Layer `config.<name>.*` from next to the config file over it (e.g. dev, staging, prod)
+    #[arg(long, global = true, value_parser = config::parse_profile)]
+    profile: Option<String>,
+
+    /// Tolerate comments and trailing commas in the configuration file
+    #[arg(long, global = true)]
+    lenient: bool,
+
+    /// Treat configuration warnings as errors
+    #[arg(long, global = true)]
+    strict: bool,
+
+    /// Log level (trace, debug, info, warn or error); overrides `log_level` and `RUST_LOG`
+    #[arg(long)]
+    log_level: Option<LogLevel>,
+
+    /// Use the existing database schema without running migrations (e.g. on a read replica)
+    #[arg(long)]
+    skip_migrations: bool,
+
+    /// If a vote port is taken, serve on the next free port instead of failing
+    #[arg(long)]
+    auto_port: bool,
+
+    /// Add this to every listening port (TFS and vote), to run several nodes from one config
+    #[arg(long)]
+    port_offset: Option<u16>,
+
+    /// Don't print the startup summary once the node is up
+    #[arg(long)]
+    quiet: bool,
+
+    /// Log how long each startup phase took (the total is always logged)
+    #[arg(long)]
+    verbose_startup: bool,
+
+    /// Print the resolved configuration (after env overrides, secrets redacted) and exit
+    #[arg(long)]
+    print_config: bool,
+
+    /// Go through startup short of binding ports (config, persistence, migrations against a
+    /// throwaway schema, service wiring), report each stage and exit
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Load environment overrides from a .env file before applying them
+    #[arg(long, global = true)]
+    env_file: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Print deployment wiring (systemd unit or compose service) for the config, without starting anything
+    Scaffold {
+        /// Deployment target to generate
+        #[arg(long, value_enum)]
+        target: ScaffoldTarget,
+    },
+    /// Write a config file populated with default values, as a starting point for a new node
+    GenerateConfig {
+        /// File to write (default: stdout)
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// Overwrite the output file if it already exists
+        #[arg(long)]
+        force: bool,
+    },
+    /// Print a JSON Schema for the config file, for editor autocomplete and config linting
+    Schema,
+    /// Check the config, ports, database and root URL without starting the servers
+    Doctor,
+    /// Create the node's database schema and TVS tables, then exit without starting the servers
+    #[cfg(feature = "postgres")]
+    Migrate,
+    /// Write every recorded vote as JSON lines, then exit without starting the servers
+    #[cfg(feature = "postgres")]
+    ExportVotes {
+        /// File to write (default: stdout)
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Load votes written by `export-votes`, skipping ids already present, then exit
+    #[cfg(feature = "postgres")]
+    ImportVotes {
+        /// JSON lines file to read
+        #[arg(short, long)]
+        input: String,
+    },
+    /// Print the version, git commit, enabled features, node id and ports as JSON
+    Info,
+}
+
+fn main() -> ExitCode {
+    match try_main() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            // Distinct exit codes let supervisors tell config mistakes from runtime failures
+            eprintln!("Error: {}", e);
+            ExitCode::from(error::exit_code(e.as_ref()))
+        }
+    }
 }
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+fn try_main() -> Result<(), Box<dyn std::error::Error>> {
     // Parse command line arguments
     let args = Args::parse();
+    if let Err(e) = check_dry_run(&args) {
+        e.exit();
+    }
+
+    // Load a .env file first so it can influence config loading as well as overrides
+    if let Some(env_file) = &args.env_file {
+        let applied = env_file::load_env_file(env_file)?;
+        eprintln!(
+            "✓ Loaded {} variable(s) from env file {}",
+            applied, env_file
+        );
+    }
+
+    // Resolved after the env file so it can set TVS_NODE_CONFIG
+    let config_path = config::resolve_config_path(
+        args.config.first().cloned(),
+        std::env::var(CONFIG_PATH_VAR).ok(),
+    );
+
+    // Generating a template needs no existing config
+    if let Some(Command::GenerateConfig { output, force }) = &args.command {
+        return template::generate(output.as_deref(), *force);
+    }
+
+    // Nor does the schema, which describes every config rather than this one
+    if let Some(Command::Schema) = args.command {
+        return Ok(schema::print()?);
+    }
+
+    // The doctor reports a config that fails to load instead of stopping on it
+    if let Some(Command::Doctor) = args.command {
+        return doctor::run(load_config(&args, &config_path), args.strict);
+    }
+
+    let (config, config_source) = load_config(&args, &config_path)?;
 
-    let mut config = TvsNodeConfig::read_config(&args.config)
-        .expect(&format!("Failed to read config {}", &args.config));
+    // Validation is the dry run's first stage, so it reports a bad config like any other failure
+    // The dry run and the database commands below exit before the node installs its logger,
+    // so each installs a plain one for the persistence status lines they share with startup
+    if args.dry_run {
+        println!("✓ Loaded configuration from {}", config_source);
+        logging::init_cli_tracing(config.tfs.log_level.as_deref());
+        return dry_run::run(&config, args.strict);
+    }
+    config.validate_with_strictness(args.strict)?;
+
+    if args.print_config {
+        println!("{}", config.to_redacted_json()?);
+        return Ok(());
+    }
+
+    if let Some(Command::Info) = args.command {
+        let info = info::render(&config, Some(&config_source));
+        println!("{}", serde_json::to_string_pretty(&info)?);
+        return Ok(());
+    }
+
+    if let Some(Command::Scaffold { target }) = args.command {
+        print!("{}", scaffold::render(&config, &config_path, target));
+        return Ok(());
+    }
+
+    #[cfg(feature = "postgres")]
+    if let Some(Command::Migrate) = args.command {
+        println!("✓ Loaded configuration from {}", config_source);
+        logging::init_cli_tracing(config.tfs.log_level.as_deref());
+        migrate::run(&config)?;
+        return Ok(());
+    }
+
+    // Status goes to stderr here, as stdout may be carrying the export
+    #[cfg(feature = "postgres")]
+    if let Some(Command::ExportVotes { output }) = &args.command {
+        eprintln!("✓ Loaded configuration from {}", config_source);
+        logging::init_cli_tracing(config.tfs.log_level.as_deref());
+        export_votes::run(&config, output.as_deref())?;
+        return Ok(());
+    }
+
+    #[cfg(feature = "postgres")]
+    if let Some(Command::ImportVotes { input }) = &args.command {
+        println!("✓ Loaded configuration from {}", config_source);
+        logging::init_cli_tracing(config.tfs.log_level.as_deref());
+        import_votes::run(&config, input)?;
+        return Ok(());
+    }
+
+    println!("✓ Loaded configuration from {}", config_source);
+
+    // The tracing setup consults RUST_LOG before the config's log_level, so the flag has to
+    // replace it too; done here while the process is still single-threaded
+    if let Some(level) = args.log_level {
+        std::env::set_var("RUST_LOG", level.as_str());
+    }
+
+    // Apply process-level tuning (niceness) before any servers start
+    let runtime_config = config.runtime_config();
+    runtime::apply_runtime_config(&runtime_config);
+
+    // SIGHUP re-reads the file the same way; an environment-only or piped config has nothing to re-read
+    let reloader = match config_source {
+        ConfigSource::Environment | ConfigSource::Stdin => None,
+        ConfigSource::File { .. } => Some(ConfigReloader {
+            path: config_path,
+            format: args.format,
+            lenient: args.lenient,
+            profile: args.profile.clone(),
+            overlays: config_overlays(&args).to_vec(),
+            log_level_override: args.log_level,
+            skip_migrations_override: args.skip_migrations,
+            quiet_override: args.quiet,
+            verbose_startup_override: args.verbose_startup,
+            auto_port_override: args.auto_port,
+            port_offset: args.port_offset,
+        }),
+    };
+
+    // Build the Tokio runtime by hand so runtime settings (stack size) can shape it
+    runtime::build_tokio_runtime(&runtime_config)?.block_on(run(
+        config,
+        config_source,
+        reloader,
+    ))?;
+    Ok(())
+}
+
+/// `--dry-run` stands in for starting the node, so a subcommand given with it would either
+/// run instead or be skipped for the dry run; refuse the combination like any other
+/// conflicting flags
+fn check_dry_run(args: &Args) -> Result<(), clap::Error> {
+    match &args.command {
+        Some(_) if args.dry_run => Err(Args::command().error(
+            ErrorKind::ArgumentConflict,
+            "the argument '--dry-run' cannot be used with a subcommand",
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// The `--config` files after the first, layered over it in order
+fn config_overlays(args: &Args) -> &[String] {
+    args.config.get(1..).unwrap_or_default()
+}
+
+/// Load the config from the file (or the environment) and apply the env and flag overrides
+fn load_config(
+    args: &Args,
+    config_path: &str,
+) -> Result<(TvsNodeConfig, ConfigSource), Box<dyn std::error::Error>> {
+    // Fall back to a fileless, environment-only config when explicitly requested
+    let (mut config, config_source) = if config_path == STDIN_CONFIG_PATH {
+        let config = TvsNodeConfig::read_config_from_reader(
+            std::io::stdin().lock(),
+            args.format,
+            args.lenient,
+        )?;
+        (config, ConfigSource::Stdin)
+    } else if !std::path::Path::new(config_path).exists()
+        && TvsNodeConfig::config_from_env_requested()
+    {
+        eprintln!(
+            "⚠ Config file {} not found - building configuration from environment (TVS_CONFIG_FROM_ENV)",
+            config_path
+        );
+        (TvsNodeConfig::from_env()?, ConfigSource::Environment)
+    } else {
+        let config =
+            TvsNodeConfig::read_config_with_format(config_path, args.format, args.lenient)?;
+        (config, ConfigSource::file(config_path))
+    };
+
+    // Profile values win over the base file, and env overrides win over both
+    if let Some(profile) = &args.profile {
+        if !matches!(config_source, ConfigSource::File { .. }) {
+            return Err(ConfigError::ProfileWithoutFile {
+                profile: profile.clone(),
+            }
+            .into());
+        }
+        let path = config::profile_path(config_path, profile);
+        config = config.with_profile(profile, &path, args.format, args.lenient)?;
+    }
+
+    // Later `--config` files win over the first and its profile
+    for overlay in config_overlays(args) {
+        config = config.with_overlay(overlay, args.lenient)?;
+    }
 
     // Apply environment variable overrides (for Docker/containerized deployments)
-    config.apply_env_overrides();
+    config.apply_env_overrides()?;
+    // After the env overrides, so the offset also applies to ports they set
+    if let Some(offset) = args.port_offset {
+        config.apply_port_offset(offset)?;
+    }
+    if let Some(level) = args.log_level {
+        config.tfs.log_level = Some(level.as_str().to_string());
+    }
+    if args.quiet {
+        config.quiet = true;
+    }
+    if args.verbose_startup {
+        config.verbose_startup = true;
+    }
+    // Without a tvs section the vote server runs on the defaults, so the flag has to create
+    // one to reach it
+    if args.auto_port {
+        config.tvs.get_or_insert_with(Default::default).auto_port = true;
+    }
+    if args.skip_migrations {
+        config
+            .persistence
+            .get_or_insert_with(Default::default)
+            .skip_migrations = true;
+    }
+    Ok((config, config_source))
+}
 
+async fn run(
+    config: TvsNodeConfig,
+    source: ConfigSource,
+    reloader: Option<ConfigReloader>,
+) -> Result<(), NodeError> {
     // Build and run the TVS node with feature-based persistence
-    let runner = TvsNodeRunner::build_with_config(config).await?;
+    let mut runner = TvsNodeRunner::build_with_config_source(config, source).await?;
+    if let Some(reloader) = reloader {
+        runner = runner.with_config_reload(reloader);
+    }
 
     // Run until shutdown (consumes runner)
     runner.run_until_shutdown().await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_checks_are_global() {
+        // Both orders work, so `doctor --strict` checks the same way startup would
+        for argv in [
+            ["tvs_node", "doctor", "--strict", "--lenient"],
+            ["tvs_node", "--strict", "--lenient", "doctor"],
+        ] {
+            let args = Args::try_parse_from(argv).unwrap();
+            assert!(args.strict, "{:?}", argv);
+            assert!(args.lenient, "{:?}", argv);
+            assert!(matches!(args.command, Some(Command::Doctor)));
+        }
+    }
+
+    #[test]
+    fn test_dry_run_conflicts_with_subcommands() {
+        let args = Args::try_parse_from(["tvs_node", "--dry-run"]).unwrap();
+        assert!(check_dry_run(&args).is_ok());
+
+        for subcommand in ["doctor", "info", "schema"] {
+            let args = Args::try_parse_from(["tvs_node", "--dry-run", subcommand]).unwrap();
+            let err = check_dry_run(&args).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::ArgumentConflict, "{}", subcommand);
+        }
+        // Not a global flag, so it can't follow the subcommand in the first place
+        assert!(Args::try_parse_from(["tvs_node", "doctor", "--dry-run"]).is_err());
+    }
+}
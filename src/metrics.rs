@@ -0,0 +1,300 @@
+use std::time::Instant;
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::{header, Method},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use prometheus::{Encoder, HistogramOpts, HistogramVec, IntCounter, Opts, Registry, TextEncoder};
+
+use crate::metrics_checkpoint::Checkpoint;
+
+/// Per-node Prometheus metrics for the vote server
+/// Every metric carries a constant `node` label so several nodes can share one scrape target
+#[derive(Clone)]
+pub struct VoteMetrics {
+    registry: Registry,
+    votes_recorded: IntCounter,
+    vote_failures: IntCounter,
+    request_latency: HistogramVec,
+    /// The vote counters across restarts; only kept with a metrics checkpoint
+    cumulative: Option<CumulativeCounters>,
+}
+
+/// `tvs_votes_recorded_total` and `tvs_vote_failures_total` carried on from a checkpoint,
+/// as `*_cumulative_total` so they can't be mistaken for the since-start counters
+#[derive(Clone)]
+struct CumulativeCounters {
+    votes_recorded: IntCounter,
+    vote_failures: IntCounter,
+}
+
+impl VoteMetrics {
+    /// Create a fresh registry for `node` with the vote counters and latency histogram
+    pub fn new(node: &str) -> prometheus::Result<Self> {
+        let registry = Registry::new();
+
+        let votes_recorded = IntCounter::with_opts(
+            Opts::new(
+                "tvs_votes_recorded_total",
+                "Votes cast successfully since the process started",
+            )
+            .const_label("node", node),
+        )?;
+        let vote_failures = IntCounter::with_opts(
+            Opts::new(
+                "tvs_vote_failures_total",
+                "Vote casts that were rejected or failed since the process started",
+            )
+            .const_label("node", node),
+        )?;
+        let request_latency = HistogramVec::new(
+            HistogramOpts::new(
+                "tvs_request_duration_seconds",
+                "Vote server request latency",
+            )
+            .const_label("node", node),
+            &["method", "route", "status"],
+        )?;
+
+        registry.register(Box::new(votes_recorded.clone()))?;
+        registry.register(Box::new(vote_failures.clone()))?;
+        registry.register(Box::new(request_latency.clone()))?;
+
+        Ok(Self {
+            registry,
+            votes_recorded,
+            vote_failures,
+            request_latency,
+            cumulative: None,
+        })
+    }
+
+    /// `new`, plus cumulative vote counters that start from `restored` instead of zero
+    pub fn with_cumulative(node: &str, restored: Checkpoint) -> prometheus::Result<Self> {
+        let mut metrics = Self::new(node)?;
+
+        let votes_recorded = IntCounter::with_opts(
+            Opts::new(
+                "tvs_votes_recorded_cumulative_total",
+                "Votes cast successfully since the metrics checkpoint was first written",
+            )
+            .const_label("node", node),
+        )?;
+        let vote_failures = IntCounter::with_opts(
+            Opts::new(
+                "tvs_vote_failures_cumulative_total",
+                "Vote casts that were rejected or failed since the metrics checkpoint was \
+                 first written",
+            )
+            .const_label("node", node),
+        )?;
+        votes_recorded.inc_by(restored.votes_recorded);
+        vote_failures.inc_by(restored.vote_failures);
+
+        metrics.registry.register(Box::new(votes_recorded.clone()))?;
+        metrics.registry.register(Box::new(vote_failures.clone()))?;
+        metrics.cumulative = Some(CumulativeCounters {
+            votes_recorded,
+            vote_failures,
+        });
+        Ok(metrics)
+    }
+
+    /// Vote casts recorded and failed since the node started, as `(recorded, failed)`
+    pub fn vote_counts(&self) -> (u64, u64) {
+        (self.votes_recorded.get(), self.vote_failures.get())
+    }
+
+    /// The cumulative vote counters as a checkpoint; None without `with_cumulative`
+    pub fn cumulative_counts(&self) -> Option<Checkpoint> {
+        self.cumulative.as_ref().map(|cumulative| Checkpoint {
+            votes_recorded: cumulative.votes_recorded.get(),
+            vote_failures: cumulative.vote_failures.get(),
+        })
+    }
+
+    /// Count one vote cast in the since-start counters, and in the cumulative ones if kept
+    pub(crate) fn count_vote(&self, recorded: bool) {
+        let counters = std::iter::once((&self.votes_recorded, &self.vote_failures)).chain(
+            self.cumulative
+                .iter()
+                .map(|cumulative| (&cumulative.votes_recorded, &cumulative.vote_failures)),
+        );
+        for (votes_recorded, vote_failures) in counters {
+            if recorded {
+                votes_recorded.inc();
+            } else {
+                vote_failures.inc();
+            }
+        }
+    }
+
+    /// Encode every registered metric in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        // Encoding into a Vec only fails on invalid metric families, which `new` rules out
+        let _ = TextEncoder::new().encode(&self.registry.gather(), &mut buffer);
+        String::from_utf8(buffer).unwrap_or_default()
+    }
+}
+
+/// Record latency for every request to `router`, count vote casts, and serve `GET /metrics`
+pub fn instrument(router: Router, metrics: VoteMetrics) -> Router {
+    router
+        .layer(middleware::from_fn_with_state(metrics.clone(), track))
+        .route("/metrics", get(render_metrics).with_state(metrics))
+}
+
+async fn track(State(metrics): State<VoteMetrics>, request: Request, next: Next) -> Response {
+    let method = request.method().clone();
+    // Label by route template, not the raw path, so vote UUIDs don't explode cardinality
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+
+    let started = Instant::now();
+    let response = next.run(request).await;
+    let status = response.status();
+
+    metrics
+        .request_latency
+        .with_label_values(&[method.as_str(), &route, status.as_str()])
+        .observe(started.elapsed().as_secs_f64());
+
+    if method == Method::POST && route.contains("cast_vote") {
+        metrics.count_vote(status.is_success());
+    }
+
+    response
+}
+
+async fn render_metrics(State(metrics): State<VoteMetrics>) -> impl IntoResponse {
+    (
+        [(
+            header::CONTENT_TYPE,
+            TextEncoder::new().format_type().to_string(),
+        )],
+        metrics.render(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vote_server::VoteServerRunner;
+    use axum::{http::StatusCode, routing::post};
+
+    /// Parse exposition text into (series, value) pairs, failing on any malformed sample line
+    fn parse_exposition(text: &str) -> Vec<(String, f64)> {
+        text.lines()
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                let (series, value) = line.rsplit_once(' ').expect("sample line has a value");
+                (
+                    series.to_string(),
+                    value.parse().expect("sample value is numeric"),
+                )
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_metrics_endpoint_exposition() {
+        let router = Router::new()
+            .route("/cast_vote/{vote_uuid}", post(|| async { StatusCode::OK }))
+            .route("/votes/active", get(|| async { "[]" }));
+        let metrics = VoteMetrics::new("tvs_node_1").unwrap();
+        let runner = VoteServerRunner::serve(instrument(router, metrics), "127.0.0.1", 0)
+            .await
+            .unwrap();
+        let base = format!("http://{}", runner.local_addr());
+
+        let client = reqwest::Client::new();
+        client
+            .post(format!(
+                "{}/cast_vote/550e8400-e29b-41d4-a716-446655440000",
+                base
+            ))
+            .send()
+            .await
+            .unwrap();
+        client
+            .get(format!("{}/votes/active", base))
+            .send()
+            .await
+            .unwrap();
+
+        let response = client
+            .get(format!("{}/metrics", base))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        assert!(response.headers()[reqwest::header::CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .starts_with("text/plain"));
+        let samples = parse_exposition(&response.text().await.unwrap());
+
+        let value = |series: &str| {
+            samples
+                .iter()
+                .find(|(name, _)| name == series)
+                .map(|(_, value)| *value)
+        };
+        assert_eq!(
+            value("tvs_votes_recorded_total{node=\"tvs_node_1\"}"),
+            Some(1.0)
+        );
+        assert_eq!(
+            value("tvs_vote_failures_total{node=\"tvs_node_1\"}"),
+            Some(0.0)
+        );
+        assert!(samples.iter().any(|(name, _)| {
+            name.starts_with("tvs_request_duration_seconds_count")
+                && name.contains("route=\"/cast_vote/{vote_uuid}\"")
+        }));
+
+        runner.shutdown().await.unwrap();
+    }
+
+    #[test]
+    fn test_cumulative_series_are_separate() {
+        let restored = Checkpoint {
+            votes_recorded: 40,
+            vote_failures: 2,
+        };
+        let metrics = VoteMetrics::with_cumulative("tvs_node_1", restored).unwrap();
+        metrics.count_vote(true);
+
+        let samples = parse_exposition(&metrics.render());
+        let value = |series: &str| {
+            samples
+                .iter()
+                .find(|(name, _)| name == series)
+                .map(|(_, value)| *value)
+        };
+        assert_eq!(
+            value("tvs_votes_recorded_total{node=\"tvs_node_1\"}"),
+            Some(1.0)
+        );
+        assert_eq!(
+            value("tvs_votes_recorded_cumulative_total{node=\"tvs_node_1\"}"),
+            Some(41.0)
+        );
+        assert_eq!(
+            value("tvs_vote_failures_cumulative_total{node=\"tvs_node_1\"}"),
+            Some(2.0)
+        );
+
+        // Without a checkpoint only the since-start series exist
+        let metrics = VoteMetrics::new("tvs_node_1").unwrap();
+        assert!(!metrics.render().contains("_cumulative_total"));
+    }
+}
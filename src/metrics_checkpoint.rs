@@ -0,0 +1,233 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+
+use crate::{config::TvsServerConfig, error::NodeError, metrics::VoteMetrics};
+
+/// The cumulative vote counters as `tvs.metrics_checkpoint_path` keeps them
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub votes_recorded: u64,
+    pub vote_failures: u64,
+}
+
+impl Checkpoint {
+    /// Read the checkpoint at `path`; a missing file is a first start, counting from zero
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text).map_err(|e| e.to_string()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    /// Write the checkpoint to `<path>.tmp` and rename it over `path`, so a crash while
+    /// writing leaves the previous checkpoint rather than a torn one
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let mut temp = path.as_os_str().to_owned();
+        temp.push(".tmp");
+        std::fs::write(&temp, serde_json::to_vec(self)?)?;
+        std::fs::rename(&temp, path)
+    }
+}
+
+/// Vote metrics for `node`; with `tvs.metrics_checkpoint_path` set they include cumulative
+/// counters restored from the checkpoint there
+/// A checkpoint that can't be read stops startup, as the next one would overwrite it
+pub fn vote_metrics(node: &str, tvs: &TvsServerConfig) -> Result<VoteMetrics, NodeError> {
+    let metrics = match &tvs.metrics_checkpoint_path {
+        Some(path) => {
+            let restored = Checkpoint::load(Path::new(path)).map_err(|e| {
+                NodeError::VoteServer(
+                    format!("could not restore the metrics checkpoint {}: {}", path, e).into(),
+                )
+            })?;
+            tracing::info!(
+                path = %path,
+                votes_recorded = restored.votes_recorded,
+                vote_failures = restored.vote_failures,
+                "✓ Restored cumulative vote counts from {}: {} recorded, {} failed",
+                path,
+                restored.votes_recorded,
+                restored.vote_failures
+            );
+            VoteMetrics::with_cumulative(node, restored)
+        }
+        None => VoteMetrics::new(node),
+    };
+    metrics.map_err(|e| NodeError::VoteServer(e.into()))
+}
+
+/// Writes the cumulative vote counters to the checkpoint file every interval, and once
+/// more when dropped
+pub struct MetricsCheckpoint {
+    path: PathBuf,
+    metrics: VoteMetrics,
+    task: JoinHandle<()>,
+}
+
+impl MetricsCheckpoint {
+    /// Checkpoint `metrics` as `tvs` configures; None without `tvs.metrics_checkpoint_path`
+    pub fn start(metrics: &VoteMetrics, tvs: &TvsServerConfig) -> Option<Self> {
+        let path = PathBuf::from(tvs.metrics_checkpoint_path.as_ref()?);
+        let interval = Duration::from_secs(tvs.metrics_checkpoint_interval_secs);
+        let task = tokio::spawn({
+            let path = path.clone();
+            let metrics = metrics.clone();
+            async move {
+                let mut ticks = tokio::time::interval(interval);
+                // The first tick is immediate; the restored counts are already on disk
+                ticks.tick().await;
+                loop {
+                    ticks.tick().await;
+                    write(&path, &metrics);
+                }
+            }
+        });
+        tracing::info!(
+            path = %path.display(),
+            interval_secs = interval.as_secs(),
+            "✓ Checkpointing cumulative vote counts to {} every {}s",
+            path.display(),
+            interval.as_secs()
+        );
+        Some(Self {
+            path,
+            metrics: metrics.clone(),
+            task,
+        })
+    }
+}
+
+/// Stops the periodic checkpoints and writes the final counts; the node holds it until
+/// `run_until_shutdown` returns, after the vote servers have drained
+impl Drop for MetricsCheckpoint {
+    fn drop(&mut self) {
+        self.task.abort();
+        write(&self.path, &self.metrics);
+    }
+}
+
+/// Save the cumulative counts of `metrics` to `path`; a failure is logged and the next
+/// checkpoint tries again
+fn write(path: &Path, metrics: &VoteMetrics) {
+    let Some(counts) = metrics.cumulative_counts() else {
+        return;
+    };
+    if let Err(e) = counts.save(path) {
+        tracing::warn!(
+            path = %path.display(),
+            error = %e,
+            "⚠ Could not write the metrics checkpoint {}: {}",
+            path.display(),
+            e
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checkpoint_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(name);
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    fn checkpoint_config(path: &Path, interval_secs: u64) -> TvsServerConfig {
+        TvsServerConfig {
+            metrics_checkpoint_path: Some(path.to_str().unwrap().to_string()),
+            metrics_checkpoint_interval_secs: interval_secs,
+            ..TvsServerConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_checkpoint_round_trip() {
+        let path = checkpoint_path("tvs_node_checkpoint_round_trip.json");
+        // Nothing written yet: a first start counts from zero
+        assert_eq!(Checkpoint::load(&path).unwrap(), Checkpoint::default());
+
+        let checkpoint = Checkpoint {
+            votes_recorded: 1200,
+            vote_failures: 7,
+        };
+        checkpoint.save(&path).unwrap();
+        assert_eq!(Checkpoint::load(&path).unwrap(), checkpoint);
+        assert!(!path.with_extension("json.tmp").exists());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_unreadable_checkpoint_stops_startup() {
+        let path = checkpoint_path("tvs_node_checkpoint_corrupt.json");
+        std::fs::write(&path, "{\"votes_recorded\":").unwrap();
+
+        let err = vote_metrics("tvs_node_1", &checkpoint_config(&path, 60))
+            .err()
+            .unwrap();
+        assert!(
+            err.to_string()
+                .contains("could not restore the metrics checkpoint"),
+            "{}",
+            err
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_restored_counts_continue_across_restarts() {
+        let path = checkpoint_path("tvs_node_checkpoint_restored.json");
+        Checkpoint {
+            votes_recorded: 40,
+            vote_failures: 2,
+        }
+        .save(&path)
+        .unwrap();
+
+        let metrics = vote_metrics("tvs_node_1", &checkpoint_config(&path, 60)).unwrap();
+        metrics.count_vote(true);
+        // Since start only counts this process, the cumulative counters carry on
+        assert_eq!(metrics.vote_counts(), (1, 0));
+        assert_eq!(
+            metrics.cumulative_counts(),
+            Some(Checkpoint {
+                votes_recorded: 41,
+                vote_failures: 2,
+            })
+        );
+        std::fs::remove_file(&path).unwrap();
+
+        // Without a checkpoint there is no cumulative series to report
+        let metrics = vote_metrics("tvs_node_1", &TvsServerConfig::default()).unwrap();
+        assert_eq!(metrics.cumulative_counts(), None);
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_is_written_periodically_and_on_drop() {
+        let path = checkpoint_path("tvs_node_checkpoint_periodic.json");
+        let tvs = checkpoint_config(&path, 1);
+        let metrics = vote_metrics("tvs_node_1", &tvs).unwrap();
+        let checkpoint = MetricsCheckpoint::start(&metrics, &tvs).unwrap();
+
+        metrics.count_vote(true);
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        assert_eq!(Checkpoint::load(&path).unwrap().votes_recorded, 1);
+
+        metrics.count_vote(false);
+        drop(checkpoint);
+        assert_eq!(
+            Checkpoint::load(&path).unwrap(),
+            Checkpoint {
+                votes_recorded: 1,
+                vote_failures: 1,
+            }
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+}
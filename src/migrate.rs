@@ -0,0 +1,124 @@
+use tfs::tfs::node_id::NodeId;
+
+use crate::{
+    config::{ConfigError, TvsNodeConfig},
+    error::NodeError,
+};
+
+/// Run the database migrations for this node and exit, without starting TFS or binding
+/// any ports; lets a deploy pipeline migrate ahead of rolling the nodes
+pub fn run(config: &TvsNodeConfig) -> Result<(), NodeError> {
+    let node_id = node_id(config, "run migrations")?;
+    println!("✓ Node id: {}", node_id);
+
+    let persistence = config.persistence_config();
+    println!(
+        "  Connecting to PostgreSQL at {}",
+        crate::persistence::database_host(&persistence)
+    );
+    crate::persistence::with_connect_retries(&persistence, || {
+        let pool = crate::persistence::establish_pool(&persistence)?;
+        // An explicit `migrate` always migrates, whatever skip_migrations says
+        crate::persistence::run_migrations(&pool, &node_id, false)?;
+        Ok(())
+    })?;
+
+    println!("✓ Migrations complete for node: {}", node_id);
+    Ok(())
+}
+
+/// The node id the schema is named after; commands working on the database without a
+/// running node (`action`, e.g. "run migrations") must target a fixed schema, so both
+/// `node_name` and `node_uuid` are required here
+pub fn node_id(config: &TvsNodeConfig, action: &str) -> Result<NodeId, ConfigError> {
+    let mut missing = Vec::new();
+    if config.tfs.node_name.is_none() {
+        missing.push(format!("node_name must be set to {}", action));
+    }
+    if config.tfs.node_uuid.is_none() {
+        missing.push(format!("node_uuid must be set to {}", action));
+    }
+
+    config
+        .configured_node_id()
+        .ok_or(ConfigError::Validation(missing))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PersistenceConfig;
+
+    fn config(json: serde_json::Value) -> TvsNodeConfig {
+        let mut base = serde_json::json!({
+            "server": {"cluster_message_port": 8080, "app_port": 8081, "admin_port": 8082}
+        });
+        base.as_object_mut()
+            .unwrap()
+            .extend(json.as_object().unwrap().clone());
+        serde_json::from_value(base).unwrap()
+    }
+
+    #[test]
+    fn test_node_id_from_config() {
+        let uuid = uuid::Uuid::new_v4();
+        let config = config(serde_json::json!({"node_name": "tvs_node_1", "node_uuid": uuid}));
+
+        assert_eq!(
+            node_id(&config, "run migrations").unwrap(),
+            NodeId::new("tvs_node_1".to_string(), uuid)
+        );
+    }
+
+    #[test]
+    fn test_node_id_requires_name_and_uuid() {
+        let config = config(serde_json::json!({}));
+
+        match node_id(&config, "run migrations") {
+            Err(ConfigError::Validation(errors)) => {
+                assert_eq!(errors.len(), 2);
+                assert!(errors[0].contains("node_name"));
+                assert!(errors[1].contains("node_uuid"));
+            }
+            other => panic!("expected a validation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_run_reports_unreachable_database_as_persistence_error() {
+        let mut config = config(serde_json::json!({
+            "node_name": "tvs_node_1",
+            "node_uuid": uuid::Uuid::new_v4()
+        }));
+        config.persistence = Some(PersistenceConfig {
+            database_url: Some("postgres://tvs@127.0.0.1:1/tfs_tvs_db".to_string()),
+            db_connect_retries: 0,
+            connection_timeout_secs: Some(1),
+            ..PersistenceConfig::default()
+        });
+
+        let err = run(&config).unwrap_err();
+        assert!(matches!(err, NodeError::Persistence(_)));
+        assert!(err.to_string().contains("127.0.0.1:1"));
+    }
+
+    /// Runs against a real database when `TEST_DATABASE_URL` is set; skipped otherwise
+    #[test]
+    fn test_migrations_against_test_database() {
+        let Ok(database_url) = std::env::var("TEST_DATABASE_URL") else {
+            return;
+        };
+        let mut config = config(serde_json::json!({
+            "node_name": "tvs_node_migrate_test",
+            "node_uuid": uuid::Uuid::new_v4()
+        }));
+        config.persistence = Some(PersistenceConfig {
+            database_url: Some(database_url),
+            ..PersistenceConfig::default()
+        });
+
+        run(&config).unwrap();
+        // A second run finds the schema already current
+        run(&config).unwrap();
+    }
+}
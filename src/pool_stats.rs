@@ -0,0 +1,78 @@
+use axum::{routing::get, Json, Router};
+use serde::Serialize;
+use tfs_postgres::DbPool;
+
+/// Snapshot of the connection pool, served on `/debug/pool`
+#[derive(Debug, Serialize)]
+pub struct PoolStats {
+    /// Connections currently open, idle or checked out
+    pub size: u32,
+    pub idle: u32,
+    pub in_use: u32,
+    /// Most connections the pool will open (`persistence.max_pool_size`)
+    pub max_size: u32,
+}
+
+impl PoolStats {
+    pub fn of(pool: &DbPool) -> Self {
+        let state = pool.state();
+        Self {
+            size: state.connections,
+            idle: state.idle_connections,
+            in_use: state.connections.saturating_sub(state.idle_connections),
+            max_size: pool.max_size(),
+        }
+    }
+}
+
+/// `/debug/pool`, reporting how saturated the vote backend's connection pool is
+/// Reading the state takes no connection, so it answers even while the pool is exhausted
+pub fn routes(pool: DbPool) -> Router {
+    Router::new().route(
+        "/debug/pool",
+        get(move || async move { Json(PoolStats::of(&pool)) }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diesel::{r2d2::ConnectionManager, PgConnection};
+
+    #[tokio::test]
+    async fn test_debug_pool_reports_counts() {
+        // The pool is built without connecting, so no database is needed
+        let manager =
+            ConnectionManager::<PgConnection>::new("postgres://tvs@127.0.0.1:1/tfs_tvs_db");
+        let pool = DbPool::builder()
+            .max_size(4)
+            .min_idle(Some(0))
+            .build_unchecked(manager);
+
+        let runner = crate::vote_server::VoteServerRunner::serve(routes(pool), "127.0.0.1", 0)
+            .await
+            .unwrap();
+        let body: serde_json::Value =
+            reqwest::get(format!("http://{}/debug/pool", runner.local_addr()))
+                .await
+                .unwrap()
+                .json()
+                .await
+                .unwrap();
+        runner.shutdown().await.unwrap();
+
+        for field in ["size", "idle", "in_use", "max_size"] {
+            assert!(
+                body[field].is_u64(),
+                "{} should be a non-negative integer: {}",
+                field,
+                body
+            );
+        }
+        assert_eq!(body["max_size"], 4);
+        assert_eq!(
+            body["in_use"].as_u64().unwrap(),
+            body["size"].as_u64().unwrap() - body["idle"].as_u64().unwrap()
+        );
+    }
+}
@@ -0,0 +1,249 @@
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{header, HeaderValue, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    Router,
+};
+
+use crate::config::RateLimitConfig;
+
+/// Past this many tracked clients, buckets that have refilled completely are dropped,
+/// so a scan from many addresses can't grow the map without bound
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// Token bucket holding up to `capacity` tokens, refilled continuously at `refill_per_sec`
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    capacity: f64,
+    refill_per_sec: f64,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    /// A full bucket as of `now`
+    pub fn new(requests_per_second: u32, burst: u32, now: Instant) -> Self {
+        Self {
+            capacity: f64::from(burst),
+            refill_per_sec: f64::from(requests_per_second),
+            tokens: f64::from(burst),
+            updated: now,
+        }
+    }
+
+    /// Take a token at `now`, or return how long until one is available
+    pub fn try_acquire(&mut self, now: Instant) -> Result<(), Duration> {
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - self.tokens) / self.refill_per_sec,
+            ))
+        }
+    }
+
+    /// Whether the bucket would be full at `now`, i.e. the client has been idle long enough
+    /// that forgetting it changes nothing
+    fn is_full(&self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens + elapsed * self.refill_per_sec >= self.capacity
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.updated = now;
+    }
+}
+
+/// One token bucket per client IP
+#[derive(Clone)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: Arc<Mutex<HashMap<IpAddr, TokenBucket>>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            buckets: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Take a token from `client`'s bucket at `now`, or return how long it has to wait
+    pub fn check(&self, client: IpAddr, now: Instant) -> Result<(), Duration> {
+        // A poisoned lock only means another request panicked mid-update; the map is still usable
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if buckets.len() >= PRUNE_THRESHOLD {
+            buckets.retain(|_, bucket| !bucket.is_full(now));
+        }
+        buckets
+            .entry(client)
+            .or_insert_with(|| {
+                TokenBucket::new(self.config.requests_per_second, self.config.burst, now)
+            })
+            .try_acquire(now)
+    }
+}
+
+/// Limit each client IP on `router` per `config`, answering `config.status` (429 by default)
+/// with `Retry-After` once its bucket is empty
+/// Needs connect info, so the router must be served with `SocketAddr` connect info
+pub fn apply(router: Router, config: RateLimitConfig) -> Router {
+    router.layer(middleware::from_fn_with_state(
+        RateLimiter::new(config),
+        limit,
+    ))
+}
+
+async fn limit(
+    State(limiter): State<RateLimiter>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    match limiter.check(peer.ip(), Instant::now()) {
+        Ok(()) => next.run(request).await,
+        Err(wait) => {
+            // Retry-After is whole seconds; rounding down would invite an early retry
+            let retry_after = limiter
+                .config
+                .retry_after_secs
+                .unwrap_or_else(|| wait.as_secs_f64().ceil().max(1.0) as u64);
+            // Validation keeps the status to 4xx and 5xx
+            let status = StatusCode::from_u16(limiter.config.status)
+                .unwrap_or(StatusCode::TOO_MANY_REQUESTS);
+            (
+                status,
+                [(header::RETRY_AFTER, HeaderValue::from(retry_after))],
+                "rate limit exceeded",
+            )
+                .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::get;
+
+    #[test]
+    fn test_burst_then_reject() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(2, 5, start);
+
+        for _ in 0..5 {
+            assert_eq!(bucket.try_acquire(start), Ok(()));
+        }
+        // Empty: the next token arrives after 1 / requests_per_second
+        assert_eq!(bucket.try_acquire(start), Err(Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn test_sustained_load_is_held_to_the_rate() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(10, 3, start);
+        for _ in 0..3 {
+            bucket.try_acquire(start).unwrap();
+        }
+
+        // A client sending 20 requests per second for two seconds gets 10 per second through
+        let accepted = (1..=40)
+            .map(|i| start + Duration::from_millis(50 * i))
+            .filter(|&now| bucket.try_acquire(now).is_ok())
+            .count();
+        assert_eq!(accepted, 20);
+    }
+
+    #[test]
+    fn test_idle_bucket_refills_only_to_burst() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(100, 2, start);
+        bucket.try_acquire(start).unwrap();
+        bucket.try_acquire(start).unwrap();
+
+        let later = start + Duration::from_secs(60);
+        assert!(bucket.is_full(later));
+        assert!(bucket.try_acquire(later).is_ok());
+        assert!(bucket.try_acquire(later).is_ok());
+        assert!(bucket.try_acquire(later).is_err());
+    }
+
+    #[test]
+    fn test_clients_have_separate_buckets() {
+        let limiter = RateLimiter::new(RateLimitConfig::new(1, 1));
+        let now = Instant::now();
+        let first: IpAddr = "10.0.0.1".parse().unwrap();
+        let second: IpAddr = "10.0.0.2".parse().unwrap();
+
+        assert!(limiter.check(first, now).is_ok());
+        assert!(limiter.check(first, now).is_err());
+        assert!(limiter.check(second, now).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_limited_route_returns_429_and_probes_are_exempt() {
+        let limited = apply(
+            Router::new().route("/votes/active", get(|| async { "[]" })),
+            RateLimitConfig::new(1, 1),
+        );
+        let router = limited.route("/healthz", get(|| async { "ok" }));
+        let runner = crate::vote_server::VoteServerRunner::serve(router, "127.0.0.1", 0)
+            .await
+            .unwrap();
+        let base = format!("http://{}", runner.local_addr());
+
+        let first = reqwest::get(format!("{}/votes/active", base))
+            .await
+            .unwrap();
+        assert_eq!(first.status(), reqwest::StatusCode::OK);
+        let second = reqwest::get(format!("{}/votes/active", base))
+            .await
+            .unwrap();
+        assert_eq!(second.status(), reqwest::StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(second.headers()["retry-after"], "1");
+
+        for _ in 0..3 {
+            let health = reqwest::get(format!("{}/healthz", base)).await.unwrap();
+            assert_eq!(health.status(), reqwest::StatusCode::OK);
+        }
+
+        runner.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_refusal_status_and_retry_after_are_configurable() {
+        let config = RateLimitConfig {
+            status: 503,
+            retry_after_secs: Some(30),
+            ..RateLimitConfig::new(1, 1)
+        };
+        let limited = apply(
+            Router::new().route("/votes/active", get(|| async { "[]" })),
+            config,
+        );
+        let runner = crate::vote_server::VoteServerRunner::serve(limited, "127.0.0.1", 0)
+            .await
+            .unwrap();
+        let url = format!("http://{}/votes/active", runner.local_addr());
+
+        reqwest::get(&url).await.unwrap();
+        let refused = reqwest::get(&url).await.unwrap();
+        assert_eq!(refused.status(), reqwest::StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(refused.headers()["retry-after"], "30");
+
+        runner.shutdown().await.unwrap();
+    }
+}
@@ -0,0 +1,191 @@
+use std::{
+    future::Future,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tokio::time::Instant;
+
+use crate::config::ReadinessConfig;
+
+/// How often a readiness probe is retried
+pub const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Poll `probe` every `interval` until it succeeds or `timeout` elapses
+/// Returns the number of attempts on success, or the last probe error on timeout
+pub async fn wait_until_ready<F, Fut>(
+    timeout: Duration,
+    interval: Duration,
+    mut probe: F,
+) -> Result<u32, String>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<(), String>>,
+{
+    let deadline = Instant::now() + timeout;
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let error = match tokio::time::timeout_at(deadline, probe()).await {
+            Ok(Ok(())) => return Ok(attempts),
+            Ok(Err(e)) => e,
+            Err(_) => "probe did not complete".to_string(),
+        };
+
+        if Instant::now() + interval >= deadline {
+            return Err(error);
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// Probe the TFS node: its id must be available and its HTTP API must accept connections
+pub async fn probe_tfs(
+    app_interface: &tfs::tfs_app_interface::TFSAppInterface,
+    app_port: u16,
+) -> Result<(), String> {
+    let node_id = app_interface.get_this_node_id().to_string();
+    if node_id.is_empty() {
+        return Err("node id not assigned yet".to_string());
+    }
+
+    tokio::net::TcpStream::connect(("127.0.0.1", app_port))
+        .await
+        .map(|_| ())
+        .map_err(|e| {
+            format!(
+                "HTTP API on port {} not accepting connections: {}",
+                app_port, e
+            )
+        })
+}
+
+/// What `/readyz` reports, changed only after `readiness.failure_threshold` failed checks
+/// in a row (or `success_threshold` successful ones while not ready). Starts ready, since the
+/// vote server only comes up once the backend has been set up
+#[derive(Debug, Clone)]
+pub struct ReadinessGate {
+    config: ReadinessConfig,
+    state: Arc<Mutex<GateState>>,
+}
+
+#[derive(Debug)]
+struct GateState {
+    ready: bool,
+    /// Checks in a row that disagreed with `ready`
+    streak: u32,
+}
+
+impl ReadinessGate {
+    pub fn new(config: ReadinessConfig) -> Self {
+        Self {
+            config,
+            state: Arc::new(Mutex::new(GateState {
+                ready: true,
+                streak: 0,
+            })),
+        }
+    }
+
+    /// Count one check result, returning whether the node should now report ready
+    pub fn observe(&self, passed: bool) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if passed == state.ready {
+            state.streak = 0;
+            return state.ready;
+        }
+
+        state.streak += 1;
+        let threshold = if state.ready {
+            self.config.failure_threshold
+        } else {
+            self.config.success_threshold
+        };
+        if state.streak >= threshold {
+            state.ready = passed;
+            state.streak = 0;
+        }
+        state.ready
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    };
+
+    const TICK: Duration = Duration::from_millis(5);
+
+    #[tokio::test]
+    async fn test_waits_for_slow_interface() {
+        // Stands in for a TFS interface that only answers from the fourth call on
+        let calls = Arc::new(AtomicU32::new(0));
+        let probe_calls = calls.clone();
+        let result = wait_until_ready(Duration::from_secs(5), TICK, move || {
+            let calls = probe_calls.clone();
+            async move {
+                if calls.fetch_add(1, Ordering::SeqCst) < 3 {
+                    Err("still starting".to_string())
+                } else {
+                    Ok(())
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(result, Ok(4));
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_times_out_with_last_error() {
+        let result = wait_until_ready(Duration::from_millis(50), TICK, || async {
+            Err("connection refused".to_string())
+        })
+        .await;
+
+        assert_eq!(result, Err("connection refused".to_string()));
+    }
+
+    #[test]
+    fn test_gate_needs_consecutive_failures_and_successes() {
+        let gate = ReadinessGate::new(ReadinessConfig {
+            failure_threshold: 3,
+            success_threshold: 2,
+        });
+
+        // A blip shorter than the threshold doesn't flip readiness
+        assert!(gate.observe(false));
+        assert!(gate.observe(false));
+        assert!(gate.observe(true));
+        assert!(gate.observe(false));
+        assert!(gate.observe(false));
+        assert!(!gate.observe(false));
+
+        // Recovering takes two successes in a row
+        assert!(!gate.observe(true));
+        assert!(!gate.observe(false));
+        assert!(!gate.observe(true));
+        assert!(gate.observe(true));
+    }
+
+    #[test]
+    fn test_default_gate_follows_every_check() {
+        let gate = ReadinessGate::new(ReadinessConfig::default());
+        assert!(!gate.observe(false));
+        assert!(gate.observe(true));
+    }
+
+    #[tokio::test]
+    async fn test_hung_probe_times_out() {
+        let result = wait_until_ready(Duration::from_millis(50), TICK, || {
+            std::future::pending::<Result<(), String>>()
+        })
+        .await;
+
+        assert!(result.unwrap_err().contains("did not complete"));
+    }
+}
@@ -0,0 +1,221 @@
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use serde::Serialize;
+
+/// `runtime.ready_notify` value that selects the systemd notify protocol over a file
+pub const SYSTEMD: &str = "systemd";
+
+/// What a supervisor learns once both servers are up
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadyRecord {
+    pub node_id: String,
+    pub pid: u32,
+    pub cluster_message_port: u16,
+    pub app_port: u16,
+    /// None when the admin frontend shares the cluster port or is off
+    pub admin_port: Option<u16>,
+    /// Bound vote listeners, with the actual port when one was picked by the OS
+    pub vote_addrs: Vec<String>,
+    /// Set when the record is written
+    pub ready_at: String,
+}
+
+/// Where to announce readiness, from `runtime.ready_notify`
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReadyNotifier {
+    /// Write the record as JSON to this path, and remove it again on shutdown
+    File(PathBuf),
+    /// `READY=1` (and `STOPPING=1` on shutdown) to `$NOTIFY_SOCKET`
+    Systemd,
+}
+
+impl ReadyNotifier {
+    pub fn parse(value: &str) -> Self {
+        if value == SYSTEMD {
+            ReadyNotifier::Systemd
+        } else {
+            ReadyNotifier::File(PathBuf::from(value))
+        }
+    }
+
+    /// Announce that the node is ready, stamping `record` with the current time
+    pub fn notify(&self, record: &ReadyRecord) -> std::io::Result<()> {
+        match self {
+            ReadyNotifier::File(path) => {
+                let record = ReadyRecord {
+                    ready_at: Utc::now().to_rfc3339(),
+                    ..record.clone()
+                };
+                write_record(path, &record)
+            }
+            ReadyNotifier::Systemd => sd_notify(&format!("READY=1\nMAINPID={}", record.pid)),
+        }
+    }
+
+    /// Announce readiness, logging the outcome; failing to is reported but doesn't stop the node
+    /// The returned guard withdraws the announcement when dropped
+    pub fn announce(self, record: &ReadyRecord) -> Announcement {
+        match self.notify(record) {
+            Ok(()) => tracing::info!(notify = ?self, "✓ Announced readiness to {:?}", self),
+            Err(e) => tracing::warn!(
+                notify = ?self,
+                error = %e,
+                "⚠ Could not announce readiness to {:?} (runtime.ready_notify): {}",
+                self,
+                e
+            ),
+        }
+        Announcement(self)
+    }
+
+    /// Take the announcement back on shutdown, so a stale file isn't mistaken for a live node
+    pub fn withdraw(&self) -> std::io::Result<()> {
+        match self {
+            ReadyNotifier::File(path) => match std::fs::remove_file(path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            },
+            ReadyNotifier::Systemd => sd_notify("STOPPING=1"),
+        }
+    }
+}
+
+/// A readiness announcement, withdrawn on drop
+pub struct Announcement(ReadyNotifier);
+
+impl Drop for Announcement {
+    fn drop(&mut self) {
+        if let Err(e) = self.0.withdraw() {
+            tracing::warn!(notify = ?self.0, error = %e, "⚠ Could not withdraw readiness from {:?}: {}", self.0, e);
+        }
+    }
+}
+
+/// Write `record` next to `path` and rename it into place, so a supervisor polling for the
+/// file never reads it half-written
+fn write_record(path: &Path, record: &ReadyRecord) -> std::io::Result<()> {
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".tmp");
+    let partial = PathBuf::from(partial);
+    std::fs::write(&partial, serde_json::to_vec_pretty(record)?)?;
+    std::fs::rename(&partial, path)
+}
+
+/// Send `state` over the systemd notify protocol; does nothing outside a `Type=notify` unit,
+/// where `NOTIFY_SOCKET` isn't set
+#[cfg(unix)]
+fn sd_notify(state: &str) -> std::io::Result<()> {
+    use std::os::unix::net::UnixDatagram;
+
+    let Some(socket) = std::env::var_os("NOTIFY_SOCKET") else {
+        return Ok(());
+    };
+    let socket = socket.to_string_lossy().into_owned();
+    let datagram = UnixDatagram::unbound()?;
+    match socket.strip_prefix('@') {
+        #[cfg(target_os = "linux")]
+        Some(name) => {
+            use std::os::linux::net::SocketAddrExt;
+            let addr = std::os::unix::net::SocketAddr::from_abstract_name(name)?;
+            datagram.send_to_addr(state.as_bytes(), &addr)?;
+        }
+        #[cfg(not(target_os = "linux"))]
+        Some(_) => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "abstract NOTIFY_SOCKET addresses are Linux-only",
+            ))
+        }
+        None => {
+            datagram.send_to(state.as_bytes(), &socket)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn sd_notify(_state: &str) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "systemd notification needs a Unix platform",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    fn record() -> ReadyRecord {
+        ReadyRecord {
+            node_id: "tvs_node_1".to_string(),
+            pid: 4242,
+            cluster_message_port: 8080,
+            app_port: 8081,
+            admin_port: None,
+            vote_addrs: vec!["127.0.0.1:8090".to_string()],
+            ready_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(ReadyNotifier::parse("systemd"), ReadyNotifier::Systemd);
+        assert_eq!(
+            ReadyNotifier::parse("/run/tvs/ready.json"),
+            ReadyNotifier::File(PathBuf::from("/run/tvs/ready.json"))
+        );
+    }
+
+    #[test]
+    fn test_file_notifier_writes_record_and_withdraws_it() {
+        let path = std::env::temp_dir().join("tvs_node_ready_notify_test.json");
+        let notifier = ReadyNotifier::File(path.clone());
+
+        notifier.notify(&record()).unwrap();
+        let written: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        let ready_at = written["ready_at"].as_str().unwrap().to_string();
+        assert!(chrono::DateTime::parse_from_rfc3339(&ready_at).is_ok());
+        assert_eq!(
+            written,
+            serde_json::json!({
+                "node_id": "tvs_node_1",
+                "pid": 4242,
+                "cluster_message_port": 8080,
+                "app_port": 8081,
+                "admin_port": null,
+                "vote_addrs": ["127.0.0.1:8090"],
+                "ready_at": ready_at
+            })
+        );
+
+        notifier.withdraw().unwrap();
+        assert!(!path.exists());
+        // Already gone is fine, e.g. removed by the supervisor
+        notifier.withdraw().unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[serial]
+    fn test_systemd_notifier_sends_ready() {
+        let socket = std::env::temp_dir().join("tvs_node_ready_notify_test.sock");
+        let _ = std::fs::remove_file(&socket);
+        let listener = std::os::unix::net::UnixDatagram::bind(&socket).unwrap();
+        std::env::set_var("NOTIFY_SOCKET", &socket);
+
+        let result = ReadyNotifier::Systemd.notify(&record());
+        std::env::remove_var("NOTIFY_SOCKET");
+        result.unwrap();
+
+        let mut buf = [0u8; 64];
+        let len = listener.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..len], b"READY=1\nMAINPID=4242");
+        std::fs::remove_file(&socket).unwrap();
+
+        // Outside a notify unit there is no socket, and nothing to do
+        ReadyNotifier::Systemd.notify(&record()).unwrap();
+    }
+}
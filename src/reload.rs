@@ -0,0 +1,266 @@
+use serde_json::Value;
+
+use crate::config::{ConfigError, ConfigFormat, LogLevel, TvsNodeConfig};
+
+/// Settings that can change while the node runs; everything else needs a restart
+const HOT_RELOADABLE: &[&str] = &["log_level"];
+
+/// Where to re-read the config from on SIGHUP, and how it was loaded the first time
+#[derive(Debug, Clone)]
+pub struct ConfigReloader {
+    pub path: String,
+    pub format: Option<ConfigFormat>,
+    pub lenient: bool,
+    /// `--profile` was given; its file is re-read and layered over the base again
+    pub profile: Option<String>,
+    /// Further `--config` files, re-read and layered in order after the profile
+    pub overlays: Vec<String>,
+    /// `--log-level` was given; it keeps winning over the file after a reload
+    pub log_level_override: Option<LogLevel>,
+    /// `--skip-migrations` was given; re-applied so a reload doesn't report it as a change
+    pub skip_migrations_override: bool,
+    /// `--quiet` was given; re-applied for the same reason
+    pub quiet_override: bool,
+    /// `--verbose-startup` was given; likewise
+    pub verbose_startup_override: bool,
+    /// `--auto-port` was given; likewise
+    pub auto_port_override: bool,
+    /// `--port-offset` was given; re-applied to the re-read ports
+    pub port_offset: Option<u16>,
+}
+
+impl ConfigReloader {
+    /// Read the config the same way startup did: files, profile, env overrides, flags, validation
+    pub fn read(&self) -> Result<TvsNodeConfig, ConfigError> {
+        let mut config =
+            TvsNodeConfig::read_config_with_format(&self.path, self.format, self.lenient)?;
+        if let Some(profile) = &self.profile {
+            let path = crate::config::profile_path(&self.path, profile);
+            config = config.with_profile(profile, &path, self.format, self.lenient)?;
+        }
+        for overlay in &self.overlays {
+            config = config.with_overlay(overlay, self.lenient)?;
+        }
+        config.apply_env_overrides()?;
+        if let Some(offset) = self.port_offset {
+            config.apply_port_offset(offset)?;
+        }
+        if let Some(level) = self.log_level_override {
+            config.tfs.log_level = Some(level.as_str().to_string());
+        }
+        if self.skip_migrations_override {
+            config
+                .persistence
+                .get_or_insert_with(Default::default)
+                .skip_migrations = true;
+        }
+        if self.quiet_override {
+            config.quiet = true;
+        }
+        if self.verbose_startup_override {
+            config.verbose_startup = true;
+        }
+        if self.auto_port_override {
+            config.tvs.get_or_insert_with(Default::default).auto_port = true;
+        }
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+/// What a reload changed
+#[derive(Debug, Default, PartialEq)]
+pub struct ReloadSummary {
+    /// Hot-reloadable settings that were applied to the running config
+    pub applied: Vec<String>,
+    /// Changed settings that only take effect after a restart
+    pub restart_required: Vec<String>,
+}
+
+/// Copy the hot-reloadable settings from `reloaded` into `current`, leaving every other
+/// setting untouched, and report which changes were applied and which were ignored
+pub fn merge(current: &mut TvsNodeConfig, reloaded: &TvsNodeConfig) -> ReloadSummary {
+    // Both sides serialize the same struct, so this can't fail in practice
+    let before = serde_json::to_value(&*current).unwrap_or_default();
+    let after = serde_json::to_value(reloaded).unwrap_or_default();
+
+    let mut summary = ReloadSummary::default();
+    for path in changed_paths(&before, &after, "") {
+        if HOT_RELOADABLE.contains(&path.as_str()) {
+            summary.applied.push(path);
+        } else {
+            summary.restart_required.push(path);
+        }
+    }
+
+    if summary.applied.iter().any(|path| path == "log_level") {
+        current.tfs.log_level = reloaded.tfs.log_level.clone();
+    }
+    summary
+}
+
+/// Dotted paths of every leaf that differs between two JSON documents
+fn changed_paths(before: &Value, after: &Value, prefix: &str) -> Vec<String> {
+    match (before, after) {
+        (Value::Object(before), Value::Object(after)) => {
+            let mut keys: Vec<&String> = before.keys().chain(after.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            keys.into_iter()
+                .flat_map(|key| {
+                    let path = if prefix.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{}.{}", prefix, key)
+                    };
+                    let missing = Value::Null;
+                    changed_paths(
+                        before.get(key).unwrap_or(&missing),
+                        after.get(key).unwrap_or(&missing),
+                        &path,
+                    )
+                })
+                .collect()
+        }
+        _ if before == after => Vec::new(),
+        _ => vec![prefix.to_string()],
+    }
+}
+
+/// Handle SIGHUP: re-read the config, apply what can change live and log the rest
+pub fn reload(reloader: Option<&ConfigReloader>, current: &mut TvsNodeConfig) {
+    let Some(reloader) = reloader else {
+        tracing::warn!(
+            "⚠ Received SIGHUP, but the config wasn't loaded from a file - nothing to reload"
+        );
+        return;
+    };
+
+    let reloaded = match reloader.read() {
+        Ok(reloaded) => reloaded,
+        Err(e) => {
+            tracing::warn!(
+                path = %reloader.path,
+                error = %e,
+                "⚠ Config reload failed, keeping the running config: {}",
+                e
+            );
+            return;
+        }
+    };
+
+    let summary = merge(current, &reloaded);
+    tracing::info!(
+        path = %reloader.path,
+        applied = summary.applied.len(),
+        "✓ Reloaded configuration from {}",
+        reloader.path
+    );
+    for key in &summary.restart_required {
+        tracing::warn!(key = %key, "⚠ {} changed - ignored, restart required", key);
+    }
+    if summary.applied.iter().any(|path| path == "log_level") {
+        let level = current.tfs.log_level.as_deref().unwrap_or("info");
+        match crate::logging::set_log_level(level) {
+            Ok(()) => tracing::info!(level, "✓ Log level set to {}", level),
+            Err(e) => tracing::warn!(
+                level,
+                error = %e,
+                "⚠ log_level changed but could not be applied: {}",
+                e
+            ),
+        }
+    }
+    if summary == ReloadSummary::default() {
+        tracing::info!("  No changes");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(json: Value) -> TvsNodeConfig {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_merge_applies_only_hot_reloadable_fields() {
+        let mut current = config(serde_json::json!({
+            "server": {"cluster_message_port": 8080, "app_port": 8081, "admin_port": 8082},
+            "log_level": "info",
+            "tvs": {"vote_port": 8090}
+        }));
+        let reloaded = config(serde_json::json!({
+            "server": {"cluster_message_port": 8080, "app_port": 9081, "admin_port": 8082},
+            "log_level": "debug",
+            "tvs": {"vote_port": 9090}
+        }));
+
+        let summary = merge(&mut current, &reloaded);
+
+        assert_eq!(summary.applied, vec!["log_level".to_string()]);
+        assert_eq!(
+            summary.restart_required,
+            vec!["server.app_port".to_string(), "tvs.vote_port".to_string()]
+        );
+        assert_eq!(current.tfs.log_level.as_deref(), Some("debug"));
+        assert_eq!(current.tfs.server.app_port, 8081);
+        assert_eq!(current.tvs_config().unwrap().vote_port, 8090);
+    }
+
+    #[test]
+    fn test_merge_without_changes() {
+        let json = serde_json::json!({
+            "server": {"cluster_message_port": 8080, "app_port": 8081, "admin_port": 8082}
+        });
+        let mut current = config(json.clone());
+
+        assert_eq!(merge(&mut current, &config(json)), ReloadSummary::default());
+    }
+
+    #[test]
+    fn test_merge_reports_added_section() {
+        let mut current = config(serde_json::json!({
+            "server": {"cluster_message_port": 8080, "app_port": 8081, "admin_port": 8082}
+        }));
+        let reloaded = config(serde_json::json!({
+            "server": {"cluster_message_port": 8080, "app_port": 8081, "admin_port": 8082},
+            "runtime": {"nice": 5}
+        }));
+
+        let summary = merge(&mut current, &reloaded);
+        assert_eq!(summary.restart_required, vec!["runtime".to_string()]);
+        assert!(current.runtime.is_none());
+    }
+
+    #[test]
+    fn test_failed_reload_is_logged_with_path() {
+        let mut current = config(serde_json::json!({
+            "server": {"cluster_message_port": 8080, "app_port": 8081, "admin_port": 8082}
+        }));
+        let reloader = ConfigReloader {
+            path: "/nonexistent/tvs_node.json".to_string(),
+            format: None,
+            lenient: false,
+            profile: None,
+            overlays: Vec::new(),
+            log_level_override: None,
+            skip_migrations_override: false,
+            quiet_override: false,
+            verbose_startup_override: false,
+            auto_port_override: false,
+            port_offset: None,
+        };
+
+        let events = crate::logging::tests::Capture::json_events(|| {
+            reload(Some(&reloader), &mut current);
+            reload(None, &mut current);
+        });
+
+        assert_eq!(events.len(), 2, "{:?}", events);
+        assert!(events.iter().all(|event| event["level"] == "WARN"));
+        assert_eq!(events[0]["fields"]["path"], "/nonexistent/tvs_node.json");
+        assert!(events[0]["fields"]["error"].is_string());
+    }
+}
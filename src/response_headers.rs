@@ -0,0 +1,85 @@
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderName, HeaderValue},
+    middleware::{self, Next},
+    response::Response,
+    Router,
+};
+
+/// Header naming the node that served a vote server response
+pub const NODE_ID_HEADER: HeaderName = HeaderName::from_static("x-tvs-node-id");
+
+/// `Server` value sent on every vote server response
+fn server_header() -> HeaderValue {
+    HeaderValue::from_static(concat!("tvs_node/", env!("CARGO_PKG_VERSION")))
+}
+
+/// Headers added to every response, probes included
+#[derive(Clone)]
+struct ResponseHeaders {
+    /// None when `tvs.expose_node_header` is off
+    node_id: Option<HeaderValue>,
+}
+
+/// Set `Server: tvs_node/<version>` on every response from `router`, and `X-TVS-Node-Id`
+/// when `node_id` is given, so a response can be traced to its node behind a load balancer
+pub fn apply(router: Router, node_id: Option<&str>) -> Router {
+    // Node ids are generated identifiers, but don't fail startup over one that isn't a valid header
+    let headers = ResponseHeaders {
+        node_id: node_id.and_then(|node_id| HeaderValue::from_str(node_id).ok()),
+    };
+    router.layer(middleware::from_fn_with_state(headers, add_headers))
+}
+
+async fn add_headers(
+    State(headers): State<ResponseHeaders>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let mut response = next.run(request).await;
+    let response_headers = response.headers_mut();
+    response_headers.insert(header::SERVER, server_header());
+    if let Some(node_id) = headers.node_id {
+        response_headers.insert(NODE_ID_HEADER, node_id);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::get;
+
+    async fn healthz(node_id: Option<&str>) -> reqwest::Response {
+        let router = apply(
+            Router::new().route("/healthz", get(|| async { "ok" })),
+            node_id,
+        );
+        let runner = crate::vote_server::VoteServerRunner::serve(router, "127.0.0.1", 0)
+            .await
+            .unwrap();
+        let response = reqwest::get(format!("http://{}/healthz", runner.local_addr()))
+            .await
+            .unwrap();
+        runner.shutdown().await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_headers_on_health_response() {
+        let response = healthz(Some("tvs_node_1")).await;
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        assert_eq!(
+            response.headers()["server"],
+            format!("tvs_node/{}", env!("CARGO_PKG_VERSION")).as_str()
+        );
+        assert_eq!(response.headers()["x-tvs-node-id"], "tvs_node_1");
+    }
+
+    #[tokio::test]
+    async fn test_node_header_can_be_hidden() {
+        let response = healthz(None).await;
+        assert!(response.headers().contains_key("server"));
+        assert!(!response.headers().contains_key("x-tvs-node-id"));
+    }
+}
@@ -0,0 +1,117 @@
+use crate::config::RuntimeConfig;
+
+/// Apply process-level runtime settings from the config
+/// Failures are reported as warnings so an unsupported platform or missing
+/// permission never blocks startup
+pub fn apply_runtime_config(config: &RuntimeConfig) {
+    if let Some(nice) = config.nice {
+        apply_niceness(nice);
+    }
+}
+
+/// Build the multi-threaded Tokio runtime, honouring `runtime.thread_stack_bytes`
+pub fn build_tokio_runtime(config: &RuntimeConfig) -> std::io::Result<tokio::runtime::Runtime> {
+    let mut builder = tokio::runtime::Builder::new_multi_thread();
+    builder.enable_all();
+
+    if let Some(stack_bytes) = config.thread_stack_bytes {
+        builder.thread_stack_size(stack_bytes);
+    }
+
+    builder.build()
+}
+
+#[cfg(unix)]
+fn apply_niceness(nice: i32) {
+    // SAFETY: setpriority only reads its integer arguments; `who == 0` targets this process
+    let result = unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, nice) };
+    if result == 0 {
+        println!("✓ Process niceness set to {}", nice);
+    } else {
+        println!(
+            "⚠ Could not set process niceness to {}: {}",
+            nice,
+            std::io::Error::last_os_error()
+        );
+        println!("  Raising priority (negative values) usually requires CAP_SYS_NICE or root");
+    }
+}
+
+#[cfg(not(unix))]
+fn apply_niceness(nice: i32) {
+    println!(
+        "⚠ runtime.nice = {} ignored: process niceness is not supported on this platform",
+        nice
+    );
+}
+
+/// Wait for SIGTERM or SIGINT (Ctrl+C), returning the name of the signal received
+#[cfg(unix)]
+pub async fn wait_for_shutdown_signal() -> &'static str {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut terminate = signal(SignalKind::terminate()).expect("Failed to install SIGTERM handler");
+    let mut interrupt = signal(SignalKind::interrupt()).expect("Failed to install SIGINT handler");
+
+    tokio::select! {
+        _ = terminate.recv() => "SIGTERM",
+        _ = interrupt.recv() => "SIGINT",
+    }
+}
+
+/// SIGHUP listener used to trigger a config reload; never fires on platforms without SIGHUP
+pub(crate) struct ReloadSignal {
+    #[cfg(unix)]
+    hangup: tokio::signal::unix::Signal,
+}
+
+impl ReloadSignal {
+    pub fn new() -> Self {
+        Self {
+            #[cfg(unix)]
+            hangup: tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())
+                .expect("Failed to install SIGHUP handler"),
+        }
+    }
+
+    /// Wait for the next SIGHUP
+    pub async fn recv(&mut self) {
+        #[cfg(unix)]
+        self.hangup.recv().await;
+        #[cfg(not(unix))]
+        std::future::pending::<()>().await;
+    }
+}
+
+/// SIGUSR1 listener used to toggle vote draining; never fires on platforms without SIGUSR1
+pub(crate) struct DrainSignal {
+    #[cfg(unix)]
+    user1: tokio::signal::unix::Signal,
+}
+
+impl DrainSignal {
+    pub fn new() -> Self {
+        Self {
+            #[cfg(unix)]
+            user1: tokio::signal::unix::signal(tokio::signal::unix::SignalKind::user_defined1())
+                .expect("Failed to install SIGUSR1 handler"),
+        }
+    }
+
+    /// Wait for the next SIGUSR1
+    pub async fn recv(&mut self) {
+        #[cfg(unix)]
+        self.user1.recv().await;
+        #[cfg(not(unix))]
+        std::future::pending::<()>().await;
+    }
+}
+
+/// Wait for Ctrl+C, the only shutdown signal available on this platform
+#[cfg(not(unix))]
+pub async fn wait_for_shutdown_signal() -> &'static str {
+    tokio::signal::ctrl_c()
+        .await
+        .expect("Failed to install Ctrl+C handler");
+    "Ctrl+C"
+}
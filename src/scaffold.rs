@@ -0,0 +1,199 @@
+use std::fmt::Write;
+
+use clap::ValueEnum;
+
+use crate::config::TvsNodeConfig;
+
+/// Deployment target for `tvs_node scaffold`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ScaffoldTarget {
+    /// A systemd service unit
+    Systemd,
+    /// A docker-compose service definition
+    Compose,
+}
+
+/// Ports and environment a deployment needs, derived from a resolved config
+struct DeploymentSpec {
+    node_name: String,
+    config_path: String,
+    /// (port, description) pairs, in the order they should be published
+    ports: Vec<(u16, &'static str)>,
+    env: Vec<(&'static str, String)>,
+}
+
+impl DeploymentSpec {
+    fn from_config(config: &TvsNodeConfig, config_path: &str) -> Self {
+        let server = &config.tfs.server;
+        let node_name = config
+            .tfs
+            .node_name
+            .clone()
+            .unwrap_or_else(|| "tvs_node".to_string());
+
+        let mut ports = vec![
+            (server.cluster_message_port, "Cluster messaging"),
+            (server.app_port, "HTTP API"),
+            (server.admin_port, "Admin interface"),
+        ];
+        let mut env = vec![
+            ("NODE_NAME", node_name.clone()),
+            (
+                "CLUSTER_MESSAGE_PORT",
+                server.cluster_message_port.to_string(),
+            ),
+            ("APP_PORT", server.app_port.to_string()),
+            ("ADMIN_PORT", server.admin_port.to_string()),
+        ];
+
+        // The vote server also starts with defaults when the tvs section is absent
+        let tvs = config.vote_server_config();
+        if tvs.enabled {
+            ports.push((tvs.vote_port, "Vote service"));
+            env.push(("TVS_VOTE_PORT", tvs.vote_port.to_string()));
+            env.push(("TVS_VOTE_HOST", tvs.vote_host));
+        }
+
+        Self {
+            node_name,
+            config_path: config_path.to_string(),
+            ports,
+            env,
+        }
+    }
+}
+
+/// Render deployment wiring for the given config without starting anything
+pub fn render(config: &TvsNodeConfig, config_path: &str, target: ScaffoldTarget) -> String {
+    let spec = DeploymentSpec::from_config(config, config_path);
+    match target {
+        ScaffoldTarget::Systemd => render_systemd(&spec),
+        ScaffoldTarget::Compose => render_compose(&spec),
+    }
+}
+
+fn render_systemd(spec: &DeploymentSpec) -> String {
+    let config_path = std::fs::canonicalize(&spec.config_path)
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|_| spec.config_path.clone());
+
+    let mut out = String::new();
+    let _ = writeln!(out, "# Generated by `tvs_node scaffold --target systemd`");
+    for (port, description) in &spec.ports {
+        let _ = writeln!(out, "# Port {} - {}", port, description);
+    }
+    let _ = writeln!(out, "[Unit]");
+    let _ = writeln!(out, "Description=TVS node {}", spec.node_name);
+    let _ = writeln!(out, "After=network-online.target");
+    let _ = writeln!(out, "Wants=network-online.target");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "[Service]");
+    let _ = writeln!(out, "Type=simple");
+    let _ = writeln!(
+        out,
+        "ExecStart=/usr/local/bin/tvs_node --config {}",
+        config_path
+    );
+    for (key, value) in &spec.env {
+        let _ = writeln!(out, "Environment={}={}", key, value);
+    }
+    let _ = writeln!(
+        out,
+        "# Secrets (e.g. POSTGRES_DATABASE_URL, TVS_ROOT_URL) belong in this file, not the unit"
+    );
+    let _ = writeln!(out, "EnvironmentFile=-/etc/tvs_node/{}.env", spec.node_name);
+    let _ = writeln!(out, "Restart=on-failure");
+    let _ = writeln!(out, "RestartSec=5");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "[Install]");
+    let _ = writeln!(out, "WantedBy=multi-user.target");
+    out
+}
+
+fn render_compose(spec: &DeploymentSpec) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# Generated by `tvs_node scaffold --target compose`");
+    let _ = writeln!(out, "services:");
+    let _ = writeln!(out, "  {}:", spec.node_name);
+    let _ = writeln!(out, "    image: tvs_node:latest");
+    let _ = writeln!(out, "    container_name: {}", spec.node_name);
+    let _ = writeln!(out, "    hostname: {}", spec.node_name);
+    let _ = writeln!(out, "    ports:");
+    for (port, description) in &spec.ports {
+        let _ = writeln!(out, "      - \"{port}:{port}\"  # {}", description);
+    }
+    let _ = writeln!(out, "    environment:");
+    for (key, value) in &spec.env {
+        // Inside a container the vote server must listen on all interfaces to be published
+        let value = if *key == "TVS_VOTE_HOST" {
+            "0.0.0.0"
+        } else {
+            value.as_str()
+        };
+        let _ = writeln!(out, "      {}: \"{}\"", key, value);
+    }
+    if cfg!(feature = "postgres") {
+        let _ = writeln!(
+            out,
+            "      POSTGRES_DATABASE_URL: \"${{POSTGRES_DATABASE_URL}}\""
+        );
+    }
+    let _ = writeln!(out, "    volumes:");
+    let _ = writeln!(out, "      - {}:/app/config.json:ro", spec.config_path);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config(tvs: &str) -> TvsNodeConfig {
+        serde_json::from_str(&format!(
+            r#"{{
+                "server": {{
+                    "cluster_message_port": 10080,
+                    "app_port": 10081,
+                    "admin_port": 10082
+                }},
+                "node_name": "tvs_node_2"
+                {}
+            }}"#,
+            tvs
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_systemd_scaffold() {
+        let config = test_config(r#", "tvs": { "vote_port": 10090 }"#);
+        let unit = render(&config, "deploy/node2.json", ScaffoldTarget::Systemd);
+
+        assert!(unit.contains("Description=TVS node tvs_node_2"));
+        assert!(unit.contains("ExecStart=/usr/local/bin/tvs_node --config deploy/node2.json"));
+        assert!(unit.contains("Environment=APP_PORT=10081"));
+        assert!(unit.contains("Environment=TVS_VOTE_PORT=10090"));
+        assert!(unit.contains("EnvironmentFile=-/etc/tvs_node/tvs_node_2.env"));
+    }
+
+    #[test]
+    fn test_compose_scaffold() {
+        let config = test_config("");
+        let service = render(&config, "./configs/node2.json", ScaffoldTarget::Compose);
+
+        assert!(service.contains("  tvs_node_2:"));
+        assert!(service.contains("- \"10080:10080\"  # Cluster messaging"));
+        // Vote server defaults apply when the tvs section is absent
+        assert!(service.contains("- \"8090:8090\"  # Vote service"));
+        assert!(service.contains("TVS_VOTE_HOST: \"0.0.0.0\""));
+        assert!(service.contains("- ./configs/node2.json:/app/config.json:ro"));
+    }
+
+    #[test]
+    fn test_scaffold_without_vote_server() {
+        let config = test_config(r#", "tvs": { "enabled": false }"#);
+        let service = render(&config, "config.json", ScaffoldTarget::Compose);
+
+        assert!(!service.contains("Vote service"));
+        assert!(!service.contains("TVS_VOTE_PORT"));
+    }
+}
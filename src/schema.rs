@@ -0,0 +1,115 @@
+use schemars::{schema::RootSchema, schema_for, JsonSchema};
+
+use crate::config::TvsNodeConfig;
+
+/// Schema stand-in for the flattened upstream `AppConfig`, which doesn't derive `JsonSchema`
+/// Only describes the keys this node reads; upstream may accept more, so none are refused
+#[derive(JsonSchema)]
+#[schemars(rename = "AppConfig")]
+#[allow(dead_code)]
+pub struct AppConfigSchema {
+    /// TFS server ports
+    server: ServerConfigSchema,
+
+    /// Node name, also used to name the Postgres schema. `NODE_NAME` overrides it
+    #[serde(default)]
+    node_name: Option<String>,
+
+    /// Node UUID; generated when unset. `NODE_UUID` overrides it
+    #[serde(default)]
+    node_uuid: Option<uuid::Uuid>,
+
+    /// Log verbosity: trace, debug, info, warn or error. `LOG_LEVEL` overrides it
+    #[serde(default)]
+    log_level: Option<String>,
+
+    /// Directory for log files. `LOG_DIR` overrides it
+    #[serde(default)]
+    log_dir: Option<String>,
+}
+
+/// Schema stand-in for the upstream `ServerConfig`
+#[derive(JsonSchema)]
+#[schemars(rename = "ServerConfig")]
+#[allow(dead_code)]
+struct ServerConfigSchema {
+    /// Port for cluster messages between nodes. `CLUSTER_MESSAGE_PORT` overrides it
+    cluster_message_port: u16,
+
+    /// Port for the TFS app server. `APP_PORT` overrides it
+    app_port: u16,
+
+    /// Port for the admin server. `ADMIN_PORT` overrides it
+    admin_port: u16,
+}
+
+/// JSON Schema for the config file, for editors and config linters
+pub fn schema() -> RootSchema {
+    schema_for!(TvsNodeConfig)
+}
+
+/// Print the schema to stdout as pretty JSON
+pub fn print() -> Result<(), serde_json::Error> {
+    println!("{}", serde_json::to_string_pretty(&schema())?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn rendered() -> Value {
+        let rendered = serde_json::to_string_pretty(&schema()).unwrap();
+        serde_json::from_str(&rendered).unwrap()
+    }
+
+    /// The definition a `$ref` property points at
+    fn definition<'a>(schema: &'a Value, property: &Value) -> &'a Value {
+        let reference = ["anyOf", "allOf"]
+            .iter()
+            .find_map(|key| property[key][0]["$ref"].as_str())
+            .unwrap();
+        let name = reference.trim_start_matches("#/definitions/");
+        &schema["definitions"][name]
+    }
+
+    #[test]
+    fn test_schema_describes_vote_port_default() {
+        let schema = rendered();
+        let tvs = definition(&schema, &schema["properties"]["tvs"]);
+
+        let vote_port = &tvs["properties"]["vote_port"];
+        assert_eq!(vote_port["type"], "integer");
+        assert_eq!(vote_port["default"], 8090);
+        assert!(vote_port["description"]
+            .as_str()
+            .unwrap()
+            .contains("vote server"));
+    }
+
+    #[test]
+    fn test_schema_includes_sections_and_required_fields() {
+        let schema = rendered();
+        let properties = &schema["properties"];
+
+        for section in ["tvs", "persistence", "runtime", "server"] {
+            assert!(properties.get(section).is_some(), "missing {}", section);
+        }
+        assert_eq!(schema["required"], serde_json::json!(["server"]));
+
+        let persistence = definition(&schema, &properties["persistence"]);
+        assert_eq!(
+            persistence["properties"]["db_connect_retries"]["default"],
+            5
+        );
+        let log_format = definition(&schema, &properties["log_format"]);
+        let variants: Vec<&Value> = log_format["oneOf"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|variant| &variant["enum"][0])
+            .collect();
+        assert_eq!(variants, ["text", "json"]);
+    }
+}
@@ -1,202 +1,1210 @@
-
+use std::{
+    net::SocketAddr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use tfs_http::tfs_web_server_builder::{TfsWebServerBuilder, TfsWebServerRunner};
-use tvs::{
-    services::tfs_services_adapter::ActualTfsAppInterfaceAdapter,
-    webserver::{TvsWebServer, TvsWebServerRunner, TvsConfig},
-};
+use tokio::sync::watch;
+#[cfg(all(feature = "ephemeral", not(feature = "postgres")))]
+use tvs::services::tfs_services_adapter::ActualTfsAppInterfaceAdapter;
 
-use crate::config::TvsNodeConfig;
+use crate::{
+    config::{ConfigError, ConfigSource, LogFormat, PersistenceConfig, TvsNodeConfig},
+    connection_limit::AcceptPolicy,
+    drain::DrainSwitch,
+    error::NodeError,
+    ready_notify::{ReadyNotifier, ReadyRecord},
+    reload::ConfigReloader,
+    startup_timing::StartupTimer,
+    stats::RuntimeStats,
+    vote_server::{BindOptions, ListenAddr, VoteBackend, VoteServerRunner},
+};
 
 #[cfg(feature = "postgres")]
-use tvs_postgres::{PostgresVoteService, PostgresVoteUrlService, initialize_tvs_tables};
-#[cfg(feature = "postgres")]
-use tfs_postgres::{establish_connection_pool, DbSession, SchemaContext, DbPool};
+use tvs_postgres::{PostgresVoteService, PostgresVoteUrlService};
 
 pub struct TvsNodeRunner {
     tfs_web_server_runner: TfsWebServerRunner,
-    tvs_web_server_runner: Option<TvsWebServerRunner>
+    tvs_web_server_runners: Vec<VoteServerRunner>,
+    max_uptime: Option<Duration>,
+    shutdown_timeout: Duration,
+    /// Set to true to request shutdown; shared with every `TvsNodeHandle`
+    shutdown_tx: Arc<watch::Sender<bool>>,
+    /// Dropped when `run_until_shutdown` returns, which tells handles the node has stopped
+    running_tx: watch::Sender<bool>,
+    /// The config the node is running with, updated by hot reloads
+    config: TvsNodeConfig,
+    /// Source to re-read the config from on SIGHUP; none when it didn't come from a file
+    reloader: Option<ConfigReloader>,
+    /// Refuses new votes while set; flipped by SIGUSR1 and `TvsNodeHandle::set_draining`
+    drain: DrainSwitch,
+    /// `runtime.ready_notify` target and what to tell it once `run_until_shutdown` starts
+    ready_notify: Option<(ReadyNotifier, ReadyRecord)>,
+    /// Dropped when `run_until_shutdown` returns, which writes the final checkpoint
+    #[cfg(feature = "metrics")]
+    metrics_checkpoint: Option<crate::metrics_checkpoint::MetricsCheckpoint>,
+}
+
+/// What `start_tvs_vote_server` started: a runner per listener, and the metrics checkpoint
+/// when one is kept
+#[derive(Default)]
+struct VoteServers {
+    runners: Vec<VoteServerRunner>,
+    #[cfg(feature = "metrics")]
+    checkpoint: Option<crate::metrics_checkpoint::MetricsCheckpoint>,
+}
+
+/// Handle for stopping a node from outside `run_until_shutdown`, e.g. from an embedding
+/// supervisor; cheap to clone. The binary itself only uses signals
+#[derive(Clone)]
+pub struct TvsNodeHandle {
+    shutdown_tx: Arc<watch::Sender<bool>>,
+    running_rx: watch::Receiver<bool>,
+    drain: DrainSwitch,
+}
+
+impl TvsNodeHandle {
+    /// Ask the node to shut down and wait until it has drained and stopped
+    pub async fn shutdown(&self) {
+        self.shutdown_tx.send_replace(true);
+        let mut running_rx = self.running_rx.clone();
+        // An error means the runner is gone, which is just as stopped
+        let _ = running_rx.wait_for(|running| !running).await;
+    }
+
+    /// Whether the node is still up: built, and neither shut down nor dropped
+    pub fn is_running(&self) -> bool {
+        *self.running_rx.borrow() && self.running_rx.has_changed().is_ok()
+    }
+
+    /// Start or stop refusing new votes with 503 for maintenance; reads keep being served
+    /// Returns whether the node was draining before
+    pub fn set_draining(&self, draining: bool) -> bool {
+        let was_draining = self.drain.set(draining);
+        if draining != was_draining {
+            log_drain(draining, "TvsNodeHandle");
+        }
+        was_draining
+    }
+
+    pub fn is_draining(&self) -> bool {
+        self.drain.is_draining()
+    }
 }
 
 impl TvsNodeRunner {
-    pub async fn build_with_config(
+    pub async fn build_with_config(config: TvsNodeConfig) -> Result<TvsNodeRunner, NodeError> {
+        Self::build(config, None, None).await
+    }
+
+    /// `build_with_config` for a config loaded from `source`, which `/debug/stats` reports
+    /// as it was at startup
+    pub async fn build_with_config_source(
         config: TvsNodeConfig,
-    ) -> Result<TvsNodeRunner, Box<dyn std::error::Error>> {
-        // Configure admin frontend based on feature flag
-        let mut tfs_config = config.tfs.clone();
-        Self::configure_admin_frontend(&mut tfs_config)?;
+        source: ConfigSource,
+    ) -> Result<TvsNodeRunner, NodeError> {
+        Self::build(config, Some(source), None).await
+    }
+
+    /// `build_with_config`, registering the vote services through `registration` instead
+    /// of the backend's own when given; tests use it to make a registration fail
+    async fn build(
+        config: TvsNodeConfig,
+        source: Option<ConfigSource>,
+        registration: Option<Box<dyn VoteRegistration + Send>>,
+    ) -> Result<TvsNodeRunner, NodeError> {
+        let mut timer = StartupTimer::start();
+
+        // Embedders may call this without going through main's validation; warnings were
+        // already reported there, so only errors are checked here
+        let report = config.validation_report();
+        if !report.errors.is_empty() {
+            return Err(ConfigError::Validation(report.errors).into());
+        }
+        let running_config = config.clone();
+
+        let max_uptime = config.max_uptime_secs().map(Duration::from_secs);
+        let shutdown_timeout = Duration::from_secs(config.shutdown_timeout_secs());
 
+        let tfs_config = config.tfs.clone();
+        let node_name = tfs_config.node_name.clone().unwrap_or_else(|| "tvs_node".to_string());
+        let log_level = tfs_config.log_level.clone();
         let mut tfs_web_server_builder = TfsWebServerBuilder::new(tfs_config);
 
+        tfs_web_server_builder.setup_node();
+        match config.log_format {
+            LogFormat::Text => {
+                tfs_web_server_builder.init_tracing();
+            }
+            LogFormat::Json => crate::logging::init_json_tracing(&node_name, log_level.as_deref()),
+        }
+
+        // Everything from here on goes through the subscriber installed above
+        let features = crate::info::enabled_features();
+        let features = if features.is_empty() { "none".to_string() } else { features.join(", ") };
+        tracing::info!(features = %features, "Built with features: {}", features);
+        match &config.tfs.node_name {
+            Some(name) => tracing::info!(node_name = %name, "✓ Node name: {}", name),
+            None => tracing::warn!(
+                node_name = %node_name,
+                "⚠ node_name not set - using {}; set node_name or NODE_NAME for a stable node id",
+                node_name
+            ),
+        }
+
+        // Configure admin frontend based on feature flag and config
+        Self::configure_admin_frontend(&config.tfs, config.admin_frontend_active());
         tfs_web_server_builder
-            .setup_node()
-            .init_tracing()
             .setup_app_interface()
             .setup_app_shell();
 
         // Start TFS web server
         let tfs_web_server_runner = tfs_web_server_builder
             .start_webserver()
-            .await?;
+            .await
+            .map_err(NodeError::TfsStartup)?;
+        timer.mark("TFS server start");
+
+        // The runner returns before the node answers app-interface calls; early vote
+        // requests would 500, so hold the vote port back until it does
+        let app_interface = tfs_web_server_runner.webserver().shell.app.clone();
+        Self::wait_for_tfs_ready(
+            &app_interface,
+            config.tfs.server.app_port,
+            Duration::from_secs(config.tfs_ready_timeout_secs()),
+        )
+        .await?;
+        timer.mark("TFS readiness");
 
-        let node_service = tfs_web_server_runner.webserver().shell.app.get_this_node_id();
+        let node_service = app_interface.get_this_node_id();
 
         // Configure TVS services after server is running
-        let app_interface = tfs_web_server_runner.webserver().shell.app.clone();
-        Self::configure_tvs_services(&node_service, app_interface.clone())?;
+        let backend = Self::configure_tvs_services(
+            &node_service,
+            app_interface.clone(),
+            config.vote_root_url(),
+            &config.persistence_config(),
+            config.vote_service_required,
+            registration,
+            &mut timer,
+        )
+        .await?;
+        timer.mark("service configuration");
+
+        // Optionally start TVS vote server on separate port; not without vote services
+        let drain = DrainSwitch::default();
+        let vote_servers = match backend {
+            Some(backend) => {
+                Self::start_tvs_vote_server(
+                    &node_service,
+                    app_interface,
+                    backend,
+                    &config,
+                    source.as_ref(),
+                    drain.clone(),
+                    timer.started(),
+                )
+                .await?
+            }
+            None => VoteServers::default(),
+        };
+        let tvs_runners = vote_servers.runners;
+        timer.mark("vote server start");
+        timer.log(config.verbose_startup);
+        let tvs = config.vote_server_config();
+        if tvs.bind_address.is_some() && tvs.listeners.is_empty() && !tvs_runners.is_empty() {
+            tracing::info!(vote_host = %tvs.vote_host, "  Advertised vote host: {}", tvs.vote_host);
+        }
 
-        // Optionally start TVS vote server on separate port
-        let tvs_runner = Self::start_tvs_vote_server(&node_service, app_interface, config.tvs).await?;
+        let vote_addrs: Vec<ListenAddr> =
+            tvs_runners.iter().map(|runner| runner.local_addr().clone()).collect();
+        print_startup_summary(&running_config, &node_service, &vote_addrs);
+
+        let ready_notify = config.runtime_config().ready_notify.map(|target| {
+            let server = &config.tfs.server;
+            let record = ReadyRecord {
+                node_id: node_service.to_string(),
+                pid: std::process::id(),
+                cluster_message_port: server.cluster_message_port,
+                app_port: server.app_port,
+                admin_port: config.admin_frontend_active().then_some(server.admin_port),
+                vote_addrs: vote_addrs.iter().map(ToString::to_string).collect(),
+                ready_at: String::new(),
+            };
+            (ReadyNotifier::parse(&target), record)
+        });
 
         Ok(Self {
             tfs_web_server_runner,
-            tvs_web_server_runner: tvs_runner
+            tvs_web_server_runners: tvs_runners,
+            max_uptime,
+            shutdown_timeout,
+            shutdown_tx: Arc::new(watch::channel(false).0),
+            running_tx: watch::channel(true).0,
+            config: running_config,
+            reloader: None,
+            drain,
+            ready_notify,
+            #[cfg(feature = "metrics")]
+            metrics_checkpoint: vote_servers.checkpoint,
         })
     }
 
+    /// Re-read the config through `reloader` on SIGHUP, applying the hot-reloadable settings
+    pub fn with_config_reload(mut self, reloader: ConfigReloader) -> Self {
+        self.reloader = Some(reloader);
+        self
+    }
+
+    /// Handle that can stop this node once `run_until_shutdown` is running
+    /// A shutdown requested earlier takes effect as soon as it starts
+    pub fn handle(&self) -> TvsNodeHandle {
+        TvsNodeHandle {
+            shutdown_tx: self.shutdown_tx.clone(),
+            running_rx: self.running_tx.subscribe(),
+            drain: self.drain.clone(),
+        }
+    }
+
+    /// Address the first vote listener is bound to, with the actual port when `vote_port`
+    /// is 0; None when the vote server isn't running or listens on a Unix socket
+    pub fn vote_server_addr(&self) -> Option<SocketAddr> {
+        self.tvs_web_server_runners.first()?.local_addr().tcp()
+    }
+
+    /// Poll the TFS node until it reports its id and its HTTP API accepts connections
+    async fn wait_for_tfs_ready(
+        app_interface: &tfs::tfs_app_interface::TFSAppInterface,
+        app_port: u16,
+        timeout: Duration,
+    ) -> Result<(), NodeError> {
+        let attempts = crate::readiness::wait_until_ready(
+            timeout,
+            crate::readiness::POLL_INTERVAL,
+            || crate::readiness::probe_tfs(app_interface, app_port),
+        )
+        .await
+        .map_err(|e| {
+            NodeError::TfsStartup(
+                format!(
+                    "TFS node not ready after {}s (tvs.tfs_ready_timeout_secs): {}",
+                    timeout.as_secs(),
+                    e
+                )
+                .into(),
+            )
+        })?;
+
+        tracing::info!(attempts, app_port, "✓ TFS node ready after {} check(s)", attempts);
+        Ok(())
+    }
+
     /// Configure TVS services (VoteService and VoteUrlService) based on enabled features
-    fn configure_tvs_services(
+    /// Connecting to the database and migrating is timed as a phase of its own. None when
+    /// registering the services failed and `vote_service_required` is off
+    #[cfg_attr(not(feature = "postgres"), allow(unused_variables))]
+    #[cfg_attr(
+        not(any(feature = "ephemeral", feature = "postgres")),
+        allow(unused_mut)
+    )]
+    async fn configure_tvs_services(
         node_id: &tfs::tfs::node_id::NodeId,
         app_interface: tfs::tfs_app_interface::TFSAppInterface,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+        root_url: String,
+        persistence: &PersistenceConfig,
+        vote_service_required: bool,
+        mut registration: Option<Box<dyn VoteRegistration + Send>>,
+        timer: &mut StartupTimer,
+    ) -> Result<Option<VoteBackend>, NodeError> {
+        let selected = select_backend(cfg!(feature = "postgres"), cfg!(feature = "ephemeral"))?;
+        tracing::info!(node_id = %node_id, backend = selected, "✓ Persistence backend: {}", selected);
+
         #[cfg(feature = "postgres")]
-        {
-            // Establish shared connection pool for both TFS and TVS
-            let db_pool = establish_connection_pool();
-            let schema_ctx = SchemaContext::from_node_id(node_id, false);
-            let session = DbSession::new(db_pool.clone(), schema_ctx);
-
-            // Initialize schema and run migrations
-            session.initialize_schema()?;
-            initialize_tvs_tables(&session)?;
-
-            // Configure PostgreSQL-backed vote service
-            let vote_service = PostgresVoteService::new(session.clone());
-            tvs::services::vote_service::configure_vote_service(
-                node_id,
-                Box::new(vote_service),
-            )?;
-
-            // Configure PostgreSQL-backed vote URL service
-            let root_url = std::env::var("TVS_ROOT_URL")
-                .unwrap_or_else(|_| "http://localhost:8081/vote".to_string());
-            let vote_url_service = PostgresVoteUrlService::with_root_url(session, root_url);
-            tvs::services::vote_url_service::configure_vote_url_service(
+        let backend = {
+            // Establish shared connection pool for both TFS and TVS, then initialize schema
+            // and run migrations (one node at a time per schema). Retried as a unit so a
+            // database that is still starting doesn't crash the node.
+            let (db_pool, session) =
+                crate::persistence::with_connect_retries_async(persistence, || {
+                    let db_pool = crate::persistence::establish_pool(persistence)?;
+                    let session = crate::persistence::run_migrations(
+                        &db_pool,
+                        node_id,
+                        persistence.skip_migrations,
+                    )?;
+                    Ok((db_pool, session))
+                })
+                .await?;
+            timer.mark("database migrations");
+            // The PostgreSQL services keep votes in the database, not through TFS
+            let _ = app_interface;
+
+            let mut postgres = PostgresRegistration {
                 node_id,
-                Box::new(vote_url_service),
-            )?;
+                session,
+                root_url,
+            };
+            let registration: &mut dyn VoteRegistration = match registration.as_deref_mut() {
+                Some(registration) => registration,
+                None => &mut postgres,
+            };
 
-            println!("✓ Configured PostgreSQL persistence for node: {}", node_id);
-        }
+            register_vote_services(registration, vote_service_required)?.then(|| {
+                tracing::info!(
+                    node_id = %node_id,
+                    backend = "postgres",
+                    "✓ Configured PostgreSQL persistence for node: {}",
+                    node_id
+                );
+                VoteBackend::Postgres(db_pool)
+            })
+        };
 
         #[cfg(all(feature = "ephemeral", not(feature = "postgres")))]
-        {
-            // Create TFS adapter for ephemeral vote service
-            let tfs_adapter = ActualTfsAppInterfaceAdapter::as_tfs_app_interface_adapter(
+        let backend = {
+            let mut ephemeral = EphemeralRegistration {
                 node_id,
                 app_interface,
-            );
+                root_url,
+            };
+            let registration: &mut dyn VoteRegistration = match registration.as_deref_mut() {
+                Some(registration) => registration,
+                None => &mut ephemeral,
+            };
 
-            // Configure ephemeral (in-memory) vote service
-            tvs::services::vote_service::configure_ephemeral_vote_service(node_id, tfs_adapter)?;
+            register_vote_services(registration, vote_service_required)?.then(|| {
+                tracing::info!(
+                    node_id = %node_id,
+                    backend = "ephemeral",
+                    "✓ Configured ephemeral (in-memory) persistence for node: {}",
+                    node_id
+                );
+                VoteBackend::Ephemeral
+            })
+        };
 
-            // Configure ephemeral (in-memory) vote URL service
-            let root_url = std::env::var("TVS_ROOT_URL")
-                .unwrap_or_else(|_| "http://localhost:8081/vote".to_string());
-            tvs::services::vote_url_service::configure_ephemeral_vote_url_service(
-                node_id,
-                root_url,
-            )?;
+        // Not reached: select_backend has already failed for a build without a backend
+        #[cfg(not(any(feature = "ephemeral", feature = "postgres")))]
+        let backend = Some(VoteBackend::Ephemeral);
 
-            println!("✓ Configured ephemeral (in-memory) persistence for node: {}", node_id);
-        }
+        Ok(backend)
+    }
 
-        Ok(())
+    /// Configure admin frontend availability based on feature flag and `admin_frontend_enabled`
+    fn configure_admin_frontend(
+        config: &tfs_http::app_config::AppConfig,
+        enabled: bool,
+    ) {
+        let admin_port = config.server.admin_port;
+        if enabled {
+            tracing::info!(admin_port, "✓ Admin frontend enabled");
+            tracing::info!(admin_port, "  Admin UI: http://localhost:{}/static/private", admin_port);
+            tracing::info!(admin_port, "  Admin API: http://localhost:{}/tfs/admin", admin_port);
+        } else {
+            let reason = if cfg!(feature = "admin-frontend") {
+                "admin_frontend_enabled = false"
+            } else {
+                "not compiled in"
+            };
+            tracing::warn!(reason, "⚠ Admin frontend disabled ({}, no separate admin port)", reason);
+            tracing::info!("  Admin routes consolidated with cluster port");
+        }
     }
 
-    /// Configure admin frontend availability based on feature flag
-    fn configure_admin_frontend(_config: &mut tfs_http::app_config::AppConfig) -> Result<(), Box<dyn std::error::Error>> {
-        #[cfg(feature = "admin-frontend")]
-        {
-            println!("✓ Admin frontend enabled");
-            println!("  Admin UI: http://localhost:{}/static/private", _config.server.admin_port);
-            println!("  Admin API: http://localhost:{}/tfs/admin", _config.server.admin_port);
+    /// Start one TVS vote server per configured listener if vote service is configured
+    /// The `tvs` section supplies the TLS, CORS, rate limit and body size settings shared by
+    /// every listener, and `persistence` the postgres backend retry and circuit breaker settings
+    /// `/debug/stats` reports uptime from `started` and the config's `source` when known
+    async fn start_tvs_vote_server(
+        node_id: &tfs::tfs::node_id::NodeId,
+        app_interface: tfs::tfs_app_interface::TFSAppInterface,
+        backend: VoteBackend,
+        config: &TvsNodeConfig,
+        source: Option<&ConfigSource>,
+        drain: DrainSwitch,
+        started: Instant,
+    ) -> Result<VoteServers, NodeError> {
+        let listeners = config.vote_listeners();
+        let tvs = config.vote_server_config();
+        if listeners.is_empty() {
+            tracing::warn!(node_id = %node_id, "⚠ TVS vote server disabled in configuration");
+            return Ok(VoteServers::default());
+        }
+
+        // One policy for every listener, so a client's connections count against one limit
+        // and `/debug/stats` sees them all
+        let accept = AcceptPolicy::from_config(&tvs);
+        let mut info = crate::info::render(config, source);
+        // The config only pins the id when it sets both node_name and node_uuid
+        info["node_id"] = serde_json::json!(node_id.to_string());
+        let stats = RuntimeStats {
+            started,
+            info,
+            connections: accept.connections.clone(),
+            drain,
+            #[cfg(feature = "metrics")]
+            metrics: Some(crate::metrics_checkpoint::vote_metrics(&node_id.to_string(), &tvs)?),
+        };
+        #[cfg(feature = "metrics")]
+        let metrics = stats.metrics.clone();
+
+        // Only serve vote routes if a vote service is configured for this node
+        let vote_router = VoteServerRunner::vote_router(node_id, app_interface, backend, config, stats)?;
+        let Some(router) = vote_router else {
+            tracing::warn!(node_id = %node_id, "⚠ No vote service configured - TVS vote server disabled");
+            tracing::warn!(node_id = %node_id, "  Vote routes will not be available");
+            return Ok(VoteServers::default());
+        };
+
+        if let Some(rate_limit) = &tvs.rate_limit {
+            tracing::info!(
+                requests_per_second = rate_limit.requests_per_second,
+                burst = rate_limit.burst,
+                status = rate_limit.status,
+                "✓ Rate limiting vote routes to {} request(s)/s per client IP (burst {}, then {})",
+                rate_limit.requests_per_second,
+                rate_limit.burst,
+                rate_limit.status
+            );
+        }
+        if let Some(max_connections) = tvs.max_connections {
+            tracing::info!(
+                max_connections,
+                status = tvs.max_connections_status,
+                "✓ Limiting the vote routes to {} concurrent request(s) (then {})",
+                max_connections,
+                tvs.max_connections_status
+            );
+        }
+        if let Some(max_body_bytes) = tvs.max_body_bytes {
+            tracing::info!(
+                max_body_bytes,
+                "✓ Limiting vote request bodies to {} bytes",
+                max_body_bytes
+            );
+        }
+        if !tvs.cors_allowed_origins.is_empty() {
+            let origins = tvs.cors_allowed_origins.join(", ");
+            tracing::info!(origins = %origins, "✓ CORS enabled for origins: {}", origins);
         }
+        let router = crate::cors::apply(router, tvs.cors_allowed_origins.clone());
+        let node_header = node_id.to_string();
+        let router = crate::response_headers::apply(
+            router,
+            tvs.expose_node_header.then_some(node_header.as_str()),
+        );
+        // Outermost, so logged statuses include CORS, rate limit and body size rejections
+        let router = if tvs.access_log {
+            crate::access_log::apply(router, tvs.access_log_probes)
+        } else {
+            router
+        };
+
+        // Load the certificate before binding anything, so a bad path fails startup cleanly
+        let tls = tvs
+            .tls
+            .as_ref()
+            .map(crate::tls::load_server_config)
+            .transpose()?;
 
-        #[cfg(not(feature = "admin-frontend"))]
-        {
-            println!("⚠ Admin frontend disabled (no separate admin port)");
-            println!("  Admin routes consolidated with cluster port");
+        if let Some(keepalive) = accept.socket_options.keepalive {
+            tracing::info!(
+                tcp_keepalive_secs = keepalive.as_secs(),
+                "✓ TCP keepalive on vote connections after {}s idle",
+                keepalive.as_secs()
+            );
+        }
+        if let Some(max_connections_per_ip) = tvs.max_connections_per_ip {
+            tracing::info!(
+                max_connections_per_ip,
+                trusted_proxies = tvs.trusted_proxies.len(),
+                "✓ Limiting vote connections to {} per client IP",
+                max_connections_per_ip
+            );
         }
 
-        Ok(())
+        // A listener that fails to bind drops the ones already started, which stops them
+        let mut runners = Vec::with_capacity(listeners.len());
+        for listener in listeners {
+            let tvs_runner = VoteServerRunner::serve_on_free_port(
+                router.clone(),
+                &listener.host,
+                listener.port,
+                tls.clone(),
+                tvs.auto_port,
+                BindOptions::from_config(&tvs),
+                accept.clone(),
+            )
+            .await?;
+            let port = tvs_runner.local_addr().port();
+            if let Some(port) = port.filter(|port| listener.port != 0 && *port != listener.port) {
+                tracing::warn!(
+                    configured_port = listener.port,
+                    port,
+                    "⚠ Vote port {} is in use - serving on {} instead (--auto-port)",
+                    listener.port,
+                    port
+                );
+            }
+            let scheme = if tls.is_some() { "https" } else { "http" };
+            tracing::info!(
+                node_id = %node_id,
+                scheme,
+                addr = %tvs_runner.local_addr(),
+                port,
+                "✓ Started TVS vote server on {}://{}",
+                scheme,
+                tvs_runner.local_addr()
+            );
+            runners.push(tvs_runner);
+        }
+        Ok(VoteServers {
+            runners,
+            // Started once every listener is up, so a failed start leaves the checkpoint alone
+            #[cfg(feature = "metrics")]
+            checkpoint: metrics.and_then(|metrics| {
+                crate::metrics_checkpoint::MetricsCheckpoint::start(&metrics, &tvs)
+            }),
+        })
     }
 
-    /// Start TVS vote server if vote service is configured
-    async fn start_tvs_vote_server(
-        node_id: &tfs::tfs::node_id::NodeId,
-        app_interface: tfs::tfs_app_interface::TFSAppInterface,
-        tvs_server_config: Option<crate::config::TvsServerConfig>,
-    ) -> Result<Option<TvsWebServerRunner>, Box<dyn std::error::Error>> {
-        // Check if vote service is configured for this node
-        if let Some(vote_service) = tvs::services::vote_service::get_vote_service(node_id) {
-            // Get TVS config from config file, with environment variable overrides
-            let tvs_config = if let Some(config) = tvs_server_config {
-                if !config.enabled {
-                    println!("⚠ TVS vote server disabled in configuration");
-                    return Ok(None);
-                }
+    /// Run until a server exits, SIGTERM/SIGINT arrives, a `TvsNodeHandle` requests shutdown
+    /// or `runtime.max_uptime_secs` (alias `tvs.max_lifetime_secs`) elapses, then drain the
+    /// vote servers before returning
+    /// SIGHUP meanwhile reloads the config (see `with_config_reload`)
+    pub async fn run_until_shutdown(self) -> Result<(), NodeError> {
+        let Self {
+            tfs_web_server_runner,
+            tvs_web_server_runners: mut tvs_runners,
+            max_uptime,
+            shutdown_timeout,
+            shutdown_tx,
+            running_tx: _running_tx,
+            mut config,
+            reloader,
+            drain,
+            ready_notify,
+            #[cfg(feature = "metrics")]
+            metrics_checkpoint: _metrics_checkpoint,
+        } = self;
+        let mut shutdown_rx = shutdown_tx.subscribe();
+
+        // Both servers are serving by now; the announcement is withdrawn however this returns
+        let _announcement = ready_notify.map(|(notifier, record)| notifier.announce(&record));
+
+        if !tvs_runners.is_empty() {
+            tracing::info!(
+                vote_servers = tvs_runners.len(),
+                "Running TFS and {} TVS vote server(s) until shutdown...",
+                tvs_runners.len()
+            );
+        }
 
-                TvsConfig {
-                    vote_service_port: std::env::var("TVS_VOTE_PORT")
-                        .ok()
-                        .and_then(|s| s.parse().ok())
-                        .unwrap_or(config.vote_port),
-                    vote_service_host: std::env::var("TVS_VOTE_HOST")
-                        .unwrap_or(config.vote_host),
+        // Recycle the process once the configured uptime is exceeded
+        let uptime_limit = uptime_limit(max_uptime);
+
+        // The TFS runner has no shutdown handle, so it stops when its future is dropped here
+        let tfs_run = tfs_web_server_runner.run_until_shutdown();
+        let shutdown_signal = crate::runtime::wait_for_shutdown_signal();
+        tokio::pin!(tfs_run, shutdown_signal, uptime_limit);
+        let mut reload_signal = crate::runtime::ReloadSignal::new();
+        let mut drain_signal = crate::runtime::DrainSignal::new();
+
+        // SIGHUP and SIGUSR1 act and keep going; every other branch ends the run
+        let result = loop {
+            tokio::select! {
+                result = &mut tfs_run => break result.map_err(NodeError::Runtime),
+                (index, result) = crate::vote_server::wait_any(&mut tvs_runners) => {
+                    // That vote server already exited; only the others are left to drain
+                    let exited = tvs_runners.remove(index);
+                    tracing::warn!(addr = %exited.local_addr(), "⚠ TVS vote server on {} exited", exited.local_addr());
+                    break result.map_err(NodeError::Runtime);
                 }
-            } else {
-                // No config section - use environment or defaults
-                TvsConfig {
-                    vote_service_port: std::env::var("TVS_VOTE_PORT")
-                        .ok()
-                        .and_then(|s| s.parse().ok())
-                        .unwrap_or(8090),
-                    vote_service_host: std::env::var("TVS_VOTE_HOST")
-                        .unwrap_or_else(|_| "127.0.0.1".to_string()),
+                signal = &mut shutdown_signal => {
+                    tracing::warn!(signal = %signal, "⚠ Received {} - shutting down", signal);
+                    break Ok(());
                 }
-            };
+                _ = shutdown_rx.wait_for(|requested| *requested) => {
+                    tracing::warn!("⚠ Shutdown requested through TvsNodeHandle");
+                    break Ok(());
+                }
+                _ = &mut uptime_limit => {
+                    let max_uptime_secs = max_uptime.map_or(0, |d| d.as_secs());
+                    tracing::warn!(
+                        max_uptime_secs,
+                        "⚠ Max lifetime reached after {}s (runtime.max_uptime_secs) - shutting down for restart",
+                        max_uptime_secs
+                    );
+                    break Ok(());
+                }
+                _ = reload_signal.recv() => crate::reload::reload(reloader.as_ref(), &mut config),
+                _ = drain_signal.recv() => log_drain(drain.toggle(), "SIGUSR1"),
+            }
+        };
 
-            println!("✓ Starting TVS vote server on {}:{}",
-                tvs_config.vote_service_host, tvs_config.vote_service_port);
+        // Let in-flight vote requests finish before the process exits, within the grace period
+        if !tvs_runners.is_empty() {
+            tracing::info!(
+                vote_servers = tvs_runners.len(),
+                shutdown_timeout_secs = shutdown_timeout.as_secs(),
+                "Draining {} TVS vote server(s) (up to {}s)...",
+                tvs_runners.len(),
+                shutdown_timeout.as_secs()
+            );
+            let drain = crate::vote_server::shutdown_all(tvs_runners);
+            match tokio::time::timeout(shutdown_timeout, drain).await {
+                Ok(drained) => drained.map_err(NodeError::Runtime)?,
+                Err(_) => {
+                    tracing::warn!(
+                        shutdown_timeout_secs = shutdown_timeout.as_secs(),
+                        "⚠ Shutdown forced after {}s (tvs.shutdown_timeout_secs) - in-flight requests dropped",
+                        shutdown_timeout.as_secs()
+                    );
+                    return result;
+                }
+            }
+        }
 
-            let tvs_runner = TvsWebServer::start_vote_server(
-                vote_service,
-                app_interface,
-                tvs_config,
-            ).await?;
+        tracing::info!("✓ Shutdown completed cleanly");
+        result
+    }
+}
 
-            Ok(Some(tvs_runner))
-        } else {
-            println!("⚠ No vote service configured - TVS vote server disabled");
-            println!("  Vote routes will not be available");
-            Ok(None)
+/// Report a drain mode change made through `source`
+fn log_drain(draining: bool, source: &str) {
+    if draining {
+        tracing::warn!(source, "⚠ Vote drain on ({}) - new votes get 503 until it is turned off", source);
+    } else {
+        tracing::info!(source, "✓ Vote drain off ({}) - accepting votes again", source);
+    }
+}
+
+/// Resolves once `max_uptime` has elapsed; never resolves when it is unset
+async fn uptime_limit(max_uptime: Option<Duration>) {
+    match max_uptime {
+        Some(max_uptime) => tokio::time::sleep(max_uptime).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Persistence backend a build with these features uses; postgres wins when both are on
+fn backend_for(postgres: bool, ephemeral: bool) -> Option<&'static str> {
+    match (postgres, ephemeral) {
+        (true, _) => Some("postgres"),
+        (false, true) => Some("ephemeral"),
+        (false, false) => None,
+    }
+}
+
+/// The vote service registrations startup makes, behind a trait so tests can make one of
+/// them fail; a build without a backend never registers them
+#[cfg_attr(
+    not(any(feature = "ephemeral", feature = "postgres")),
+    allow(dead_code)
+)]
+pub(crate) trait VoteRegistration {
+    fn register_vote_service(&mut self) -> Result<(), NodeError>;
+
+    fn register_vote_url_service(&mut self) -> Result<(), NodeError>;
+}
+
+/// Registers the PostgreSQL-backed services on the migrated `session`
+#[cfg(feature = "postgres")]
+struct PostgresRegistration<'a> {
+    node_id: &'a tfs::tfs::node_id::NodeId,
+    session: tfs_postgres::DbSession,
+    root_url: String,
+}
+
+#[cfg(feature = "postgres")]
+impl VoteRegistration for PostgresRegistration<'_> {
+    fn register_vote_service(&mut self) -> Result<(), NodeError> {
+        let vote_service = PostgresVoteService::new(self.session.clone());
+        tvs::services::vote_service::configure_vote_service(self.node_id, Box::new(vote_service))
+            .map_err(|e| NodeError::Persistence(e.into()))
+    }
+
+    fn register_vote_url_service(&mut self) -> Result<(), NodeError> {
+        let vote_url_service =
+            PostgresVoteUrlService::with_root_url(self.session.clone(), self.root_url.clone());
+        tvs::services::vote_url_service::configure_vote_url_service(
+            self.node_id,
+            Box::new(vote_url_service),
+        )
+        .map_err(|e| NodeError::Persistence(e.into()))
+    }
+}
+
+/// Registers the in-memory services, which keep votes through the TFS app interface
+#[cfg(all(feature = "ephemeral", not(feature = "postgres")))]
+struct EphemeralRegistration<'a> {
+    node_id: &'a tfs::tfs::node_id::NodeId,
+    app_interface: tfs::tfs_app_interface::TFSAppInterface,
+    root_url: String,
+}
+
+#[cfg(all(feature = "ephemeral", not(feature = "postgres")))]
+impl VoteRegistration for EphemeralRegistration<'_> {
+    fn register_vote_service(&mut self) -> Result<(), NodeError> {
+        let tfs_adapter = ActualTfsAppInterfaceAdapter::as_tfs_app_interface_adapter(
+            self.node_id,
+            self.app_interface.clone(),
+        );
+        tvs::services::vote_service::configure_ephemeral_vote_service(self.node_id, tfs_adapter)
+            .map_err(|e| NodeError::Persistence(e.into()))
+    }
+
+    fn register_vote_url_service(&mut self) -> Result<(), NodeError> {
+        tvs::services::vote_url_service::configure_ephemeral_vote_url_service(
+            self.node_id,
+            self.root_url.clone(),
+        )
+        .map_err(|e| NodeError::Persistence(e.into()))
+    }
+}
+
+/// Register the vote service, then the vote URL service; see `vote_services_registered`
+/// for what a failure does
+#[cfg(any(feature = "ephemeral", feature = "postgres"))]
+fn register_vote_services(
+    registration: &mut dyn VoteRegistration,
+    required: bool,
+) -> Result<bool, NodeError> {
+    let mut registered = Vec::new();
+    let result = registration.register_vote_service().and_then(|()| {
+        registered.push("VoteService");
+        registration.register_vote_url_service()
+    });
+    vote_services_registered(result, &registered, required)
+}
+
+/// Whether registering the vote services worked out; a failure stops startup unless
+/// `vote_service_required` is off, in which case it is logged and the node runs TFS alone
+/// `registered` names the services registered before the failure. The tvs registry can't
+/// unregister them, so they are reported rather than rolled back; with the vote server
+/// disabled nothing on this node serves them
+#[cfg(any(feature = "ephemeral", feature = "postgres"))]
+fn vote_services_registered(
+    result: Result<(), NodeError>,
+    registered: &[&str],
+    required: bool,
+) -> Result<bool, NodeError> {
+    match result {
+        Ok(()) => Ok(true),
+        Err(e) if !required => {
+            tracing::warn!(
+                error = %e,
+                "⚠ Could not configure the vote services: {} - vote server disabled (vote_service_required = false)",
+                e
+            );
+            if !registered.is_empty() {
+                let registered = registered.join(", ");
+                tracing::warn!(
+                    registered = %registered,
+                    "  Partially registered: {} stays registered but unused, as it can't be removed",
+                    registered
+                );
+            }
+            Ok(false)
         }
+        Err(e) => Err(e),
     }
+}
 
-    pub async fn run_until_shutdown(mut self) -> Result<(), Box<dyn std::error::Error>> {
-        // If TVS vote server is running, run both servers concurrently
-        if let Some(tvs_runner) = self.tvs_web_server_runner {
-            println!("Running both TFS and TVS servers until shutdown...");
-            TvsWebServer::run_both_until_shutdown(
-                self.tfs_web_server_runner,
-                tvs_runner,
-            ).await;
+/// Name of the persistence backend a build with these features uses
+/// Both on is easy to hit by accident since ephemeral is a default feature, so that case
+/// is warned about; a build with neither is an error
+pub fn select_backend(postgres: bool, ephemeral: bool) -> Result<&'static str, NodeError> {
+    if postgres && ephemeral {
+        tracing::warn!("⚠ Both the postgres and ephemeral features are enabled; using postgres");
+        tracing::warn!("  Build with --no-default-features --features postgres to select it explicitly");
+    }
+    backend_for(postgres, ephemeral).ok_or_else(|| {
+        NodeError::Persistence(
+            "no persistence backend compiled in; build with the ephemeral or postgres feature"
+                .into(),
+        )
+    })
+}
+
+/// Print the startup summary unless `quiet` is set (`--quiet`)
+fn print_startup_summary(
+    config: &TvsNodeConfig,
+    node_id: &tfs::tfs::node_id::NodeId,
+    vote_addrs: &[ListenAddr],
+) {
+    if !config.quiet {
+        print!("{}", startup_summary(config, node_id, vote_addrs));
+    }
+}
+
+/// One block describing the node as it was started: identity, ports, backend and frontends
+/// `vote_addrs` are where the vote servers actually listen, which `--auto-port` may have moved
+fn startup_summary(
+    config: &TvsNodeConfig,
+    node_id: &tfs::tfs::node_id::NodeId,
+    vote_addrs: &[ListenAddr],
+) -> String {
+    let server = &config.tfs.server;
+    let (admin_port, admin_frontend) = if config.admin_frontend_active() {
+        (server.admin_port.to_string(), "enabled")
+    } else {
+        ("shared with cluster port".to_string(), "disabled")
+    };
+    let vote_server = if vote_addrs.is_empty() {
+        "disabled".to_string()
+    } else {
+        vote_addrs
+            .iter()
+            .map(ListenAddr::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let backend = backend_for(cfg!(feature = "postgres"), cfg!(feature = "ephemeral"));
+
+    let rows = [
+        ("Node id", node_id.to_string()),
+        ("Node name", config.tfs.node_name.clone().unwrap_or_else(|| "tvs_node".to_string())),
+        ("TFS cluster port", server.cluster_message_port.to_string()),
+        ("TFS app port", server.app_port.to_string()),
+        ("TFS admin port", admin_port),
+        ("Persistence", backend.unwrap_or("none").to_string()),
+        ("Vote server", vote_server),
+        ("Admin frontend", admin_frontend.to_string()),
+    ];
+
+    let mut summary = String::from("──── TVS node started ────\n");
+    for (label, value) in rows {
+        summary.push_str(&format!("  {:<17} {}\n", format!("{}:", label), value));
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    /// Registers the vote service, then fails on the vote URL service
+    #[cfg(any(feature = "ephemeral", feature = "postgres"))]
+    struct FailingUrlRegistration;
+
+    #[cfg(any(feature = "ephemeral", feature = "postgres"))]
+    impl VoteRegistration for FailingUrlRegistration {
+        fn register_vote_service(&mut self) -> Result<(), NodeError> {
             Ok(())
+        }
+
+        fn register_vote_url_service(&mut self) -> Result<(), NodeError> {
+            Err(NodeError::Persistence(
+                "vote URL service already configured".into(),
+            ))
+        }
+    }
+
+    #[cfg(any(feature = "ephemeral", feature = "postgres"))]
+    #[test]
+    fn test_vote_service_failure_is_fatal_only_when_required() {
+        let failure = || Err(NodeError::Persistence("vote service already configured".into()));
+
+        assert!(vote_services_registered(Ok(()), &[], true).unwrap());
+        assert!(matches!(
+            vote_services_registered(failure(), &[], true),
+            Err(NodeError::Persistence(_))
+        ));
+        // Not required: the node carries on, without the vote server
+        assert!(!vote_services_registered(failure(), &[], false).unwrap());
+    }
+
+    #[cfg(any(feature = "ephemeral", feature = "postgres"))]
+    #[test]
+    fn test_partial_registration_is_reported() {
+        let events = crate::logging::tests::Capture::json_events(|| {
+            assert!(!register_vote_services(&mut FailingUrlRegistration, false).unwrap());
+        });
+
+        assert_eq!(events.len(), 2, "{:?}", events);
+        assert_eq!(events[1]["level"], "WARN");
+        assert_eq!(events[1]["fields"]["registered"], "VoteService");
+    }
+
+    #[test]
+    fn test_admin_frontend_event_has_fields() {
+        let config: tfs_http::app_config::AppConfig = serde_json::from_value(serde_json::json!({
+            "server": {"cluster_message_port": 8080, "app_port": 8081, "admin_port": 8082}
+        }))
+        .unwrap();
+
+        let events = crate::logging::tests::Capture::json_events(|| {
+            TvsNodeRunner::configure_admin_frontend(&config, false)
+        });
+
+        let disabled = &events[0];
+        assert_eq!(disabled["level"], "WARN");
+        assert_eq!(disabled["target"], module_path!().trim_end_matches("::tests"));
+        let reason = if cfg!(feature = "admin-frontend") {
+            "admin_frontend_enabled = false"
         } else {
-            // Just run TFS server
-            self.tfs_web_server_runner.run_until_shutdown().await
+            "not compiled in"
+        };
+        assert_eq!(disabled["fields"]["reason"], reason);
+        assert!(disabled["fields"]["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("⚠ Admin frontend disabled"));
+    }
+
+    #[test]
+    fn test_ambiguous_backend_warning_is_an_event() {
+        let events = crate::logging::tests::Capture::json_events(|| {
+            select_backend(true, true).unwrap();
+        });
+
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().all(|event| event["level"] == "WARN"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_startup_summary_contains_key_fields() {
+        let config: TvsNodeConfig = serde_json::from_value(serde_json::json!({
+            "server": {"cluster_message_port": 8080, "app_port": 8081, "admin_port": 8082},
+            "node_name": "tvs_node_1",
+            "tvs": {"vote_host": "127.0.0.1", "vote_port": 8090}
+        }))
+        .unwrap();
+        let node_id = tfs::tfs::node_id::NodeId::new("tvs_node_1".to_string(), uuid::Uuid::nil());
+
+        let vote_addrs = [ListenAddr::Tcp("127.0.0.1:8090".parse().unwrap())];
+        let summary = startup_summary(&config, &node_id, &vote_addrs);
+        assert!(summary.contains(&node_id.to_string()));
+        assert!(summary.contains("Node name:        tvs_node_1"));
+        assert!(summary.contains("TFS cluster port: 8080"));
+        assert!(summary.contains("TFS app port:     8081"));
+        assert!(summary.contains("Vote server:      127.0.0.1:8090"));
+        assert!(summary.contains(&format!(
+            "Persistence:      {}",
+            backend_for(cfg!(feature = "postgres"), cfg!(feature = "ephemeral")).unwrap_or("none")
+        )));
+        assert!(summary.contains("Admin frontend:"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_startup_summary_reports_disabled_vote_server() {
+        let config: TvsNodeConfig = serde_json::from_value(serde_json::json!({
+            "server": {"cluster_message_port": 8080, "app_port": 8081, "admin_port": 8082},
+            "tvs": {"enabled": false}
+        }))
+        .unwrap();
+        let node_id = tfs::tfs::node_id::NodeId::new("tvs_node".to_string(), uuid::Uuid::nil());
+
+        assert!(startup_summary(&config, &node_id, &[]).contains("Vote server:      disabled"));
+    }
+
+    #[test]
+    fn test_select_backend_without_persistence_feature() {
+        let err = select_backend(false, false).unwrap_err();
+        assert!(matches!(err, NodeError::Persistence(_)));
+        assert_eq!(err.exit_code(), 4);
+        assert!(err.to_string().contains("no persistence backend compiled in"));
+    }
+
+    #[test]
+    fn test_select_backend_prefers_postgres() {
+        assert_eq!(select_backend(true, false).unwrap(), "postgres");
+        assert_eq!(select_backend(false, true).unwrap(), "ephemeral");
+        assert_eq!(select_backend(true, true).unwrap(), "postgres");
+    }
+
+    #[tokio::test]
+    async fn test_build_rejects_invalid_config() {
+        let config: TvsNodeConfig = serde_json::from_str(
+            r#"{
+                "server": {
+                    "cluster_message_port": 8080,
+                    "app_port": 8080,
+                    "admin_port": 8082
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let err = TvsNodeRunner::build_with_config(config).await.err().unwrap();
+        assert!(matches!(err, NodeError::Config(ConfigError::Validation(_))));
+        assert_eq!(err.exit_code(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_uptime_limit_fires_after_max_uptime() {
+        let limit = Duration::from_millis(20);
+        let started = tokio::time::Instant::now();
+
+        tokio::time::timeout(Duration::from_secs(5), uptime_limit(Some(limit)))
+            .await
+            .expect("uptime limit did not fire");
+        assert!(started.elapsed() >= limit);
+    }
+
+    #[tokio::test]
+    async fn test_uptime_limit_unset_never_fires() {
+        let fired = tokio::time::timeout(Duration::from_millis(50), uptime_limit(None)).await;
+        assert!(fired.is_err());
+    }
+
+    /// `n` ports free right now: reserved, then released again for the node to bind
+    #[cfg(all(feature = "ephemeral", not(feature = "postgres")))]
+    fn free_ports(n: usize) -> Vec<u16> {
+        let listeners: Vec<_> = (0..n)
+            .map(|_| std::net::TcpListener::bind("127.0.0.1:0").unwrap())
+            .collect();
+        listeners
+            .iter()
+            .map(|listener| listener.local_addr().unwrap().port())
+            .collect()
+    }
+
+    /// A node named `node_name` on the cluster message, app and admin `ports`, with its
+    /// vote server on `vote_port`; `extra` adds top-level settings
+    #[cfg(all(feature = "ephemeral", not(feature = "postgres")))]
+    fn node_config(
+        node_name: &str,
+        ports: &[u16],
+        vote_port: u16,
+        extra: serde_json::Value,
+    ) -> TvsNodeConfig {
+        let mut config = serde_json::json!({
+            "server": {
+                "cluster_message_port": ports[0],
+                "app_port": ports[1],
+                "admin_port": ports[2]
+            },
+            "node_name": node_name,
+            "tvs": { "vote_port": vote_port }
+        });
+        if let serde_json::Value::Object(extra) = extra {
+            config.as_object_mut().unwrap().extend(extra);
         }
+        serde_json::from_value(config).unwrap()
     }
 
-}
\ No newline at end of file
+    // A postgres build needs a live database to get this far, and a build without a
+    // backend never does
+    #[cfg(all(feature = "ephemeral", not(feature = "postgres")))]
+    #[tokio::test]
+    async fn test_handle_triggers_shutdown() {
+        let ports = free_ports(4);
+        let config = node_config("handle_test_node", &ports, ports[3], serde_json::json!({}));
+
+        let runner = TvsNodeRunner::build_with_config(config).await.unwrap();
+        let handle = runner.handle();
+        assert!(handle.is_running());
+
+        // The runner's future isn't Send, so drive it alongside the shutdown on this task
+        let stop = async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            handle.shutdown().await;
+        };
+        let (result, ()) = tokio::time::timeout(
+            Duration::from_secs(10),
+            async { tokio::join!(runner.run_until_shutdown(), stop) },
+        )
+        .await
+        .expect("node did not stop");
+
+        assert!(result.is_ok());
+        assert!(!handle.is_running());
+    }
+
+    #[cfg(all(feature = "ephemeral", not(feature = "postgres")))]
+    #[tokio::test]
+    async fn test_runner_continues_when_vote_registration_fails() {
+        // Two nodes' worth, as the refused build may not release its TFS ports
+        let ports = free_ports(8);
+        let config = |required: bool, ports: &[u16]| {
+            node_config(
+                "registration_failure_test_node",
+                ports,
+                ports[3],
+                serde_json::json!({ "quiet": true, "vote_service_required": required }),
+            )
+        };
+
+        // Required (the default), the failure stops startup
+        let required = TvsNodeRunner::build(
+            config(true, &ports[4..]),
+            None,
+            Some(Box::new(FailingUrlRegistration)),
+        )
+        .await;
+        assert!(matches!(required, Err(NodeError::Persistence(_))));
+
+        let runner = TvsNodeRunner::build(
+            config(false, &ports[..4]),
+            None,
+            Some(Box::new(FailingUrlRegistration)),
+        )
+        .await
+        .unwrap();
+        // TFS is up, without the vote server
+        assert!(runner.vote_server_addr().is_none());
+        let handle = runner.handle();
+        assert!(handle.is_running());
+
+        let (result, ()) = tokio::time::timeout(Duration::from_secs(10), async {
+            tokio::join!(runner.run_until_shutdown(), handle.shutdown())
+        })
+        .await
+        .expect("node did not stop");
+        assert!(result.is_ok());
+    }
+
+    #[cfg(all(feature = "ephemeral", not(feature = "postgres")))]
+    #[tokio::test]
+    async fn test_vote_server_addr_reports_os_assigned_port() {
+        let ports = free_ports(3);
+        let config = node_config(
+            "vote_addr_test_node",
+            &ports,
+            0,
+            serde_json::json!({ "quiet": true }),
+        );
+
+        let runner = TvsNodeRunner::build_with_config(config).await.unwrap();
+        let addr = runner.vote_server_addr().expect("vote server should be running");
+        assert_ne!(addr.port(), 0);
+        assert!(addr.ip().is_loopback());
+
+        let response = reqwest::get(format!("http://{}/healthz", addr)).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        let node_header = response.headers()["x-tvs-node-id"].to_str().unwrap().to_string();
+        // TFS answered before the vote server started, so the combined report is healthy too
+        let health: serde_json::Value = reqwest::get(format!("http://{}/health", addr))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(health["status"], "ok");
+        assert_eq!(health["tfs"], "ok");
+        assert_eq!(health["tvs"], "ok");
+        assert_eq!(health["node"], node_header);
+
+        let handle = runner.handle();
+        assert!(!handle.is_draining());
+        assert!(!handle.set_draining(true));
+        assert!(handle.is_draining());
+        // Draining refuses writes only, so probes keep answering
+        let response = reqwest::get(format!("http://{}/healthz", addr)).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+
+        let (result, ()) = tokio::time::timeout(
+            Duration::from_secs(10),
+            async { tokio::join!(runner.run_until_shutdown(), handle.shutdown()) },
+        )
+        .await
+        .expect("node did not stop");
+        assert!(result.is_ok());
+    }
+}
@@ -0,0 +1,144 @@
+use std::{net::SocketAddr, time::Duration};
+
+use socket2::{Domain, Protocol, SockRef, Socket, TcpKeepalive, Type};
+use tokio::net::TcpStream;
+
+use crate::config::TvsServerConfig;
+
+/// Settings applied to every accepted vote connection, from `tvs.tcp_keepalive_secs` and
+/// `tvs.tcp_nodelay`; the default leaves sockets as the OS hands them out
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SocketOptions {
+    /// Idle time before the first keepalive probe; None leaves keepalive off
+    pub keepalive: Option<Duration>,
+    pub nodelay: bool,
+}
+
+impl SocketOptions {
+    pub fn from_config(tvs: &TvsServerConfig) -> Self {
+        Self {
+            keepalive: tvs.tcp_keepalive_secs.map(Duration::from_secs),
+            nodelay: tvs.tcp_nodelay,
+        }
+    }
+
+    /// Set the options on an accepted `stream`; settings left at their default aren't touched
+    pub fn apply(&self, stream: &TcpStream) -> std::io::Result<()> {
+        if let Some(idle) = self.keepalive {
+            SockRef::from(stream).set_tcp_keepalive(&TcpKeepalive::new().with_time(idle))?;
+        }
+        if self.nodelay {
+            stream.set_nodelay(true)?;
+        }
+        Ok(())
+    }
+
+    /// `apply`, logging rather than dropping the connection when the OS refuses a setting
+    pub fn apply_or_warn(&self, stream: &TcpStream) {
+        if let Err(e) = self.apply(stream) {
+            tracing::warn!(error = %e, "⚠ Could not set TCP options on a vote connection: {}", e);
+        }
+    }
+}
+
+/// Pending connections a vote listener queues, as `tokio::net::TcpListener::bind` uses
+const LISTEN_BACKLOG: i32 = 1024;
+
+/// Bind a non-blocking listener on `addr`, setting `IPV6_V6ONLY` to `ipv6_only` before the
+/// bind when it is an IPv6 address; an IPv4 address ignores `ipv6_only`
+pub fn bind_listener(addr: SocketAddr, ipv6_only: bool) -> std::io::Result<std::net::TcpListener> {
+    let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))?;
+    if addr.is_ipv6() {
+        socket.set_only_v6(ipv6_only)?;
+    }
+    // Like tokio's bind: a restarted node can rebind a port with connections in TIME_WAIT
+    #[cfg(unix)]
+    socket.set_reuse_address(true)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&addr.into())?;
+    socket.listen(LISTEN_BACKLOG)?;
+    Ok(socket.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_config() {
+        let tvs = TvsServerConfig::default();
+        // The defaults keep sockets as they were before the options existed
+        assert_eq!(SocketOptions::from_config(&tvs), SocketOptions::default());
+
+        let tvs = TvsServerConfig {
+            tcp_keepalive_secs: Some(60),
+            tcp_nodelay: true,
+            ..TvsServerConfig::default()
+        };
+        assert_eq!(
+            SocketOptions::from_config(&tvs),
+            SocketOptions {
+                keepalive: Some(Duration::from_secs(60)),
+                nodelay: true,
+            }
+        );
+    }
+
+    async fn connected_pair() -> (TcpStream, TcpStream) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (accepted, _) = listener.accept().await.unwrap();
+        (accepted, client)
+    }
+
+    #[tokio::test]
+    async fn test_apply_sets_socket_options() {
+        let (accepted, _client) = connected_pair().await;
+        SocketOptions {
+            keepalive: Some(Duration::from_secs(45)),
+            nodelay: true,
+        }
+        .apply(&accepted)
+        .unwrap();
+
+        let socket = SockRef::from(&accepted);
+        assert!(socket.keepalive().unwrap());
+        assert_eq!(
+            socket.tcp_keepalive_time().unwrap(),
+            Duration::from_secs(45)
+        );
+        assert!(accepted.nodelay().unwrap());
+    }
+
+    #[test]
+    fn test_bind_listener_sets_ipv6_only() {
+        // The unspecified address, since Linux makes a socket bound to a specific IPv6
+        // address IPv6-only regardless
+        for ipv6_only in [true, false] {
+            let listener = bind_listener("[::]:0".parse().unwrap(), ipv6_only).unwrap();
+            assert_eq!(SockRef::from(&listener).only_v6().unwrap(), ipv6_only);
+        }
+
+        // Not an option on an IPv4 socket, so it is left alone there
+        let listener = bind_listener("127.0.0.1:0".parse().unwrap(), true).unwrap();
+        assert!(listener.local_addr().unwrap().is_ipv4());
+    }
+
+    #[test]
+    fn test_bind_listener_reports_taken_port() {
+        let taken = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let err = bind_listener(taken.local_addr().unwrap(), false).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::AddrInUse);
+    }
+
+    #[tokio::test]
+    async fn test_default_leaves_socket_alone() {
+        let (accepted, _client) = connected_pair().await;
+        SocketOptions::default().apply(&accepted).unwrap();
+
+        assert!(!SockRef::from(&accepted).keepalive().unwrap());
+        assert!(!accepted.nodelay().unwrap());
+    }
+}
@@ -0,0 +1,108 @@
+use std::time::{Duration, Instant};
+
+/// Collects how long each phase of `build_with_config` took
+/// A phase runs from the previous `mark` (or the start) to its own, so the phases add up
+/// to the total
+#[derive(Debug)]
+pub struct StartupTimer {
+    started: Instant,
+    last_mark: Instant,
+    phases: Vec<(&'static str, Duration)>,
+}
+
+impl StartupTimer {
+    pub fn start() -> Self {
+        let now = Instant::now();
+        Self {
+            started: now,
+            last_mark: now,
+            phases: Vec::new(),
+        }
+    }
+
+    /// End phase `name` now
+    pub fn mark(&mut self, name: &'static str) {
+        let now = Instant::now();
+        self.record(name, now.duration_since(self.last_mark));
+        self.last_mark = now;
+    }
+
+    /// Add `elapsed` to phase `name`; a phase recorded twice keeps its first position
+    pub fn record(&mut self, name: &'static str, elapsed: Duration) {
+        match self.phases.iter_mut().find(|(phase, _)| *phase == name) {
+            Some((_, total)) => *total += elapsed,
+            None => self.phases.push((name, elapsed)),
+        }
+    }
+
+    /// When startup began, which is where the node's uptime counts from
+    pub fn started(&self) -> Instant {
+        self.started
+    }
+
+    pub fn phases(&self) -> &[(&'static str, Duration)] {
+        &self.phases
+    }
+
+    pub fn total(&self) -> Duration {
+        self.last_mark.duration_since(self.started)
+    }
+
+    /// Log the total, and with `verbose` (`--verbose-startup`) each phase before it
+    pub fn log(&self, verbose: bool) {
+        if verbose {
+            for (phase, elapsed) in self.phases() {
+                let ms = millis(*elapsed);
+                tracing::info!(
+                    phase = *phase,
+                    ms,
+                    "  Startup phase {}: {:.1} ms",
+                    phase,
+                    ms
+                );
+            }
+        }
+        let ms = millis(self.total());
+        tracing::info!(total_ms = ms, "✓ Node started in {:.1} ms", ms);
+    }
+}
+
+fn millis(elapsed: Duration) -> f64 {
+    elapsed.as_secs_f64() * 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_accumulates_named_phases() {
+        let mut timer = StartupTimer::start();
+        timer.record("tfs server start", Duration::from_millis(120));
+        timer.record("database migrations", Duration::from_millis(40));
+        timer.record("tfs server start", Duration::from_millis(5));
+
+        assert_eq!(
+            timer.phases(),
+            &[
+                ("tfs server start", Duration::from_millis(125)),
+                ("database migrations", Duration::from_millis(40)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_marked_phases_add_up_to_total() {
+        let mut timer = StartupTimer::start();
+        std::thread::sleep(Duration::from_millis(5));
+        timer.mark("first");
+        std::thread::sleep(Duration::from_millis(5));
+        timer.mark("second");
+
+        let phases = timer.phases();
+        assert_eq!(phases.len(), 2);
+        assert!(phases[0].1 >= Duration::from_millis(5));
+        assert!(phases[1].1 >= Duration::from_millis(5));
+        assert_eq!(phases[0].1 + phases[1].1, timer.total());
+    }
+}
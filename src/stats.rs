@@ -0,0 +1,148 @@
+use std::time::Instant;
+
+use axum::{routing::get, Json, Router};
+use serde_json::{json, Value};
+
+use crate::{connection_limit::OpenConnections, drain::DrainSwitch, vote_server::VoteBackend};
+
+/// Live node state behind `/debug/stats`, gathered from the parts that track it
+#[derive(Clone)]
+pub struct RuntimeStats {
+    /// When startup began; uptime counts from here
+    pub started: Instant,
+    /// Build and identity fields, as `tvs_node info` renders them
+    pub info: Value,
+    pub connections: OpenConnections,
+    pub drain: DrainSwitch,
+    /// Vote counters; None leaves the vote routes uninstrumented
+    #[cfg(feature = "metrics")]
+    pub metrics: Option<crate::metrics::VoteMetrics>,
+}
+
+impl RuntimeStats {
+    /// Everything `info` reports plus the state that changes while the node runs
+    /// `votes` is null in a build without the metrics feature, which is what counts them,
+    /// and `pool` is null without a connection pool
+    pub fn snapshot(&self, backend: &VoteBackend) -> Value {
+        let mut stats = self.info.clone();
+        stats["uptime_secs"] = json!(self.started.elapsed().as_secs());
+        stats["backend"] = json!(backend.name());
+        stats["draining"] = json!(self.drain.is_draining());
+        stats["connections"] = json!({ "open": self.connections.get() });
+        stats["votes"] = self.votes();
+        stats["pool"] = match backend {
+            #[cfg(feature = "postgres")]
+            VoteBackend::Postgres(pool) => json!(crate::pool_stats::PoolStats::of(pool)),
+            _ => Value::Null,
+        };
+        stats
+    }
+
+    fn votes(&self) -> Value {
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.metrics {
+            let (recorded, failed) = metrics.vote_counts();
+            return json!({ "recorded": recorded, "failed": failed });
+        }
+        Value::Null
+    }
+}
+
+/// `/debug/stats`, one JSON snapshot of the node for troubleshooting
+/// Like `/debug/pool` it takes no connection, so it answers while the backend is struggling
+pub fn routes(stats: RuntimeStats, backend: VoteBackend) -> Router {
+    Router::new().route(
+        "/debug/stats",
+        get(move || async move { Json(stats.snapshot(&backend)) }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{connection_limit::AcceptPolicy, vote_server::VoteServerRunner};
+
+    fn stats(connections: OpenConnections) -> RuntimeStats {
+        RuntimeStats {
+            started: Instant::now() - std::time::Duration::from_secs(90),
+            info: json!({ "version": env!("CARGO_PKG_VERSION"), "node_id": "tvs_node_1" }),
+            connections,
+            drain: DrainSwitch::default(),
+            #[cfg(feature = "metrics")]
+            metrics: None,
+        }
+    }
+
+    #[test]
+    fn test_snapshot_extends_info() {
+        let stats = stats(OpenConnections::default());
+        stats.drain.set(true);
+
+        let snapshot = stats.snapshot(&VoteBackend::Ephemeral);
+        assert_eq!(snapshot["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(snapshot["node_id"], "tvs_node_1");
+        assert!(snapshot["uptime_secs"].as_u64().unwrap() >= 90);
+        assert_eq!(snapshot["backend"], "ephemeral");
+        assert_eq!(snapshot["draining"], true);
+        assert_eq!(snapshot["connections"]["open"], 0);
+        assert_eq!(snapshot["votes"], Value::Null);
+        assert_eq!(snapshot["pool"], Value::Null);
+    }
+
+    #[test]
+    fn test_snapshot_reports_config_source() {
+        let config: crate::config::TvsNodeConfig = serde_json::from_value(json!({
+            "server": {"cluster_message_port": 8080, "app_port": 8081, "admin_port": 8082}
+        }))
+        .unwrap();
+        let mut stats = stats(OpenConnections::default());
+        stats.info = crate::info::render(&config, Some(&crate::config::ConfigSource::Stdin));
+
+        let snapshot = stats.snapshot(&VoteBackend::Ephemeral);
+        assert_eq!(snapshot["config_source"], json!({ "kind": "stdin" }));
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_snapshot_reports_vote_counts() {
+        let mut stats = stats(OpenConnections::default());
+        stats.metrics = Some(crate::metrics::VoteMetrics::new("tvs_node_1").unwrap());
+
+        let snapshot = stats.snapshot(&VoteBackend::Ephemeral);
+        assert_eq!(snapshot["votes"], json!({ "recorded": 0, "failed": 0 }));
+    }
+
+    #[tokio::test]
+    async fn test_debug_stats_counts_open_connections() {
+        let accept = AcceptPolicy::default();
+        let router = routes(stats(accept.connections.clone()), VoteBackend::Ephemeral);
+        let runner = VoteServerRunner::serve_on_free_port(
+            router,
+            "127.0.0.1",
+            0,
+            None,
+            false,
+            crate::vote_server::BindOptions::default(),
+            accept,
+        )
+        .await
+        .unwrap();
+
+        let body: serde_json::Value =
+            reqwest::get(format!("http://{}/debug/stats", runner.local_addr()))
+                .await
+                .unwrap()
+                .json()
+                .await
+                .unwrap();
+        runner.shutdown().await.unwrap();
+
+        // The request's own connection is open while it is answered
+        assert!(
+            body["connections"]["open"].as_u64().unwrap() >= 1,
+            "{}",
+            body
+        );
+        assert_eq!(body["backend"], "ephemeral");
+    }
+}
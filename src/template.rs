@@ -0,0 +1,81 @@
+use std::path::Path;
+
+use crate::config::{ConfigError, TvsNodeConfig};
+
+/// Write the default config template to `output`, or to stdout when no path is given
+/// An existing file is only replaced when `force` is set
+pub fn generate(output: Option<&str>, force: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let rendered = render()?;
+
+    let Some(path) = output else {
+        print!("{}", rendered);
+        return Ok(());
+    };
+
+    if Path::new(path).exists() && !force {
+        return Err(format!(
+            "Refusing to overwrite existing file {} (pass --force)",
+            path
+        )
+        .into());
+    }
+
+    std::fs::write(path, rendered).map_err(|source| ConfigError::Io {
+        path: path.to_string(),
+        source,
+    })?;
+    eprintln!("✓ Wrote config template to {}", path);
+    Ok(())
+}
+
+/// Render the template as pretty JSON with a trailing newline
+fn render() -> Result<String, serde_json::Error> {
+    let mut rendered = serde_json::to_string_pretty(&TvsNodeConfig::template())?;
+    rendered.push('\n');
+    Ok(rendered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("tvs_node_{}_{}.json", name, std::process::id()))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn test_generate_round_trips() {
+        let path = temp_path("template");
+        let _ = std::fs::remove_file(&path);
+
+        generate(Some(&path), false).unwrap();
+        let config = TvsNodeConfig::read_config(&path);
+        let _ = std::fs::remove_file(&path);
+
+        let config = config.unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.tfs.server.cluster_message_port, 8080);
+        assert_eq!(config.tvs_config().unwrap().vote_port, 8090);
+        assert_eq!(config.persistence.is_some(), cfg!(feature = "postgres"));
+    }
+
+    #[test]
+    fn test_generate_refuses_to_overwrite() {
+        let path = temp_path("existing");
+        std::fs::write(&path, "{}").unwrap();
+
+        let refused = generate(Some(&path), false);
+        let untouched = std::fs::read_to_string(&path).unwrap();
+        let forced = generate(Some(&path), true);
+        let overwritten = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert!(refused.unwrap_err().to_string().contains("--force"));
+        assert_eq!(untouched, "{}");
+        assert!(forced.is_ok());
+        assert!(overwritten.contains("\"tvs\""));
+    }
+}
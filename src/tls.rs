@@ -0,0 +1,88 @@
+use std::sync::Arc;
+
+use rustls::ServerConfig;
+
+use crate::{config::TlsConfig, error::NodeError};
+
+/// Load the PEM certificate chain and private key named in `tls` into a rustls server config
+/// Every failure names the file involved, since a wrong path is the usual cause
+pub fn load_server_config(tls: &TlsConfig) -> Result<Arc<ServerConfig>, NodeError> {
+    let tls_error = |message: String| NodeError::VoteServer(message.into());
+
+    let cert_pem = std::fs::read(&tls.cert_path).map_err(|e| {
+        tls_error(format!(
+            "could not read TLS certificate {}: {}",
+            tls.cert_path, e
+        ))
+    })?;
+    let key_pem = std::fs::read(&tls.key_path)
+        .map_err(|e| tls_error(format!("could not read TLS key {}: {}", tls.key_path, e)))?;
+
+    let certs = rustls_pemfile::certs(&mut cert_pem.as_slice())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| {
+            tls_error(format!(
+                "invalid PEM in TLS certificate {}: {}",
+                tls.cert_path, e
+            ))
+        })?;
+    if certs.is_empty() {
+        return Err(tls_error(format!(
+            "no certificates found in {}",
+            tls.cert_path
+        )));
+    }
+
+    let key = rustls_pemfile::private_key(&mut key_pem.as_slice())
+        .map_err(|e| tls_error(format!("invalid PEM in TLS key {}: {}", tls.key_path, e)))?
+        .ok_or_else(|| tls_error(format!("no private key found in {}", tls.key_path)))?;
+
+    let mut config =
+        ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .map_err(|e| tls_error(e.to_string()))?
+            .with_no_client_auth()
+            .with_single_cert(certs, key)
+            .map_err(|e| {
+                tls_error(format!(
+                    "TLS key {} does not match certificate {}: {}",
+                    tls.key_path, tls.cert_path, e
+                ))
+            })?;
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+    Ok(Arc::new(config))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_cert_names_path() {
+        let tls = TlsConfig {
+            cert_path: "/nonexistent/tvs_node/cert.pem".to_string(),
+            key_path: "/nonexistent/tvs_node/key.pem".to_string(),
+        };
+
+        let err = load_server_config(&tls).unwrap_err();
+        assert!(matches!(err, NodeError::VoteServer(_)));
+        assert!(err
+            .to_string()
+            .contains("could not read TLS certificate /nonexistent/tvs_node/cert.pem"));
+    }
+
+    #[test]
+    fn test_cert_without_pem_blocks() {
+        let path = std::env::temp_dir().join("tvs_node_empty_cert.pem");
+        std::fs::write(&path, "not a certificate").unwrap();
+        let tls = TlsConfig {
+            cert_path: path.to_string_lossy().into_owned(),
+            key_path: path.to_string_lossy().into_owned(),
+        };
+
+        let err = load_server_config(&tls).unwrap_err();
+        std::fs::remove_file(&path).ok();
+        assert!(err.to_string().contains("no certificates found in"));
+    }
+}
@@ -0,0 +1,1055 @@
+use std::{
+    fmt,
+    future::Future,
+    net::{SocketAddr, ToSocketAddrs},
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+    task::Poll,
+};
+
+use axum::serve::ListenerExt;
+use axum::{http::StatusCode, routing::get, Json, Router};
+use axum_server::tls_rustls::{RustlsAcceptor, RustlsConfig};
+use serde_json::{json, Value};
+use tokio::{net::TcpListener, sync::oneshot, task::JoinHandle};
+use tvs::{
+    services::vote_service::get_vote_service,
+    webserver::{create_nested_vote_router, TVSAppState},
+};
+
+use crate::{
+    config::{IpVersion, TvsNodeConfig, TvsServerConfig},
+    connection_limit::{AcceptPolicy, LimitedListener},
+    error::NodeError,
+    readiness::ReadinessGate,
+    stats::RuntimeStats,
+};
+
+/// Ports `--auto-port` tries, starting with the configured one, before giving up
+const AUTO_PORT_ATTEMPTS: u16 = 100;
+
+/// How a vote listener's address is picked and its socket bound, from `tvs.ip_version` and
+/// `tvs.ipv6_only`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BindOptions {
+    pub ip_version: IpVersion,
+    pub ipv6_only: bool,
+}
+
+impl BindOptions {
+    pub fn from_config(tvs: &TvsServerConfig) -> Self {
+        Self {
+            ip_version: tvs.ip_version,
+            ipv6_only: tvs.ipv6_only,
+        }
+    }
+}
+
+/// Handle to a running vote server
+/// The node owns the listener (rather than going through `TvsWebServer`) so it can
+/// stop accepting connections and drain in-flight requests on shutdown
+pub struct VoteServerRunner {
+    local_addr: ListenAddr,
+    shutdown_tx: Option<oneshot::Sender<()>>,
+    handle: JoinHandle<std::io::Result<()>>,
+}
+
+impl VoteServerRunner {
+    /// Vote routes for the node's configured vote service, if there is one
+    /// The router is shared by every listener, so they all report into the same metrics
+    /// and stats
+    pub fn vote_router(
+        node_id: &tfs::tfs::node_id::NodeId,
+        app_interface: tfs::tfs_app_interface::TFSAppInterface,
+        backend: VoteBackend,
+        config: &TvsNodeConfig,
+        stats: RuntimeStats,
+    ) -> Result<Option<Router>, NodeError> {
+        let Some(vote_service) = get_vote_service(node_id) else {
+            return Ok(None);
+        };
+        let tvs = config.vote_server_config();
+
+        let health = crate::health::routes(
+            node_id.to_string(),
+            app_interface.clone(),
+            config.tfs.server.app_port,
+            backend.clone(),
+        );
+        let router = create_nested_vote_router(TVSAppState::new(vote_service, app_interface));
+
+        // Innermost, so only handler failures count against the backend, not refusals
+        // from the layers below
+        #[cfg(feature = "postgres")]
+        let router = match &backend {
+            VoteBackend::Postgres(_) => {
+                crate::backend_guard::apply(router, &config.persistence_config(), backend.clone())
+            }
+            _ => router,
+        };
+
+        let router = crate::drain::apply(router, stats.drain.clone());
+
+        let router = match tvs.max_body_bytes {
+            Some(max_body_bytes) => crate::body_limit::apply(router, max_body_bytes),
+            None => router,
+        };
+
+        // Limited inside the metrics layer so rejected requests still show up as 413s, 429s
+        // and 503s
+        let router = match tvs.rate_limit.clone() {
+            Some(rate_limit) => crate::rate_limit::apply(router, rate_limit),
+            None => router,
+        };
+
+        // Outside the rate limit, so a shed request doesn't also use up a client's tokens
+        let router = match tvs.max_connections {
+            Some(max_connections) => crate::concurrency_limit::apply(
+                router,
+                max_connections,
+                crate::concurrency_limit::Shed::from_config(&tvs),
+            ),
+            None => router,
+        };
+
+        // Outside the limits so their refusals get the same envelope as handler errors
+        let router = crate::error_response::apply(router);
+
+        // Instrument only the vote routes; probe traffic would skew the latency histogram
+        #[cfg(feature = "metrics")]
+        let router = match stats.metrics.clone() {
+            Some(metrics) => crate::metrics::instrument(router, metrics),
+            None => router,
+        };
+
+        // Unlimited and uninstrumented like the probes, so it still answers under load
+        #[cfg(feature = "postgres")]
+        let router = match &backend {
+            VoteBackend::Postgres(pool) => router.merge(crate::pool_stats::routes(pool.clone())),
+            _ => router,
+        };
+
+        Ok(Some(
+            router
+                .merge(crate::stats::routes(stats, backend.clone()))
+                .merge(probe_routes(
+                    node_id.to_string(),
+                    backend,
+                    ReadinessGate::new(config.readiness_config()),
+                ))
+                .merge(health),
+        ))
+    }
+
+    /// Bind `host:port` and serve `router` over plain HTTP in the background until shutdown is requested
+    /// The node itself goes through `serve_on_free_port`; this is the plain case
+    pub async fn serve(router: Router, host: &str, port: u16) -> Result<Self, NodeError> {
+        Self::bind(
+            router,
+            host,
+            port,
+            None,
+            BindOptions::default(),
+            AcceptPolicy::default(),
+        )
+        .await
+    }
+
+    /// Bind `host:port`, or with `auto_port` the first free port after it should that be taken,
+    /// and serve `router` (over TLS when `tls` is given), applying `accept` to each
+    /// accepted connection. Without `auto_port` a taken port is `NodeError::PortInUse`
+    pub async fn serve_on_free_port(
+        router: Router,
+        host: &str,
+        port: u16,
+        tls: Option<Arc<rustls::ServerConfig>>,
+        auto_port: bool,
+        bind: BindOptions,
+        accept: AcceptPolicy,
+    ) -> Result<Self, NodeError> {
+        let last_port = if auto_port && port != 0 {
+            port.saturating_add(AUTO_PORT_ATTEMPTS - 1)
+        } else {
+            port
+        };
+        let mut candidate = port;
+        loop {
+            let bound = Self::bind(
+                router.clone(),
+                host,
+                candidate,
+                tls.clone(),
+                bind,
+                accept.clone(),
+            );
+            match bound.await {
+                Err(NodeError::PortInUse { .. }) if candidate < last_port => candidate += 1,
+                Err(NodeError::PortInUse { .. }) if candidate != port => {
+                    return Err(NodeError::VoteServer(
+                        format!(
+                            "no free port on {} between {} and {} (--auto-port)",
+                            host, port, last_port
+                        )
+                        .into(),
+                    ))
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn bind(
+        router: Router,
+        host: &str,
+        port: u16,
+        tls: Option<Arc<rustls::ServerConfig>>,
+        bind: BindOptions,
+        accept: AcceptPolicy,
+    ) -> Result<Self, NodeError> {
+        // TCP options don't apply to a Unix socket
+        if let Some(path) = crate::config::unix_socket_path(host) {
+            if tls.is_some() {
+                return Err(NodeError::VoteServer(
+                    "TLS is not supported on a Unix socket vote host".into(),
+                ));
+            }
+            return Self::bind_unix(router, path);
+        }
+
+        let bind_error = |e: std::io::Error| match e.kind() {
+            std::io::ErrorKind::AddrInUse => NodeError::PortInUse {
+                host: host.to_string(),
+                port,
+            },
+            _ => NodeError::VoteServer(
+                format!("could not listen on {}:{}: {}", host, port, e).into(),
+            ),
+        };
+        let addr = resolve_bind_addr(host, port, bind.ip_version).map_err(bind_error)?;
+        let listener = crate::socket_options::bind_listener(addr, bind.ipv6_only)
+            .and_then(TcpListener::from_std)
+            .map_err(bind_error)?;
+        let local_addr = listener.local_addr().map_err(bind_error)?;
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+
+        let handle = match tls {
+            None => tokio::spawn(async move {
+                // Connect info is the client address the rate limiter keys on
+                axum::serve(
+                    // A tapped listener gets `SocketAddr` connect info, like a plain TcpListener
+                    LimitedListener::new(listener, accept).tap_io(|_| {}),
+                    router.into_make_service_with_connect_info::<SocketAddr>(),
+                )
+                .with_graceful_shutdown(async {
+                    let _ = shutdown_rx.await;
+                })
+                .await
+            }),
+            Some(tls) => {
+                // axum::serve has no TLS support, so hand the bound socket to axum-server
+                let listener = listener.into_std().map_err(bind_error)?;
+                let server_handle = axum_server::Handle::new();
+                let watcher = server_handle.clone();
+                tokio::spawn(async move {
+                    tokio::spawn(async move {
+                        let _ = shutdown_rx.await;
+                        watcher.graceful_shutdown(None);
+                    });
+                    let acceptor =
+                        RustlsAcceptor::new(RustlsConfig::from_config(tls)).acceptor(accept);
+                    axum_server::from_tcp(listener)
+                        .acceptor(acceptor)
+                        .handle(server_handle)
+                        .serve(router.into_make_service_with_connect_info::<SocketAddr>())
+                        .await
+                })
+            }
+        };
+
+        Ok(Self {
+            local_addr: ListenAddr::Tcp(local_addr),
+            shutdown_tx: Some(shutdown_tx),
+            handle,
+        })
+    }
+
+    /// Serve `router` on a Unix domain socket at `path`, replacing a stale socket file left
+    /// by an earlier run and removing the socket again once the server stops
+    #[cfg(unix)]
+    fn bind_unix(router: Router, path: &Path) -> Result<Self, NodeError> {
+        let bind_error = |e: std::io::Error| {
+            NodeError::VoteServer(
+                format!(
+                    "could not listen on {}: {}",
+                    ListenAddr::Unix(path.into()),
+                    e
+                )
+                .into(),
+            )
+        };
+        remove_stale_socket(path).map_err(bind_error)?;
+        let listener = tokio::net::UnixListener::bind(path).map_err(bind_error)?;
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+
+        // Socket peers have no IP address, and they are all on this host, so the rate
+        // limiter and access log see them as one loopback client
+        let router = router.layer(axum::extract::connect_info::MockConnectInfo(
+            SocketAddr::from((std::net::Ipv4Addr::LOCALHOST, 0)),
+        ));
+        let socket = path.to_path_buf();
+        let handle = tokio::spawn(async move {
+            let served = axum::serve(listener, router.into_make_service())
+                .with_graceful_shutdown(async {
+                    let _ = shutdown_rx.await;
+                })
+                .await;
+            // Don't leave a socket behind that nothing answers on
+            let _ = std::fs::remove_file(&socket);
+            served
+        });
+
+        Ok(Self {
+            local_addr: ListenAddr::Unix(path.to_path_buf()),
+            shutdown_tx: Some(shutdown_tx),
+            handle,
+        })
+    }
+
+    #[cfg(not(unix))]
+    fn bind_unix(_router: Router, path: &Path) -> Result<Self, NodeError> {
+        Err(NodeError::VoteServer(
+            format!(
+                "could not listen on {}: Unix sockets are not supported on this platform",
+                ListenAddr::Unix(path.into())
+            )
+            .into(),
+        ))
+    }
+
+    /// Address the server is listening on
+    pub fn local_addr(&self) -> &ListenAddr {
+        &self.local_addr
+    }
+
+    /// Stop accepting new connections without waiting for in-flight requests
+    fn stop_accepting(&mut self) {
+        // The server task may already be gone, in which case there is nobody to notify
+        if let Some(shutdown_tx) = self.shutdown_tx.take() {
+            let _ = shutdown_tx.send(());
+        }
+    }
+
+    /// Stop accepting connections and wait for in-flight requests to finish
+    pub async fn shutdown(mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.stop_accepting();
+        Ok(self.handle.await??)
+    }
+}
+
+/// Where a vote server listens
+#[derive(Debug, Clone, PartialEq)]
+pub enum ListenAddr {
+    Tcp(SocketAddr),
+    /// A Unix domain socket, from a `unix:` vote host
+    Unix(PathBuf),
+}
+
+impl ListenAddr {
+    /// The TCP address, or None for a Unix socket
+    pub fn tcp(&self) -> Option<SocketAddr> {
+        match self {
+            ListenAddr::Tcp(addr) => Some(*addr),
+            ListenAddr::Unix(_) => None,
+        }
+    }
+
+    /// The TCP port, or None for a Unix socket
+    pub fn port(&self) -> Option<u16> {
+        self.tcp().map(|addr| addr.port())
+    }
+}
+
+impl fmt::Display for ListenAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ListenAddr::Tcp(addr) => write!(f, "{}", addr),
+            ListenAddr::Unix(path) => {
+                write!(f, "{}{}", crate::config::UNIX_SOCKET_PREFIX, path.display())
+            }
+        }
+    }
+}
+
+/// Remove a socket file left behind by a run that didn't shut down cleanly
+/// A socket something still answers on, or a path that isn't a socket, is left alone
+#[cfg(unix)]
+fn remove_stale_socket(path: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+
+    let metadata = match std::fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    if !metadata.file_type().is_socket() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::AlreadyExists,
+            "the path exists and is not a socket",
+        ));
+    }
+    if std::os::unix::net::UnixStream::connect(path).is_ok() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::AddrInUse,
+            "another process is listening on it",
+        ));
+    }
+    std::fs::remove_file(path)
+}
+
+/// Wait for the first of `runners` to exit, returning its index and result
+/// Never resolves for an empty slice
+pub async fn wait_any(
+    runners: &mut [VoteServerRunner],
+) -> (usize, Result<(), Box<dyn std::error::Error>>) {
+    let (index, result) = std::future::poll_fn(|cx| {
+        for (index, runner) in runners.iter_mut().enumerate() {
+            if let Poll::Ready(result) = Pin::new(&mut runner.handle).poll(cx) {
+                return Poll::Ready((index, result));
+            }
+        }
+        Poll::Pending
+    })
+    .await;
+
+    let result = match result {
+        Ok(served) => served.map_err(Into::into),
+        Err(join_error) => Err(join_error.into()),
+    };
+    (index, result)
+}
+
+/// Shut down every runner, draining them concurrently
+/// All listeners stop accepting at once so the grace period isn't spent one server at a time
+pub async fn shutdown_all(
+    mut runners: Vec<VoteServerRunner>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for runner in &mut runners {
+        runner.stop_accepting();
+    }
+    for runner in runners {
+        runner.shutdown().await?;
+    }
+    Ok(())
+}
+
+/// Resolve a bind host (IPv4/IPv6 literal or hostname) to the first address it maps to in
+/// the `ip_version` family. IPv6 literals may be given bare (`::1`) or bracketed (`[::1]`)
+pub fn resolve_bind_addr(
+    host: &str,
+    port: u16,
+    ip_version: IpVersion,
+) -> std::io::Result<SocketAddr> {
+    let resolve_error = |detail: String| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("Could not resolve vote host {:?}: {}", host, detail),
+        )
+    };
+
+    let addrs = (crate::config::strip_ipv6_brackets(host), port)
+        .to_socket_addrs()
+        .map_err(|e| resolve_error(e.to_string()))?
+        .collect::<Vec<_>>();
+    if addrs.is_empty() {
+        return Err(resolve_error("no addresses found".to_string()));
+    }
+    select_addr(&addrs, ip_version).ok_or_else(|| {
+        resolve_error(format!(
+            "no {} address among {} (tvs.ip_version = \"{}\")",
+            match ip_version {
+                IpVersion::V6 => "IPv6",
+                _ => "IPv4",
+            },
+            addrs
+                .iter()
+                .map(|addr| addr.ip().to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            ip_version.as_str()
+        ))
+    })
+}
+
+/// First of the resolved `addrs` in the `ip_version` family, keeping the resolver's order
+pub fn select_addr(addrs: &[SocketAddr], ip_version: IpVersion) -> Option<SocketAddr> {
+    addrs.iter().copied().find(|addr| ip_version.matches(addr))
+}
+
+/// Persistence backend behind the vote service, as seen by the readiness probe
+#[derive(Clone)]
+pub enum VoteBackend {
+    #[cfg_attr(feature = "postgres", allow(dead_code))]
+    Ephemeral,
+    #[cfg(feature = "postgres")]
+    Postgres(tfs_postgres::DbPool),
+}
+
+impl VoteBackend {
+    pub fn name(&self) -> &'static str {
+        match self {
+            VoteBackend::Ephemeral => "ephemeral",
+            #[cfg(feature = "postgres")]
+            VoteBackend::Postgres(_) => "postgres",
+        }
+    }
+
+    /// Check that the backend can serve requests right now
+    pub async fn check(&self) -> Result<(), String> {
+        match self {
+            VoteBackend::Ephemeral => Ok(()),
+            #[cfg(feature = "postgres")]
+            VoteBackend::Postgres(pool) => {
+                // Checking out a connection blocks, so keep it off the async workers
+                let pool = pool.clone();
+                tokio::task::spawn_blocking(move || crate::persistence::ping(&pool))
+                    .await
+                    .map_err(|e| e.to_string())?
+                    .map_err(|e| e.to_string())
+            }
+        }
+    }
+}
+
+/// Orchestration probes served alongside the vote routes; `gate` debounces `/readyz`
+pub fn probe_routes(node: String, backend: VoteBackend, gate: ReadinessGate) -> Router {
+    let ready_node = node.clone();
+    let backend_node = node.clone();
+    let backend_name = backend.name();
+    Router::new()
+        .route("/healthz", get(move || healthz(node)))
+        .route("/readyz", get(move || readyz(ready_node, backend, gate)))
+        .route(
+            "/debug/backend",
+            get(move || debug_backend(backend_node, backend_name)),
+        )
+}
+
+/// Liveness: the process is up and serving, regardless of backend state
+async fn healthz(node: String) -> Json<Value> {
+    Json(json!({ "status": "ok", "node": node }))
+}
+
+/// Which persistence backend the vote services were configured with at startup
+async fn debug_backend(node: String, backend: &'static str) -> Json<Value> {
+    Json(json!({ "backend": backend, "node": node }))
+}
+
+/// Readiness: the vote backend is reachable, so traffic can be routed here
+/// The answer only changes once `gate` has seen enough checks in a row; a failed check that
+/// doesn't flip it yet is still reported under `error`
+async fn readyz(
+    node: String,
+    backend: VoteBackend,
+    gate: ReadinessGate,
+) -> (StatusCode, Json<Value>) {
+    let check = backend.check().await;
+    let (status, label) = if gate.observe(check.is_ok()) {
+        (StatusCode::OK, "ready")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "unavailable")
+    };
+
+    let mut body = json!({ "status": label, "node": node, "backend": backend.name() });
+    if let Err(e) = check {
+        body["error"] = Value::String(e);
+    }
+    (status, Json(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The probes for `tvs_node_1`, with `/readyz` following every check
+    fn probes(backend: VoteBackend) -> Router {
+        probe_routes(
+            "tvs_node_1".to_string(),
+            backend,
+            ReadinessGate::new(crate::config::ReadinessConfig::default()),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_serve_and_shutdown() {
+        let router = Router::new().route("/ping", get(|| async { "pong" }));
+        let runner = VoteServerRunner::serve(router, "127.0.0.1", 0)
+            .await
+            .unwrap();
+        let url = format!("http://{}/ping", runner.local_addr());
+
+        let body = reqwest::get(&url).await.unwrap().text().await.unwrap();
+        assert_eq!(body, "pong");
+
+        runner.shutdown().await.unwrap();
+        assert!(reqwest::get(&url).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_serve_on_ipv4_loopback() {
+        let runner = VoteServerRunner::serve(Router::new(), "127.0.0.1", 0)
+            .await
+            .unwrap();
+        assert!(runner.local_addr().tcp().unwrap().ip().is_loopback());
+        assert!(runner.local_addr().tcp().unwrap().is_ipv4());
+        runner.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_serve_on_ipv6_loopback() {
+        for host in ["::1", "[::1]"] {
+            let runner = VoteServerRunner::serve(Router::new(), host, 0)
+                .await
+                .unwrap();
+            assert!(runner.local_addr().tcp().unwrap().ip().is_loopback());
+            assert!(runner.local_addr().tcp().unwrap().is_ipv6());
+            runner.shutdown().await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ipv6_only_refuses_ipv4_clients() {
+        // Dual-stack, whichever way the OS defaults, and then IPv6 only
+        for ipv6_only in [false, true] {
+            let bind = BindOptions {
+                ip_version: IpVersion::Any,
+                ipv6_only,
+            };
+            let router = Router::new().route("/ping", get(|| async { "pong" }));
+            let runner = VoteServerRunner::serve_on_free_port(
+                router,
+                "::",
+                0,
+                None,
+                false,
+                bind,
+                AcceptPolicy::default(),
+            )
+            .await
+            .unwrap();
+            let port = runner.local_addr().port().unwrap();
+
+            let ipv4 = reqwest::get(format!("http://127.0.0.1:{}/ping", port)).await;
+            assert_eq!(ipv4.is_ok(), !ipv6_only, "ipv6_only = {}", ipv6_only);
+            let ipv6 = reqwest::get(format!("http://[::1]:{}/ping", port)).await;
+            assert_eq!(ipv6.unwrap().text().await.unwrap(), "pong");
+
+            runner.shutdown().await.unwrap();
+        }
+    }
+
+    #[test]
+    fn test_resolve_bind_addr() {
+        assert_eq!(
+            resolve_bind_addr("::", 8090, IpVersion::Any).unwrap(),
+            "[::]:8090".parse::<SocketAddr>().unwrap()
+        );
+        assert!(resolve_bind_addr("localhost", 8090, IpVersion::Any)
+            .unwrap()
+            .ip()
+            .is_loopback());
+
+        let err = resolve_bind_addr("127.0.0.1", 8090, IpVersion::V6).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Could not resolve vote host \"127.0.0.1\": no IPv6 address among 127.0.0.1 \
+             (tvs.ip_version = \"v6\")"
+        );
+    }
+
+    #[test]
+    fn test_select_addr_by_ip_version() {
+        let addrs: Vec<SocketAddr> = [
+            "[2001:db8::1]:8090",
+            "192.0.2.1:8090",
+            "[::1]:8090",
+            "192.0.2.2:8090",
+        ]
+        .iter()
+        .map(|addr| addr.parse().unwrap())
+        .collect();
+
+        assert_eq!(select_addr(&addrs, IpVersion::Any), Some(addrs[0]));
+        assert_eq!(select_addr(&addrs, IpVersion::V4), Some(addrs[1]));
+        assert_eq!(select_addr(&addrs, IpVersion::V6), Some(addrs[0]));
+
+        let v4_only = &addrs[1..2];
+        assert_eq!(select_addr(v4_only, IpVersion::V6), None);
+        assert_eq!(select_addr(v4_only, IpVersion::Any), Some(addrs[1]));
+        assert_eq!(select_addr(&[], IpVersion::Any), None);
+    }
+
+    #[tokio::test]
+    async fn test_serve_rejects_invalid_host() {
+        let err = VoteServerRunner::serve(Router::new(), "not a host!", 0)
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(err, NodeError::VoteServer(_)));
+        assert!(err.to_string().contains("\"not a host!\""));
+    }
+
+    #[tokio::test]
+    async fn test_serve_port_in_use() {
+        let taken = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = taken.local_addr().unwrap().port();
+
+        let err = VoteServerRunner::serve(Router::new(), "127.0.0.1", port)
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(
+            err,
+            NodeError::PortInUse { ref host, port: p } if host == "127.0.0.1" && p == port
+        ));
+        assert_eq!(err.exit_code(), 5);
+        assert!(err.to_string().contains(&format!("127.0.0.1:{}", port)));
+        assert!(err.to_string().contains("--auto-port"));
+    }
+
+    #[tokio::test]
+    async fn test_serve_on_free_port_without_auto_port() {
+        let taken = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = taken.local_addr().unwrap().port();
+
+        let err = VoteServerRunner::serve_on_free_port(
+            Router::new(),
+            "127.0.0.1",
+            port,
+            None,
+            false,
+            BindOptions::default(),
+            AcceptPolicy::default(),
+        )
+        .await
+        .err()
+        .unwrap();
+        assert!(matches!(err, NodeError::PortInUse { port: p, .. } if p == port));
+    }
+
+    #[tokio::test]
+    async fn test_serve_on_free_port_skips_taken_port() {
+        let taken = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = taken.local_addr().unwrap().port();
+
+        let runner = VoteServerRunner::serve_on_free_port(
+            Router::new(),
+            "127.0.0.1",
+            port,
+            None,
+            true,
+            BindOptions::default(),
+            AcceptPolicy::default(),
+        )
+        .await
+        .unwrap();
+        assert!(runner.local_addr().port().unwrap() > port);
+
+        runner.shutdown().await.unwrap();
+    }
+
+    #[cfg(unix)]
+    fn socket_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("tvs_node_{}_{}.sock", name, std::process::id()))
+    }
+
+    /// GET `path` over the Unix socket at `socket`, returning the raw response
+    #[cfg(unix)]
+    async fn unix_get(socket: &Path, path: &str) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let mut stream = tokio::net::UnixStream::connect(socket).await.unwrap();
+        let request = format!(
+            "GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+            path
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_serve_on_unix_socket() {
+        let socket = socket_path("serve");
+        let host = format!("unix:{}", socket.display());
+        // The rate limiter needs a client address, so this also checks socket peers get one
+        let router = crate::rate_limit::apply(
+            Router::new().route("/ping", get(|| async { "pong" })),
+            crate::config::RateLimitConfig::new(10, 10),
+        );
+
+        let runner = VoteServerRunner::serve(router, &host, 0).await.unwrap();
+        assert_eq!(runner.local_addr(), &ListenAddr::Unix(socket.clone()));
+        assert_eq!(runner.local_addr().to_string(), host);
+        assert_eq!(runner.local_addr().port(), None);
+
+        let response = unix_get(&socket, "/ping").await;
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+        assert!(response.ends_with("pong"));
+
+        runner.shutdown().await.unwrap();
+        assert!(!socket.exists());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_unix_socket_replaces_stale_file() {
+        let socket = socket_path("stale");
+        // A listener that is dropped without removing its file leaves a stale socket
+        drop(std::os::unix::net::UnixListener::bind(&socket).unwrap());
+        assert!(socket.exists());
+
+        let runner =
+            VoteServerRunner::serve(Router::new(), &format!("unix:{}", socket.display()), 0)
+                .await
+                .unwrap();
+        runner.shutdown().await.unwrap();
+        assert!(!socket.exists());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_unix_socket_leaves_other_files_alone() {
+        let host_for = |path: &Path| format!("unix:{}", path.display());
+
+        let file = socket_path("regular");
+        std::fs::write(&file, "not a socket").unwrap();
+        let err = VoteServerRunner::serve(Router::new(), &host_for(&file), 0)
+            .await
+            .err()
+            .unwrap();
+        let contents = std::fs::read_to_string(&file);
+        let _ = std::fs::remove_file(&file);
+        assert!(err.to_string().contains("is not a socket"), "{}", err);
+        assert_eq!(contents.unwrap(), "not a socket");
+
+        let live = socket_path("live");
+        let first = VoteServerRunner::serve(Router::new(), &host_for(&live), 0)
+            .await
+            .unwrap();
+        let err = VoteServerRunner::serve(Router::new(), &host_for(&live), 0)
+            .await
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("another process"), "{}", err);
+        first.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_all_listeners() {
+        let router = Router::new().route("/ping", get(|| async { "pong" }));
+        let mut runners = Vec::new();
+        for _ in 0..2 {
+            runners.push(
+                VoteServerRunner::serve(router.clone(), "127.0.0.1", 0)
+                    .await
+                    .unwrap(),
+            );
+        }
+        let urls: Vec<String> = runners
+            .iter()
+            .map(|runner| format!("http://{}/ping", runner.local_addr()))
+            .collect();
+
+        for url in &urls {
+            assert_eq!(
+                reqwest::get(url).await.unwrap().text().await.unwrap(),
+                "pong"
+            );
+        }
+
+        shutdown_all(runners).await.unwrap();
+        for url in &urls {
+            assert!(reqwest::get(url).await.is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wait_any_reports_exited_listener() {
+        let mut runners = Vec::new();
+        for _ in 0..2 {
+            runners.push(
+                VoteServerRunner::serve(Router::new(), "127.0.0.1", 0)
+                    .await
+                    .unwrap(),
+            );
+        }
+        // Simulate the second listener's task dying
+        runners[1].handle.abort();
+
+        let (index, result) = wait_any(&mut runners).await;
+        assert_eq!(index, 1);
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_binds_bind_address_not_vote_host() {
+        let config: crate::config::TvsNodeConfig = serde_json::from_value(json!({
+            "server": {"cluster_message_port": 8080, "app_port": 8081, "admin_port": 8082},
+            "tvs": {"vote_host": "votes.example.invalid", "bind_address": "127.0.0.1", "vote_port": 0}
+        }))
+        .unwrap();
+        let listener = config.vote_listeners().remove(0);
+
+        // The advertised host doesn't resolve, so this only works by binding bind_address
+        let runner = VoteServerRunner::serve(
+            probes(VoteBackend::Ephemeral),
+            &listener.host,
+            listener.port,
+        )
+        .await
+        .unwrap();
+        assert!(runner.local_addr().tcp().unwrap().ip().is_loopback());
+        assert_eq!(
+            config.tvs_config().unwrap().vote_host,
+            "votes.example.invalid"
+        );
+
+        runner.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_healthz() {
+        let runner = VoteServerRunner::serve(probes(VoteBackend::Ephemeral), "127.0.0.1", 0)
+            .await
+            .unwrap();
+        let url = format!("http://{}/healthz", runner.local_addr());
+
+        let response = reqwest::get(&url).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(body, json!({ "status": "ok", "node": "tvs_node_1" }));
+
+        runner.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_readyz_ephemeral() {
+        let router = probes(VoteBackend::Ephemeral);
+        let runner = VoteServerRunner::serve(router, "127.0.0.1", 0)
+            .await
+            .unwrap();
+        let url = format!("http://{}/readyz", runner.local_addr());
+
+        let response = reqwest::get(&url).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(body["status"], "ready");
+        assert_eq!(body["backend"], "ephemeral");
+
+        runner.shutdown().await.unwrap();
+    }
+
+    #[cfg(not(feature = "postgres"))]
+    #[tokio::test]
+    async fn test_debug_backend_ephemeral() {
+        let router = probes(VoteBackend::Ephemeral);
+        let runner = VoteServerRunner::serve(router, "127.0.0.1", 0)
+            .await
+            .unwrap();
+        let url = format!("http://{}/debug/backend", runner.local_addr());
+
+        let response = reqwest::get(&url).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(
+            body,
+            json!({ "backend": "ephemeral", "node": "tvs_node_1" })
+        );
+
+        runner.shutdown().await.unwrap();
+    }
+
+    #[cfg(feature = "postgres")]
+    #[tokio::test]
+    async fn test_readyz_postgres_unreachable() {
+        use diesel::{r2d2::ConnectionManager, PgConnection};
+
+        // Nothing listens on port 1, so every checkout fails quickly
+        let manager =
+            ConnectionManager::<PgConnection>::new("postgres://tvs@127.0.0.1:1/tfs_tvs_db");
+        let pool = tfs_postgres::DbPool::builder()
+            .connection_timeout(std::time::Duration::from_millis(500))
+            .build_unchecked(manager);
+
+        let router = probes(VoteBackend::Postgres(pool));
+        let runner = VoteServerRunner::serve(router, "127.0.0.1", 0)
+            .await
+            .unwrap();
+        let url = format!("http://{}/readyz", runner.local_addr());
+
+        let response = reqwest::get(&url).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::SERVICE_UNAVAILABLE);
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(body["status"], "unavailable");
+        assert_eq!(body["backend"], "postgres");
+
+        runner.shutdown().await.unwrap();
+    }
+
+    #[cfg(feature = "postgres")]
+    #[tokio::test]
+    async fn test_readyz_waits_for_failure_threshold() {
+        use diesel::{r2d2::ConnectionManager, PgConnection};
+
+        let manager =
+            ConnectionManager::<PgConnection>::new("postgres://tvs@127.0.0.1:1/tfs_tvs_db");
+        let pool = tfs_postgres::DbPool::builder()
+            .connection_timeout(std::time::Duration::from_millis(500))
+            .build_unchecked(manager);
+        let gate = ReadinessGate::new(crate::config::ReadinessConfig {
+            failure_threshold: 2,
+            success_threshold: 1,
+        });
+        let router = probe_routes("tvs_node_1".to_string(), VoteBackend::Postgres(pool), gate);
+        let runner = VoteServerRunner::serve(router, "127.0.0.1", 0)
+            .await
+            .unwrap();
+        let url = format!("http://{}/readyz", runner.local_addr());
+
+        // The first failed check is reported, but the node stays ready
+        let response = reqwest::get(&url).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(body["status"], "ready");
+        assert!(body["error"].is_string(), "{}", body);
+
+        let response = reqwest::get(&url).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::SERVICE_UNAVAILABLE);
+
+        runner.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_drains_in_flight_request() {
+        let router = Router::new().route(
+            "/slow",
+            get(|| async {
+                tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+                "done"
+            }),
+        );
+        let runner = VoteServerRunner::serve(router, "127.0.0.1", 0)
+            .await
+            .unwrap();
+        let url = format!("http://{}/slow", runner.local_addr());
+
+        let request = tokio::spawn(async move { reqwest::get(&url).await?.text().await });
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+        runner.shutdown().await.unwrap();
+        assert_eq!(request.await.unwrap().unwrap(), "done");
+    }
+}